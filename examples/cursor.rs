@@ -0,0 +1,12 @@
+use xcap::{cursor_image, cursor_position, Monitor};
+
+fn main() {
+    let (x, y) = cursor_position().unwrap();
+    let monitor = Monitor::from_point(x, y).unwrap();
+    println!("Cursor: ({}, {}) on {:?}", x, y, monitor.name());
+
+    let (image, hotspot_x, hotspot_y) = cursor_image().unwrap();
+    println!("Cursor hotspot: ({}, {})", hotspot_x, hotspot_y);
+
+    image.save("target/cursor.png").unwrap();
+}
@@ -8,11 +8,12 @@ fn main() {
 
     for monitor in monitors {
         println!(
-            "Monitor:\n id: {}\n name: {}\n position: {:?}\n size: {:?}\n state:{:?}\n",
+            "Monitor:\n id: {}\n name: {}\n position: {:?}\n size: {:?}\n work_area: {:?}\n state:{:?}\n",
             monitor.id().unwrap(),
             monitor.name().unwrap(),
             (monitor.x().unwrap(), monitor.y().unwrap()),
             (monitor.width().unwrap(), monitor.height().unwrap()),
+            monitor.work_area().unwrap(),
             (
                 monitor.rotation().unwrap(),
                 monitor.scale_factor().unwrap(),
@@ -8,10 +8,11 @@ fn main() {
 
     for window in windows.clone() {
         println!(
-            "Window:\n id: {}\n pid: {}\n app_name: {}\n title: {}\n monitor: {}\n position: {:?}\n size {:?}\n state {:?}\n",
+            "Window:\n id: {}\n pid: {}\n app_name: {}\n process_name: {}\n title: {}\n monitor: {}\n position: {:?}\n size {:?}\n state {:?}\n",
             window.id().unwrap(),
             window.pid().unwrap(),
             window.app_name().unwrap(),
+            window.process_name().unwrap(),
             window.title().unwrap(),
             window.current_monitor().unwrap().name().unwrap(),
             (window.x().unwrap(), window.y().unwrap(), window.z().unwrap()),
@@ -0,0 +1,44 @@
+use image::RgbaImage;
+
+/// How the color channels of a capture relate to its alpha channel.
+/// The `image` crate and most encoders expect [`AlphaMode::Straight`], GPU compositors
+/// usually expect [`AlphaMode::Premultiplied`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AlphaMode {
+    /// The color channels are independent of alpha. Opaque captures are always straight.
+    #[default]
+    Straight,
+    /// The color channels are already multiplied by alpha, use [`unpremultiply`] to convert
+    /// them to straight alpha.
+    Premultiplied,
+}
+
+/// Convert an image with premultiplied alpha to straight alpha in place. Fully transparent
+/// and fully opaque pixels are unchanged.
+pub fn unpremultiply(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel.0[3] as u32;
+
+        if alpha == 0 || alpha == 255 {
+            continue;
+        }
+
+        for channel in &mut pixel.0[..3] {
+            // 四舍五入，颜色值超过 alpha 的像素不是合法的预乘像素，截断到 255
+            *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
+
+#[test]
+fn unpremultiply_restores_straight_colors() {
+    let mut image =
+        RgbaImage::from_raw(3, 1, vec![64, 32, 0, 128, 10, 20, 30, 255, 0, 0, 0, 0]).unwrap();
+
+    unpremultiply(&mut image);
+
+    assert_eq!(
+        image.as_raw(),
+        &vec![128, 64, 0, 128, 10, 20, 30, 255, 0, 0, 0, 0]
+    );
+}
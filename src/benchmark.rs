@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use crate::{capture_quality::CaptureQuality, Monitor};
+
+// 每个后端截图的次数，第一次截图不计入，避免初始化的耗时影响结果
+const BENCHMARK_FRAMES: u32 = 5;
+
+/// The timing of one capture backend, see [`benchmark_backends`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendBenchmark {
+    /// The backend name, e.g. `GDI BitBlt` or `ScreenCaptureKit`
+    pub backend: &'static str,
+    /// The quality that selects the backend in
+    /// [`Monitor::capture_image_with_quality`](crate::Monitor::capture_image_with_quality)
+    pub quality: CaptureQuality,
+    /// The number of timed captures
+    pub frames: u32,
+    /// The mean time of one capture
+    pub mean_latency: Duration,
+    /// Captures per second, `1 / mean_latency`
+    pub fps: f32,
+    /// The error of the first failed capture, the timing fields are zero when set
+    pub error: Option<String>,
+}
+
+/// The result of [`benchmark_backends`], one entry per capture backend of the platform.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BenchmarkReport {
+    pub backends: Vec<BackendBenchmark>,
+}
+
+impl BenchmarkReport {
+    /// The backend with the lowest mean latency among those without an error.
+    pub fn fastest(&self) -> Option<&BackendBenchmark> {
+        self.backends
+            .iter()
+            .filter(|backend| backend.error.is_none())
+            .min_by_key(|backend| backend.mean_latency)
+    }
+}
+
+fn benchmark_backend(
+    monitor: &Monitor,
+    backend: &'static str,
+    quality: CaptureQuality,
+) -> BackendBenchmark {
+    let mut benchmark = BackendBenchmark {
+        backend,
+        quality,
+        frames: 0,
+        mean_latency: Duration::ZERO,
+        fps: 0.0,
+        error: None,
+    };
+
+    let mut total = Duration::ZERO;
+
+    for frame in 0..=BENCHMARK_FRAMES {
+        let start = Instant::now();
+
+        if let Err(err) = monitor.capture_image_with_quality(quality) {
+            benchmark.error = Some(err.to_string());
+            return benchmark;
+        }
+
+        if frame > 0 {
+            total += start.elapsed();
+        }
+    }
+
+    benchmark.frames = BENCHMARK_FRAMES;
+    benchmark.mean_latency = total / BENCHMARK_FRAMES;
+    if !benchmark.mean_latency.is_zero() {
+        benchmark.fps = 1.0 / benchmark.mean_latency.as_secs_f32();
+    }
+
+    benchmark
+}
+
+/// Time every capture backend available for `monitor` over a few frames, e.g. to find out why
+/// capture is slow on a machine or to pick the fastest [`CaptureQuality`] at startup. A backend
+/// that fails is reported with its error instead of aborting the benchmark.
+pub fn benchmark_backends(monitor: &Monitor) -> BenchmarkReport {
+    let backends = monitor
+        .impl_monitor
+        .capture_backends()
+        .into_iter()
+        .map(|(backend, quality)| benchmark_backend(monitor, backend, quality))
+        .collect();
+
+    BenchmarkReport { backends }
+}
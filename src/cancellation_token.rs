@@ -0,0 +1,51 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::error::{XCapError, XCapResult};
+
+/// A token to cancel a capture from another thread, see
+/// [`Monitor::capture_image_cancellable`](crate::Monitor::capture_image_cancellable).
+/// Clones share the same state, once cancelled a token stays cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancel the captures using this token
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 已取消时返回 `XCapError::Cancelled`
+    pub(crate) fn check(&self) -> XCapResult<()> {
+        if self.is_cancelled() {
+            return Err(XCapError::Cancelled);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn cancellation_token_is_shared_between_clones() {
+    let token = CancellationToken::new();
+    let cloned = token.clone();
+
+    assert!(token.check().is_ok());
+
+    cloned.cancel();
+
+    assert!(token.is_cancelled());
+    assert!(matches!(token.check(), Err(XCapError::Cancelled)));
+}
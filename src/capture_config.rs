@@ -0,0 +1,46 @@
+use crate::platform::impl_monitor::ImplMonitor;
+
+/// A capture backend, see [`CaptureConfig`]. Backends of other platforms fail with
+/// [`XCapError::Unsupported`](crate::XCapError::Unsupported).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// GDI `BitBlt` on Windows, the default
+    GdiBitBlt,
+    /// DXGI Desktop Duplication on Windows, also captures hardware accelerated content
+    /// that is black with GDI, but only one process per output can use it at a time
+    Dxgi,
+    /// ScreenCaptureKit on MacOS 14.0 and later
+    ScreenCaptureKit,
+    /// `CGWindowListCreateImage` on MacOS
+    CgWindowList,
+    /// `GetImage` on Linux (X11)
+    X11,
+    /// The compositor's D-Bus screenshot on Linux (Wayland)
+    Wayland,
+}
+
+/// How [`Monitor::capture_image_with_config`](crate::Monitor::capture_image_with_config)
+/// captures.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CaptureConfig {
+    /// The backends to try in order, the first one that succeeds is used
+    pub backend_order: Vec<Backend>,
+}
+
+impl CaptureConfig {
+    /// Try `backend_order` in order, e.g. `&[Backend::Dxgi, Backend::GdiBitBlt]`.
+    pub fn with_backend_order(backend_order: &[Backend]) -> CaptureConfig {
+        CaptureConfig {
+            backend_order: backend_order.to_vec(),
+        }
+    }
+}
+
+impl Default for CaptureConfig {
+    /// The backends `capture_image()` uses on the current platform, with the alternatives after them.
+    fn default() -> Self {
+        CaptureConfig {
+            backend_order: ImplMonitor::default_backend_order(),
+        }
+    }
+}
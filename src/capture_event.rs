@@ -0,0 +1,178 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use image::RgbaImage;
+
+use crate::{diagnostic::DiagnosticTarget, error::XCapResult, frame_time::Acceleration};
+
+/// The number of capture attempts [`recent_events`] keeps by default.
+pub const DEFAULT_RECENT_EVENTS_CAPACITY: usize = 64;
+
+static RECENT_EVENTS_CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_RECENT_EVENTS_CAPACITY);
+static RECENT_EVENTS: Mutex<VecDeque<CaptureEvent>> = Mutex::new(VecDeque::new());
+
+/// A capture attempt of `Monitor::capture_image` or `Window::capture_image`, see
+/// [`recent_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureEvent {
+    pub target: DiagnosticTarget,
+    /// The captured API, e.g. `Monitor::capture_image`
+    pub operation: &'static str,
+    /// The capture path, `None` when the platform doesn't tell it (window captures)
+    pub acceleration: Option<Acceleration>,
+    /// The image size, 0 when the capture failed
+    pub width: u32,
+    pub height: u32,
+    pub started_at: SystemTime,
+    pub duration: Duration,
+    /// The error message when the capture failed
+    pub error: Option<String>,
+}
+
+impl fmt::Display for CaptureEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since_epoch = self
+            .started_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        write!(
+            f,
+            "[{}.{:03}] {}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_millis(),
+            self.operation
+        )?;
+
+        match self.target {
+            DiagnosticTarget::Monitor(id) => write!(f, " monitor {}", id)?,
+            DiagnosticTarget::Window(id) => write!(f, " window {}", id)?,
+            DiagnosticTarget::Process(pid) => write!(f, " process {}", pid)?,
+            DiagnosticTarget::None => {}
+        }
+
+        if let Some(acceleration) = self.acceleration {
+            write!(f, " ({:?})", acceleration)?;
+        }
+
+        write!(f, " in {:?}: ", self.duration)?;
+
+        match &self.error {
+            Some(error) => write!(f, "failed, {}", error),
+            None => write!(f, "{}x{}", self.width, self.height),
+        }
+    }
+}
+
+/// The last capture attempts, oldest first. Always recorded in memory, attach them (formatted
+/// with `Display`) to bug reports about intermittent failures or black frames.
+pub fn recent_events() -> Vec<CaptureEvent> {
+    match RECENT_EVENTS.lock() {
+        Ok(recent_events) => recent_events.iter().cloned().collect(),
+        Err(err) => {
+            log::error!("Get recent events failed: {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// Set how many capture attempts [`recent_events`] keeps, 0 stops recording.
+/// Defaults to [`DEFAULT_RECENT_EVENTS_CAPACITY`].
+pub fn set_recent_events_capacity(capacity: usize) {
+    RECENT_EVENTS_CAPACITY.store(capacity, Ordering::Relaxed);
+
+    match RECENT_EVENTS.lock() {
+        Ok(mut recent_events) => truncate_events(&mut recent_events, capacity),
+        Err(err) => log::error!("Set recent events capacity failed: {}", err),
+    }
+}
+
+fn truncate_events(recent_events: &mut VecDeque<CaptureEvent>, capacity: usize) {
+    while recent_events.len() > capacity {
+        recent_events.pop_front();
+    }
+}
+
+fn push_event(event: CaptureEvent) {
+    let capacity = RECENT_EVENTS_CAPACITY.load(Ordering::Relaxed);
+    if capacity == 0 {
+        return;
+    }
+
+    if let Ok(mut recent_events) = RECENT_EVENTS.lock() {
+        recent_events.push_back(event);
+        truncate_events(&mut recent_events, capacity);
+    }
+}
+
+/// 执行一次截图并记录到最近的截图事件中
+pub(crate) fn record_capture<F>(
+    target: DiagnosticTarget,
+    operation: &'static str,
+    capture: F,
+) -> XCapResult<RgbaImage>
+where
+    F: FnOnce() -> XCapResult<(RgbaImage, Option<Acceleration>)>,
+{
+    if RECENT_EVENTS_CAPACITY.load(Ordering::Relaxed) == 0 {
+        return capture().map(|(image, _)| image);
+    }
+
+    let started_at = SystemTime::now();
+    let start = Instant::now();
+    let result = capture();
+
+    let mut event = CaptureEvent {
+        target,
+        operation,
+        acceleration: None,
+        width: 0,
+        height: 0,
+        started_at,
+        duration: start.elapsed(),
+        error: None,
+    };
+
+    match &result {
+        Ok((image, acceleration)) => {
+            event.acceleration = *acceleration;
+            event.width = image.width();
+            event.height = image.height();
+        }
+        Err(err) => event.error = Some(err.to_string()),
+    }
+
+    push_event(event);
+
+    result.map(|(image, _)| image)
+}
+
+#[test]
+fn truncate_events_keeps_newest() {
+    let event = |width| CaptureEvent {
+        target: DiagnosticTarget::Monitor(1),
+        operation: "Monitor::capture_image",
+        acceleration: Some(Acceleration::Software),
+        width,
+        height: 1,
+        started_at: SystemTime::UNIX_EPOCH,
+        duration: Duration::from_millis(5),
+        error: None,
+    };
+
+    let mut recent_events: VecDeque<CaptureEvent> = (0..5).map(event).collect();
+    truncate_events(&mut recent_events, 2);
+
+    assert_eq!(recent_events, VecDeque::from([event(3), event(4)]));
+    assert_eq!(
+        event(3).to_string(),
+        "[0.000] Monitor::capture_image monitor 1 (Software) in 5ms: 3x1"
+    );
+}
@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::{XCapError, XCapResult};
+
+/// The default of [`set_max_capture_pixels`], 100 megapixels.
+pub const DEFAULT_MAX_CAPTURE_PIXELS: u64 = 100_000_000;
+
+static MAX_CAPTURE_PIXELS: AtomicU64 = AtomicU64::new(DEFAULT_MAX_CAPTURE_PIXELS);
+
+/// Set the largest capture, in pixels (width × height), the crate allocates a buffer for.
+/// Larger captures fail with an error before any pixel buffer is allocated, which guards
+/// against windows reporting a bogus, enormous size. Applies to every capture in the process.
+pub fn set_max_capture_pixels(max_pixels: u64) {
+    MAX_CAPTURE_PIXELS.store(max_pixels, Ordering::Relaxed);
+}
+
+/// The limit set with [`set_max_capture_pixels`].
+pub fn max_capture_pixels() -> u64 {
+    MAX_CAPTURE_PIXELS.load(Ordering::Relaxed)
+}
+
+/// 在分配截图缓冲区之前检查尺寸是否超过上限
+pub(crate) fn check_capture_size(width: u32, height: u32) -> XCapResult<()> {
+    let pixels = width as u64 * height as u64;
+    let max_pixels = max_capture_pixels();
+
+    if pixels > max_pixels {
+        return Err(XCapError::new(format!(
+            "Capture size {}x{} exceeds the limit of {} pixels",
+            width, height, max_pixels
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+/// The tradeoff between capture speed and fidelity, used by
+/// [`Monitor::capture_image_with_quality`](crate::Monitor::capture_image_with_quality).
+///
+/// | Quality        | Windows                          | MacOS                                                      | Linux          |
+/// |----------------|----------------------------------|------------------------------------------------------------|----------------|
+/// | `Fast`         | GDI `StretchBlt` to logical size | `CGWindowListCreateImage` at nominal (point) size          | X11 `GetImage` |
+/// | `Balanced`     | GDI `BitBlt`                     | ScreenCaptureKit, or `CGWindowListCreateImage` before 14.0 | X11 `GetImage` |
+/// | `HighFidelity` | GDI `BitBlt`                     | ScreenCaptureKit converted to sRGB                         | X11 `GetImage` |
+///
+/// `Balanced` is the same as `capture_image()`. The levels that have no specific backend on a
+/// platform use the `Balanced` one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CaptureQuality {
+    /// Fewer pixels on scaled (HiDPI) monitors, e.g. for thumbnails.
+    Fast,
+    #[default]
+    Balanced,
+    /// Full resolution with color-managed output where the platform supports it.
+    HighFidelity,
+}
@@ -0,0 +1,95 @@
+use image::{imageops::crop_imm, RgbaImage};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    rect::Rect,
+};
+
+/// One frame of a monitor acquired by
+/// [`Monitor::capture_session`](crate::Monitor::capture_session), the regions sliced from it
+/// are from the same instant. The frame is freed when the session is dropped.
+#[derive(Debug, Clone)]
+pub struct CaptureSession {
+    image: RgbaImage,
+    // 显示器的宽高，和 Monitor::width()/height() 的单位相同
+    width: u32,
+    height: u32,
+}
+
+impl CaptureSession {
+    pub(crate) fn new(image: RgbaImage, width: u32, height: u32) -> CaptureSession {
+        CaptureSession {
+            image,
+            width,
+            height,
+        }
+    }
+
+    /// The whole acquired frame.
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    /// Slice an area from the acquired frame. Like in
+    /// [`Monitor::capture_area`](crate::Monitor::capture_area), `area` is relative to the monitor's top-left corner, in the same units as
+    /// `Monitor::width()`/`Monitor::height()`, and is clamped to the monitor bounds.
+    pub fn region(&self, area: Rect) -> XCapResult<RgbaImage> {
+        let pixel_area = to_pixel_area(area, self.width, self.height, self.image.dimensions())
+            .ok_or_else(|| XCapError::new("Capture area is outside the monitor"))?;
+
+        Ok(crop_imm(
+            &self.image,
+            pixel_area.x as u32,
+            pixel_area.y as u32,
+            pixel_area.width,
+            pixel_area.height,
+        )
+        .to_image())
+    }
+}
+
+/// 显示器坐标的区域换算为截图中的像素区域，截图可能是物理像素
+fn to_pixel_area(
+    area: Rect,
+    width: u32,
+    height: u32,
+    (image_width, image_height): (u32, u32),
+) -> Option<Rect> {
+    let area = Rect::new(0, 0, width, height).intersection(&area)?;
+
+    let scale_x = image_width as f64 / width as f64;
+    let scale_y = image_height as f64 / height as f64;
+
+    // 先换算左上角和右下角再取差值，避免相邻的区域之间出现缝隙
+    let left = (area.x as f64 * scale_x).round() as u32;
+    let top = (area.y as f64 * scale_y).round() as u32;
+    let right = ((area.right() as f64 * scale_x).round() as u32).min(image_width);
+    let bottom = ((area.bottom() as f64 * scale_y).round() as u32).min(image_height);
+
+    if right <= left || bottom <= top {
+        return None;
+    }
+
+    Some(Rect::new(
+        left as i32,
+        top as i32,
+        right - left,
+        bottom - top,
+    ))
+}
+
+#[test]
+fn to_pixel_area_scales_and_clamps() {
+    assert_eq!(
+        to_pixel_area(Rect::new(10, 20, 30, 40), 100, 100, (200, 200)),
+        Some(Rect::new(20, 40, 60, 80))
+    );
+    assert_eq!(
+        to_pixel_area(Rect::new(90, -10, 30, 20), 100, 100, (100, 100)),
+        Some(Rect::new(90, 0, 10, 10))
+    );
+    assert_eq!(
+        to_pixel_area(Rect::new(100, 0, 10, 10), 100, 100, (100, 100)),
+        None
+    );
+}
@@ -0,0 +1,184 @@
+use std::fmt::Debug;
+
+use image::{
+    imageops::{resize, FilterType},
+    RgbaImage,
+};
+
+use crate::{
+    diagnostic::{report, Diagnostic, DiagnosticTarget},
+    error::{XCapError, XCapResult},
+    rect::Rect,
+    Monitor, Window,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptureSourceKind {
+    Monitor,
+    Window,
+}
+
+/// A monitor or window that can be captured, for building source pickers.
+pub trait CaptureSource: Debug {
+    /// The source kind
+    fn kind(&self) -> CaptureSourceKind;
+    /// The source id, unique among sources of the same kind
+    fn id(&self) -> XCapResult<u32>;
+    /// The name to display for the source: monitor name or window title.
+    fn display_name(&self) -> XCapResult<String>;
+    /// The source bounds, the same as `Monitor::bounds`/`Window::bounds`.
+    fn geometry(&self) -> XCapResult<Rect>;
+    /// Capture image of the source
+    fn capture_image(&self) -> XCapResult<RgbaImage>;
+}
+
+impl CaptureSource for Monitor {
+    fn kind(&self) -> CaptureSourceKind {
+        CaptureSourceKind::Monitor
+    }
+
+    fn id(&self) -> XCapResult<u32> {
+        Monitor::id(self)
+    }
+
+    fn display_name(&self) -> XCapResult<String> {
+        self.name()
+    }
+
+    fn geometry(&self) -> XCapResult<Rect> {
+        self.bounds()
+    }
+
+    fn capture_image(&self) -> XCapResult<RgbaImage> {
+        Monitor::capture_image(self)
+    }
+}
+
+impl CaptureSource for Window {
+    fn kind(&self) -> CaptureSourceKind {
+        CaptureSourceKind::Window
+    }
+
+    fn id(&self) -> XCapResult<u32> {
+        Window::id(self)
+    }
+
+    fn display_name(&self) -> XCapResult<String> {
+        let title = self.title()?;
+
+        if title.is_empty() {
+            return self.app_name();
+        }
+
+        Ok(title)
+    }
+
+    fn geometry(&self) -> XCapResult<Rect> {
+        self.bounds()
+    }
+
+    fn capture_image(&self) -> XCapResult<RgbaImage> {
+        Window::capture_image(self)
+    }
+}
+
+/// List all monitors followed by all windows.
+pub fn all_sources() -> XCapResult<Vec<Box<dyn CaptureSource>>> {
+    let mut sources: Vec<Box<dyn CaptureSource>> = Vec::new();
+
+    for monitor in Monitor::all()? {
+        sources.push(Box::new(monitor));
+    }
+
+    for window in Window::all()? {
+        sources.push(Box::new(window));
+    }
+
+    Ok(sources)
+}
+
+/// A capture source with a small preview image, the shape source pickers such as the one of
+/// WebRTC's `getDisplayMedia` need.
+#[derive(Debug, Clone)]
+pub struct SourceInfo {
+    pub kind: CaptureSourceKind,
+    /// The source id, unique among sources of the same kind
+    pub id: u32,
+    /// The name to display for the source: monitor name or window title.
+    pub name: String,
+    /// The source capture scaled down to fit `max_dim`, keeping the aspect ratio
+    pub thumbnail: RgbaImage,
+}
+
+// 缩小到宽高都不超过 max_dim，不放大
+fn thumbnail_size(width: u32, height: u32, max_dim: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= max_dim {
+        return (width, height);
+    }
+
+    let scale = max_dim as f64 / longest as f64;
+
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+fn source_info(source: &dyn CaptureSource, max_dim: u32) -> XCapResult<SourceInfo> {
+    let image = source.capture_image()?;
+    let (width, height) = thumbnail_size(image.width(), image.height(), max_dim);
+
+    let thumbnail = if image.dimensions() == (width, height) {
+        image
+    } else {
+        resize(&image, width, height, FilterType::Triangle)
+    };
+
+    Ok(SourceInfo {
+        kind: source.kind(),
+        id: source.id()?,
+        name: source.display_name()?,
+        thumbnail,
+    })
+}
+
+/// List all monitors followed by all windows like [`all_sources`], each with a thumbnail at
+/// most `max_dim` pixels wide and high. Every source is captured, sources that fail to
+/// capture, e.g. windows closed meanwhile, are reported to the diagnostic hook and skipped.
+pub fn enumerate_sources_with_thumbnails(max_dim: u32) -> XCapResult<Vec<SourceInfo>> {
+    if max_dim == 0 {
+        return Err(XCapError::new("Thumbnail size must not be zero"));
+    }
+
+    let mut source_infos = Vec::new();
+
+    for source in all_sources()? {
+        match source_info(source.as_ref(), max_dim) {
+            Ok(source_info) => source_infos.push(source_info),
+            Err(err) => {
+                let id = source.id().unwrap_or_default();
+                let target = match source.kind() {
+                    CaptureSourceKind::Monitor => DiagnosticTarget::Monitor(id),
+                    CaptureSourceKind::Window => DiagnosticTarget::Window(id),
+                };
+
+                report(Diagnostic::new(
+                    target,
+                    "enumerate_sources_with_thumbnails",
+                    err,
+                ));
+            }
+        }
+    }
+
+    Ok(source_infos)
+}
+
+#[test]
+fn thumbnail_size_keeps_aspect_ratio() {
+    assert_eq!(thumbnail_size(1920, 1080, 320), (320, 180));
+    assert_eq!(thumbnail_size(100, 4000, 200), (5, 200));
+    assert_eq!(thumbnail_size(100, 50, 320), (100, 50));
+    assert_eq!(thumbnail_size(1000, 1, 10), (10, 1));
+}
@@ -0,0 +1,52 @@
+use image::RgbaImage;
+
+/// Why a captured image is likely not showing the real screen content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptureWarning {
+    /// A secure desktop (UAC prompt, lock or login screen) is active, which
+    /// can't be captured by normal processes. Only detected on Windows.
+    SecureDesktop,
+    /// The image is fully black, the content is likely protected (e.g. DRM video)
+    /// or otherwise hidden from capture.
+    ProtectedContent,
+    /// The window image is fully black, the window is likely drawn by the GPU on a hardware
+    /// overlay (e.g. games or video players) that the window capture can't read. Capturing
+    /// the monitor and cropping it to the window bounds usually works.
+    LikelyHardwareOverlay,
+}
+
+pub(crate) fn is_black_image(image: &RgbaImage) -> bool {
+    image
+        .as_raw()
+        .chunks_exact(4)
+        .all(|rgba| rgba[0] == 0 && rgba[1] == 0 && rgba[2] == 0)
+}
+
+/// Whether all pixels have the same color, checked on a grid of at most 64 x 64 samples
+/// so it stays cheap for large images.
+pub(crate) fn is_uniform_image(image: &RgbaImage) -> bool {
+    let (width, height) = image.dimensions();
+
+    if width == 0 || height == 0 {
+        return true;
+    }
+
+    let step_x = (width / 64).max(1);
+    let step_y = (height / 64).max(1);
+    let first = image.get_pixel(0, 0);
+
+    (0..height).step_by(step_y as usize).all(|y| {
+        (0..width)
+            .step_by(step_x as usize)
+            .all(|x| image.get_pixel(x, y).0[..3] == first.0[..3])
+    })
+}
+
+#[test]
+fn is_uniform_image_detects_content() {
+    let mut image = RgbaImage::new(256, 256);
+    assert!(is_uniform_image(&image));
+
+    image.put_pixel(128, 128, image::Rgba([255, 255, 255, 255]));
+    assert!(!is_uniform_image(&image));
+}
@@ -0,0 +1,43 @@
+use image::{Rgba32FImage, RgbaImage};
+
+use crate::error::{XCapError, XCapResult};
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+
+    // https://www.w3.org/Graphics/Color/srgb
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Decode the sRGB transfer function of the color channels, alpha is kept linear.
+pub(crate) fn to_linear_f32(image: &RgbaImage) -> XCapResult<Rgba32FImage> {
+    let lut: Vec<f32> = (0..=255).map(srgb_to_linear).collect();
+
+    let buffer = image
+        .as_raw()
+        .chunks_exact(4)
+        .flat_map(|rgba| {
+            [
+                lut[rgba[0] as usize],
+                lut[rgba[1] as usize],
+                lut[rgba[2] as usize],
+                rgba[3] as f32 / 255.0,
+            ]
+        })
+        .collect();
+
+    Rgba32FImage::from_raw(image.width(), image.height(), buffer)
+        .ok_or_else(|| XCapError::new("Rgba32FImage::from_raw failed"))
+}
+
+#[test]
+fn linear_f32_endpoints() {
+    let image = RgbaImage::from_raw(2, 1, vec![0, 0, 0, 0, 255, 255, 255, 255]).unwrap();
+    let linear = to_linear_f32(&image).unwrap();
+
+    assert_eq!(linear.as_raw(), &[0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+}
@@ -0,0 +1,93 @@
+use crate::rect::Rect;
+
+/// 截图中的像素坐标转换为屏幕坐标，`scale` 为每个坐标单位对应的像素数
+pub(crate) fn image_to_screen(bounds: Rect, scale: f32, px: u32, py: u32) -> (i32, i32) {
+    (
+        bounds.x + (px as f32 / scale).floor() as i32,
+        bounds.y + (py as f32 / scale).floor() as i32,
+    )
+}
+
+/// 屏幕坐标转换为截图中的像素坐标，不在显示器上时返回 None
+pub(crate) fn screen_to_image(bounds: Rect, scale: f32, x: i32, y: i32) -> Option<(u32, u32)> {
+    if !bounds.contains(x, y) {
+        return None;
+    }
+
+    Some((
+        ((x - bounds.x) as f32 * scale).floor() as u32,
+        ((y - bounds.y) as f32 * scale).floor() as u32,
+    ))
+}
+
+/// 把以点为单位、可能带小数的区域扩展到像素网格上，返回像素坐标的区域，
+/// 截图区域不在像素边界上时会被插值，边缘出现模糊的 1 像素接缝
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn snap_to_pixel_grid(x: f64, y: f64, width: f64, height: f64, scale: f64) -> Rect {
+    // 先四舍五入到 1/1000 像素，避免浮点误差使整数边界多出一个像素
+    let to_pixel = |value: f64| (value * scale * 1000.0).round() / 1000.0;
+
+    let left = to_pixel(x).floor() as i32;
+    let top = to_pixel(y).floor() as i32;
+    let right = to_pixel(x + width).ceil() as i32;
+    let bottom = to_pixel(y + height).ceil() as i32;
+
+    Rect::new(
+        left,
+        top,
+        (right - left).max(0) as u32,
+        (bottom - top).max(0) as u32,
+    )
+}
+
+/// 以点为单位的全局坐标转换为整数坐标。主显示器上方和左侧的显示器坐标为负数，
+/// 直接截断会向 0 取整，使窗口偏移 1 个点，所以向下取整
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn global_point_to_i32(x: f64, y: f64) -> (i32, i32) {
+    (x.floor() as i32, y.floor() as i32)
+}
+
+/// AppKit 的坐标原点在主显示器左下角，y 轴向上。转换为 Core Graphics 的全局坐标，
+/// 原点在主显示器左上角，y 轴向下，和 Windows、Linux 一致
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn appkit_to_global_y(y: f64, height: f64, main_display_height: f64) -> f64 {
+    main_display_height - (y + height)
+}
+
+#[test]
+fn coordinate_round_trips_on_scaled_monitor() {
+    let bounds = Rect::new(-1440, 100, 1440, 900);
+
+    assert_eq!(image_to_screen(bounds, 2.0, 0, 0), (-1440, 100));
+    assert_eq!(image_to_screen(bounds, 2.0, 301, 51), (-1290, 125));
+    assert_eq!(screen_to_image(bounds, 2.0, -1290, 125), Some((300, 50)));
+    assert_eq!(screen_to_image(bounds, 2.0, 0, 125), None);
+}
+
+#[test]
+fn snap_to_pixel_grid_covers_fractional_points() {
+    assert_eq!(
+        snap_to_pixel_grid(10.25, -20.5, 100.5, 50.0, 2.0),
+        Rect::new(20, -41, 202, 100)
+    );
+    assert_eq!(
+        snap_to_pixel_grid(0.1, 0.2, 0.3, 0.4, 10.0),
+        Rect::new(1, 2, 3, 4)
+    );
+}
+
+#[test]
+fn global_point_to_i32_floors_negative_coordinates() {
+    // 主显示器上方的显示器上的窗口
+    assert_eq!(global_point_to_i32(10.5, -1000.5), (10, -1001));
+    assert_eq!(global_point_to_i32(-0.25, 0.0), (-1, 0));
+}
+
+#[test]
+fn appkit_to_global_y_flips_around_main_display() {
+    // 主显示器高 900，上方的显示器高 1080，AppKit 中原点 y 为 900
+    assert_eq!(appkit_to_global_y(900.0, 1080.0, 900.0), -1080.0);
+    // 主显示器下方的显示器
+    assert_eq!(appkit_to_global_y(-768.0, 768.0, 900.0), 900.0);
+    assert_eq!(appkit_to_global_y(0.0, 900.0, 900.0), 0.0);
+}
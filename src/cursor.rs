@@ -0,0 +1,70 @@
+use image::RgbaImage;
+
+use crate::{error::XCapResult, platform::cursor};
+
+/// The mouse cursor position, in the same coordinates as [`Monitor::from_point`](crate::Monitor::from_point),
+/// which can be used to get the monitor the cursor is on.
+pub fn cursor_position() -> XCapResult<(i32, i32)> {
+    cursor::get_cursor_position()
+}
+
+/// The current mouse cursor image, with the (x, y) hotspot relative to the image top-left corner.
+pub fn cursor_image() -> XCapResult<(RgbaImage, i32, i32)> {
+    cursor::get_cursor_image()
+}
+
+/// Draw `cursor` on `frame` at each of `positions` (the cursor image's top-left corner, in
+/// frame pixels), ordered from the oldest to the newest. The newest position is drawn fully
+/// opaque and older ones fade out linearly, which shows the cursor motion between frames.
+pub fn composite_cursor_path(frame: &mut RgbaImage, positions: &[(i32, i32)], cursor: &RgbaImage) {
+    let count = positions.len();
+
+    for (index, &(x, y)) in positions.iter().enumerate() {
+        let opacity = (index + 1) as f32 / count as f32;
+        blend_image(frame, cursor, x, y, opacity);
+    }
+}
+
+fn blend_image(frame: &mut RgbaImage, image: &RgbaImage, x: i32, y: i32, opacity: f32) {
+    for (image_x, image_y, pixel) in image.enumerate_pixels() {
+        let frame_x = x + image_x as i32;
+        let frame_y = y + image_y as i32;
+
+        if frame_x < 0
+            || frame_y < 0
+            || frame_x >= frame.width() as i32
+            || frame_y >= frame.height() as i32
+        {
+            continue;
+        }
+
+        let alpha = pixel[3] as f32 / 255.0 * opacity;
+        if alpha == 0.0 {
+            continue;
+        }
+
+        // source-over 混合
+        let frame_pixel = frame.get_pixel_mut(frame_x as u32, frame_y as u32);
+        let frame_alpha = frame_pixel[3] as f32 / 255.0;
+        let out_alpha = alpha + frame_alpha * (1.0 - alpha);
+
+        for i in 0..3 {
+            let color =
+                pixel[i] as f32 * alpha + frame_pixel[i] as f32 * frame_alpha * (1.0 - alpha);
+            frame_pixel[i] = (color / out_alpha).round() as u8;
+        }
+        frame_pixel[3] = (out_alpha * 255.0).round() as u8;
+    }
+}
+
+#[test]
+fn composite_cursor_path_fades_older_positions() {
+    let mut frame = RgbaImage::from_pixel(4, 1, image::Rgba([0, 0, 0, 255]));
+    let cursor = RgbaImage::from_pixel(1, 1, image::Rgba([255, 255, 255, 255]));
+
+    composite_cursor_path(&mut frame, &[(0, 0), (1, 0), (9, 0)], &cursor);
+
+    assert_eq!(frame.get_pixel(0, 0).0, [85, 85, 85, 255]);
+    assert_eq!(frame.get_pixel(1, 0).0, [170, 170, 170, 255]);
+    assert_eq!(frame.get_pixel(2, 0).0, [0, 0, 0, 255]);
+}
@@ -0,0 +1,65 @@
+use image::RgbaImage;
+
+use crate::{
+    diagnostic::{report, Diagnostic, DiagnosticTarget},
+    error::XCapResult,
+    rect::Rect,
+    Window,
+};
+
+/// A window captured on its own, as returned by [`capture_desktop_layers`].
+#[derive(Debug, Clone)]
+pub struct WindowLayer {
+    /// The captured window
+    pub window: Window,
+    /// The isolated capture of the window, the parts covered by other windows are not included
+    pub image: RgbaImage,
+    /// The window bounds in global coordinates, the image may be larger on HiDPI displays
+    pub bounds: Rect,
+    /// The window z coordinate, see [`Window::z`]
+    pub z: i32,
+}
+
+/// Capture every visible window as a separate layer, sorted bottom to top so drawing the
+/// layers in order at their bounds reassembles the desktop. Minimized and empty windows are
+/// skipped, windows that fail to capture, e.g. because they were closed meanwhile, are
+/// reported to the diagnostic hook and skipped.
+pub fn capture_desktop_layers() -> XCapResult<Vec<WindowLayer>> {
+    let mut layers = Vec::new();
+
+    for window in Window::all()? {
+        // 窗口可能在枚举之后被关闭，任何一步失败都跳过该窗口
+        let layer = (|| -> XCapResult<Option<WindowLayer>> {
+            if window.is_minimized()? {
+                return Ok(None);
+            }
+
+            let bounds = window.bounds()?;
+            if bounds.area() == 0 {
+                return Ok(None);
+            }
+
+            Ok(Some(WindowLayer {
+                image: window.capture_image()?,
+                window: window.clone(),
+                bounds,
+                z: window.z()?,
+            }))
+        })();
+
+        match layer {
+            Ok(Some(layer)) => layers.push(layer),
+            Ok(None) => {}
+            Err(err) => report(Diagnostic::new(
+                DiagnosticTarget::Window(window.id().unwrap_or_default()),
+                "capture_desktop_layers",
+                err,
+            )),
+        }
+    }
+
+    // 从最底层的窗口开始
+    layers.sort_by_key(|layer| layer.z);
+
+    Ok(layers)
+}
@@ -0,0 +1,103 @@
+use std::{fmt, sync::RwLock};
+
+/// What a [`Diagnostic`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticTarget {
+    /// A monitor, by `Monitor::id`
+    Monitor(u32),
+    /// A window, by `Window::id`
+    Window(u32),
+    /// A process, by pid
+    Process(u32),
+    /// Not related to a specific monitor, window or process
+    None,
+}
+
+/// A failure that was handled internally, e.g. by falling back to another API,
+/// or that happened while releasing a platform resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub target: DiagnosticTarget,
+    /// The failed operation, usually the platform API name
+    pub operation: &'static str,
+    pub message: String,
+    /// `GetLastError`/`HRESULT` on Windows, `errno` on Linux, `CGError` on MacOS
+    pub os_error_code: Option<i32>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new<S: ToString>(
+        target: DiagnosticTarget,
+        operation: &'static str,
+        message: S,
+    ) -> Diagnostic {
+        Diagnostic {
+            target,
+            operation,
+            message: message.to_string(),
+            os_error_code: None,
+        }
+    }
+
+    // 目前只有 Windows 的 GDI 调用会附带错误码
+    #[cfg(target_os = "windows")]
+    pub(crate) fn with_os_error_code(mut self, os_error_code: i32) -> Diagnostic {
+        self.os_error_code = Some(os_error_code);
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed", self.operation)?;
+
+        match self.target {
+            DiagnosticTarget::Monitor(id) => write!(f, " for monitor {}", id)?,
+            DiagnosticTarget::Window(id) => write!(f, " for window {}", id)?,
+            DiagnosticTarget::Process(pid) => write!(f, " for process {}", pid)?,
+            DiagnosticTarget::None => {}
+        }
+
+        write!(f, ": {}", self.message)?;
+
+        if let Some(os_error_code) = self.os_error_code {
+            write!(f, " (os error {})", os_error_code)?;
+        }
+
+        Ok(())
+    }
+}
+
+type DiagnosticHook = Box<dyn Fn(&Diagnostic) + Send + Sync>;
+
+static DIAGNOSTIC_HOOK: RwLock<Option<DiagnosticHook>> = RwLock::new(None);
+
+/// Route the handled failures to `hook` instead of `log::error!`.
+pub fn set_diagnostic_hook<F>(hook: F)
+where
+    F: Fn(&Diagnostic) + Send + Sync + 'static,
+{
+    match DIAGNOSTIC_HOOK.write() {
+        Ok(mut diagnostic_hook) => *diagnostic_hook = Some(Box::new(hook)),
+        Err(err) => log::error!("Set diagnostic hook failed: {}", err),
+    }
+}
+
+/// Restore the default behavior of logging the handled failures with `log::error!`.
+pub fn clear_diagnostic_hook() {
+    match DIAGNOSTIC_HOOK.write() {
+        Ok(mut diagnostic_hook) => *diagnostic_hook = None,
+        Err(err) => log::error!("Clear diagnostic hook failed: {}", err),
+    }
+}
+
+pub(crate) fn report(diagnostic: Diagnostic) {
+    if let Ok(diagnostic_hook) = DIAGNOSTIC_HOOK.read() {
+        if let Some(hook) = diagnostic_hook.as_ref() {
+            hook(&diagnostic);
+            return;
+        }
+    }
+
+    log::error!("{}", diagnostic);
+}
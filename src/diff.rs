@@ -0,0 +1,267 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    iter,
+    ops::Range,
+};
+
+use image::{Rgba, RgbaImage};
+
+use crate::rect::Rect;
+
+/// The bounding box of the pixels that differ between the two images, in pixels.
+/// When the dimensions differ the whole current image is returned.
+pub(crate) fn diff_bounding_box(previous: &RgbaImage, current: &RgbaImage) -> Option<Rect> {
+    let (width, height) = current.dimensions();
+
+    if previous.dimensions() != current.dimensions() {
+        return Some(Rect::new(0, 0, width, height));
+    }
+
+    let row_len = width as usize * 4;
+    let previous_rows = previous.as_raw().chunks_exact(row_len);
+    let current_rows = current.as_raw().chunks_exact(row_len);
+
+    let (mut left, mut top) = (width, height);
+    let (mut right, mut bottom) = (0, 0);
+
+    for (y, (previous_row, current_row)) in previous_rows.zip(current_rows).enumerate() {
+        // 先整行比较，大部分行没有变化，不需要逐像素比较
+        if previous_row == current_row {
+            continue;
+        }
+
+        let changed_pixels = previous_row
+            .chunks_exact(4)
+            .zip(current_row.chunks_exact(4))
+            .enumerate()
+            .filter(|(_, (previous_pixel, current_pixel))| previous_pixel != current_pixel)
+            .map(|(x, _)| x as u32);
+
+        for x in changed_pixels {
+            left = left.min(x);
+            right = right.max(x + 1);
+        }
+
+        top = top.min(y as u32);
+        bottom = y as u32 + 1;
+    }
+
+    if right <= left {
+        return None;
+    }
+
+    Some(Rect::new(
+        left as i32,
+        top as i32,
+        right - left,
+        bottom - top,
+    ))
+}
+
+/// 每一行像素的哈希
+pub(crate) fn row_hashes(image: &RgbaImage) -> Vec<u64> {
+    image
+        .as_raw()
+        .chunks_exact(image.width() as usize * 4)
+        .map(|row| {
+            let mut hasher = DefaultHasher::new();
+            row.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// The ranges of rows that differ between the two images, merged when adjacent, in pixels.
+/// When the dimensions differ all rows of the current image are returned.
+pub(crate) fn changed_row_ranges(previous: &RgbaImage, current: &RgbaImage) -> Vec<Range<u32>> {
+    let (width, height) = current.dimensions();
+
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    if previous.dimensions() != current.dimensions() {
+        // 整个图像作为一段，不是 0 到 height 的每一行
+        return iter::once(0..height).collect();
+    }
+
+    let previous_hashes = row_hashes(previous);
+    let current_hashes = row_hashes(current);
+
+    let mut ranges: Vec<Range<u32>> = Vec::new();
+
+    for (y, (previous_hash, current_hash)) in
+        previous_hashes.iter().zip(&current_hashes).enumerate()
+    {
+        if previous_hash == current_hash {
+            continue;
+        }
+
+        let y = y as u32;
+        match ranges.last_mut() {
+            // 和上一段相邻时合并
+            Some(range) if range.end == y => range.end = y + 1,
+            _ => ranges.push(y..y + 1),
+        }
+    }
+
+    ranges
+}
+
+/// Whether the pixels within `band` pixels of any edge are the same in both images.
+/// Images with different dimensions never match.
+pub(crate) fn borders_match(previous: &RgbaImage, current: &RgbaImage, band: u32) -> bool {
+    let (width, height) = current.dimensions();
+
+    if previous.dimensions() != current.dimensions() {
+        return false;
+    }
+
+    let row_len = width as usize * 4;
+    let side_len = band.min(width) as usize * 4;
+    let previous_rows = previous.as_raw().chunks_exact(row_len);
+    let current_rows = current.as_raw().chunks_exact(row_len);
+
+    previous_rows
+        .zip(current_rows)
+        .enumerate()
+        .all(|(y, (previous_row, current_row))| {
+            let y = y as u32;
+            // 上下边框比较整行，其余行只比较左右两侧
+            if y < band || y >= height.saturating_sub(band) {
+                return previous_row == current_row;
+            }
+
+            previous_row[..side_len] == current_row[..side_len]
+                && previous_row[row_len - side_len..] == current_row[row_len - side_len..]
+        })
+}
+
+/// The result of [`diff_images`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffResult {
+    /// The number of pixels that differ by more than the threshold
+    pub differing_pixels: u64,
+    /// The bounding box of the differing pixels, in pixels, `None` when the images match
+    pub bounding_box: Option<Rect>,
+    /// The differing pixels in red over a faded grayscale copy of `a`
+    pub visualization: RgbaImage,
+}
+
+// 可视化图像中标记差异的颜色
+const DIFF_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+/// Compare two images pixel by pixel, e.g. the captures of the same UI rendered on two
+/// monitors. A pixel differs when any channel differs by more than `threshold`. When the
+/// dimensions differ, the images are aligned at the top-left corner and the pixels that are
+/// only in one of them count as differing.
+pub fn diff_images(a: &RgbaImage, b: &RgbaImage, threshold: u8) -> DiffResult {
+    let width = a.width().max(b.width());
+    let height = a.height().max(b.height());
+
+    let mut visualization = RgbaImage::new(width, height);
+    let mut differing_pixels = 0;
+    let (mut left, mut top) = (width, height);
+    let (mut right, mut bottom) = (0, 0);
+
+    for (x, y, pixel) in visualization.enumerate_pixels_mut() {
+        let a_pixel = a.get_pixel_checked(x, y);
+        let b_pixel = b.get_pixel_checked(x, y);
+
+        let is_different = match (a_pixel, b_pixel) {
+            (Some(a_pixel), Some(b_pixel)) => a_pixel
+                .0
+                .iter()
+                .zip(b_pixel.0.iter())
+                .any(|(a_value, b_value)| a_value.abs_diff(*b_value) > threshold),
+            _ => true,
+        };
+
+        if !is_different {
+            // 相同的像素显示为淡化的灰度，让差异更明显
+            let [r, g, b, _] = a_pixel.map(|a_pixel| a_pixel.0).unwrap_or_default();
+            let luma = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+            let faded = (255 - (255 - luma) / 3) as u8;
+            *pixel = Rgba([faded, faded, faded, 255]);
+            continue;
+        }
+
+        *pixel = DIFF_COLOR;
+        differing_pixels += 1;
+        left = left.min(x);
+        top = top.min(y);
+        right = right.max(x + 1);
+        bottom = bottom.max(y + 1);
+    }
+
+    let bounding_box = (differing_pixels > 0)
+        .then(|| Rect::new(left as i32, top as i32, right - left, bottom - top));
+
+    DiffResult {
+        differing_pixels,
+        bounding_box,
+        visualization,
+    }
+}
+
+#[test]
+fn diff_bounding_box_covers_changed_pixels() {
+    let previous = RgbaImage::new(8, 6);
+    let mut current = previous.clone();
+
+    assert_eq!(diff_bounding_box(&previous, &current), None);
+
+    current.put_pixel(2, 1, image::Rgba([255, 0, 0, 255]));
+    current.put_pixel(5, 3, image::Rgba([0, 255, 0, 255]));
+
+    assert_eq!(
+        diff_bounding_box(&previous, &current),
+        Some(Rect::new(2, 1, 4, 3))
+    );
+}
+
+#[test]
+fn changed_row_ranges_merges_adjacent_rows() {
+    let previous = RgbaImage::new(4, 8);
+    let mut current = previous.clone();
+    current.put_pixel(0, 1, Rgba([1, 0, 0, 255]));
+    current.put_pixel(3, 2, Rgba([1, 0, 0, 255]));
+    current.put_pixel(2, 6, Rgba([1, 0, 0, 255]));
+
+    assert_eq!(changed_row_ranges(&previous, &current), vec![1..3, 6..7]);
+    assert!(changed_row_ranges(&previous, &previous).is_empty());
+
+    // 尺寸不同时整个图像是一段
+    let resized_ranges = changed_row_ranges(&RgbaImage::new(4, 4), &current);
+    assert_eq!(resized_ranges.len(), 1);
+    assert_eq!(resized_ranges[0], 0..8);
+}
+
+#[test]
+fn borders_match_ignores_changes_inside_the_band() {
+    let previous = RgbaImage::new(10, 10);
+    let mut current = previous.clone();
+
+    current.put_pixel(5, 5, image::Rgba([255, 0, 0, 255]));
+    assert!(borders_match(&previous, &current, 2));
+
+    current.put_pixel(9, 5, image::Rgba([255, 0, 0, 255]));
+    assert!(!borders_match(&previous, &current, 2));
+}
+
+#[test]
+fn diff_images_counts_pixels_above_threshold() {
+    let a = RgbaImage::from_pixel(4, 3, Rgba([100, 100, 100, 255]));
+    let mut b = a.clone();
+
+    b.put_pixel(1, 1, Rgba([104, 100, 100, 255]));
+    b.put_pixel(3, 2, Rgba([120, 100, 100, 255]));
+
+    let result = diff_images(&a, &b, 5);
+
+    assert_eq!(result.differing_pixels, 1);
+    assert_eq!(result.bounding_box, Some(Rect::new(3, 2, 1, 1)));
+    assert_eq!(*result.visualization.get_pixel(3, 2), DIFF_COLOR);
+    assert_ne!(*result.visualization.get_pixel(1, 1), DIFF_COLOR);
+}
@@ -0,0 +1,14 @@
+/// A display mode a monitor supports, see
+/// [`Monitor::available_modes`](crate::Monitor::available_modes).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayMode {
+    /// Width in physical pixels
+    pub width: u32,
+    /// Height in physical pixels
+    pub height: u32,
+    /// Refresh rate in Hz, 0 when the platform doesn't report it
+    pub refresh_rate: f32,
+    /// Bits per pixel
+    pub bit_depth: u32,
+}
@@ -0,0 +1,74 @@
+// MacOS 从 IOKit 读取厂商和序列号，不需要解析 EDID
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+const DESCRIPTOR_OFFSET: usize = 0x36;
+
+/// Format the EDID manufacturer, product code and serial number as a stable id,
+/// e.g. `DEL-4093-ABC123`.
+pub(crate) fn format_edid_id(manufacturer: u16, product: u16, serial: &str) -> String {
+    // 厂商 id 是 3 个 5 位的字母，1 表示 A
+    let manufacturer: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (((manufacturer >> shift) & 0x1F) as u8 + b'A' - 1) as char)
+        .collect();
+
+    format!("{}-{:04X}-{}", manufacturer, product, serial)
+}
+
+/// Parse the id of the monitor from the EDID, returns `None` if the EDID is invalid
+/// or doesn't contain a serial number.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+pub(crate) fn parse_edid_id(edid: &[u8]) -> Option<String> {
+    if edid.len() < 128 || edid[0..8] != EDID_HEADER {
+        return None;
+    }
+
+    let manufacturer = u16::from_be_bytes([edid[8], edid[9]]);
+    let product = u16::from_le_bytes([edid[10], edid[11]]);
+
+    // 优先使用描述符中的序列号字符串 (0xFF)，其次使用数字序列号
+    let serial_text = (0..4).find_map(|i| {
+        let offset = DESCRIPTOR_OFFSET + i * 18;
+        let descriptor = &edid[offset..offset + 18];
+        if descriptor[0..3] != [0, 0, 0] || descriptor[3] != 0xFF {
+            return None;
+        }
+
+        let text = String::from_utf8_lossy(&descriptor[5..]);
+        let serial = text.split('\n').next().unwrap_or_default().trim();
+
+        (!serial.is_empty()).then(|| serial.to_string())
+    });
+
+    let serial = match serial_text {
+        Some(serial) => serial,
+        None => {
+            let serial = u32::from_le_bytes([edid[12], edid[13], edid[14], edid[15]]);
+            if serial == 0 {
+                return None;
+            }
+            serial.to_string()
+        }
+    };
+
+    Some(format_edid_id(manufacturer, product, &serial))
+}
+
+#[test]
+fn parse_edid_id_prefers_serial_descriptor() {
+    let mut edid = vec![0u8; 128];
+    edid[0..8].copy_from_slice(&EDID_HEADER);
+    // DEL
+    edid[8..10].copy_from_slice(&0x10ACu16.to_be_bytes());
+    edid[10..12].copy_from_slice(&0x4093u16.to_le_bytes());
+    edid[12..16].copy_from_slice(&1234u32.to_le_bytes());
+
+    assert_eq!(parse_edid_id(&edid), Some("DEL-4093-1234".to_string()));
+
+    edid[DESCRIPTOR_OFFSET + 3] = 0xFF;
+    edid[DESCRIPTOR_OFFSET + 5..DESCRIPTOR_OFFSET + 12].copy_from_slice(b"ABC123\n");
+
+    assert_eq!(parse_edid_id(&edid), Some("DEL-4093-ABC123".to_string()));
+    assert_eq!(parse_edid_id(&edid[..64]), None);
+}
@@ -0,0 +1,130 @@
+use std::io::{BufWriter, Cursor, Write};
+
+use image::{
+    codecs::{bmp::BmpEncoder, jpeg::JpegEncoder, png::PngEncoder},
+    DynamicImage, ExtendedColorType, ImageEncoder, ImageFormat, RgbaImage,
+};
+use tiff::{
+    encoder::{colortype::RGBA8, Rational, TiffEncoder},
+    tags::ResolutionUnit,
+};
+
+use crate::error::{XCapError, XCapResult};
+
+// 缩放比例为 1 时的 DPI
+const BASE_DPI: f32 = 96.0;
+// encode_to 编码 JPEG 时的质量
+const STREAM_JPEG_QUALITY: u8 = 90;
+
+/// Encode the image as JPEG, `quality` is between 1 and 100.
+/// JPEG has no alpha channel, so the alpha is dropped.
+pub(crate) fn encode_jpeg(image: &RgbaImage, quality: u8) -> XCapResult<Vec<u8>> {
+    let rgb_image = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+
+    let mut buffer = Vec::new();
+    JpegEncoder::new_with_quality(&mut buffer, quality.clamp(1, 100)).encode_image(&rgb_image)?;
+
+    Ok(buffer)
+}
+
+/// Encode the image as lossy WebP, `quality` is between 0 and 100.
+pub(crate) fn encode_webp(image: &RgbaImage, quality: f32) -> XCapResult<Vec<u8>> {
+    let encoder = webp::Encoder::from_rgba(image.as_raw(), image.width(), image.height());
+    let webp_memory = encoder.encode(quality.clamp(0.0, 100.0));
+
+    Ok(webp_memory.to_vec())
+}
+
+/// Encode the image as uncompressed 24-bit BMP, the alpha is dropped.
+pub(crate) fn encode_bmp(image: &RgbaImage) -> XCapResult<Vec<u8>> {
+    let rgb_image = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+
+    let mut buffer = Vec::new();
+    BmpEncoder::new(&mut buffer).encode(
+        rgb_image.as_raw(),
+        rgb_image.width(),
+        rgb_image.height(),
+        ExtendedColorType::Rgb8,
+    )?;
+
+    Ok(buffer)
+}
+
+/// Encode the image as uncompressed 8-bit RGBA TIFF. With a `scale_factor` the resolution
+/// is set to `96 * scale_factor` DPI.
+pub(crate) fn encode_tiff(image: &RgbaImage, scale_factor: Option<f32>) -> XCapResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+
+    let map_err = |err| XCapError::with_source("Encode TIFF failed", err);
+
+    let mut encoder = TiffEncoder::new(Cursor::new(&mut buffer)).map_err(map_err)?;
+    let mut tiff_image = encoder
+        .new_image::<RGBA8>(image.width(), image.height())
+        .map_err(map_err)?;
+
+    if let Some(scale_factor) = scale_factor {
+        let dpi = (BASE_DPI * scale_factor).round() as u32;
+        tiff_image.resolution(ResolutionUnit::Inch, Rational { n: dpi, d: 1 });
+    }
+
+    tiff_image.write_data(image.as_raw()).map_err(map_err)?;
+
+    Ok(buffer)
+}
+
+/// Encode the image as PNG, JPEG or BMP and write the bytes to `writer` while encoding,
+/// without collecting the whole file in memory. JPEG and BMP drop the alpha channel.
+pub(crate) fn encode_to<W: Write>(
+    image: RgbaImage,
+    writer: W,
+    format: ImageFormat,
+) -> XCapResult<()> {
+    // 编码器会多次写入少量字节，写入 socket 时需要缓冲
+    let mut writer = BufWriter::new(writer);
+
+    match format {
+        ImageFormat::Png => PngEncoder::new(&mut writer).write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            ExtendedColorType::Rgba8,
+        )?,
+        // 转换后立即释放 RGBA 图像，避免同时保留两份
+        ImageFormat::Jpeg => {
+            let rgb_image = DynamicImage::ImageRgba8(image).into_rgb8();
+            JpegEncoder::new_with_quality(&mut writer, STREAM_JPEG_QUALITY)
+                .encode_image(&rgb_image)?
+        }
+        ImageFormat::Bmp => {
+            let rgb_image = DynamicImage::ImageRgba8(image).into_rgb8();
+            BmpEncoder::new(&mut writer).encode(
+                rgb_image.as_raw(),
+                rgb_image.width(),
+                rgb_image.height(),
+                ExtendedColorType::Rgb8,
+            )?
+        }
+        _ => {
+            return Err(XCapError::Unsupported(format!(
+                "Streaming {:?} images is not supported",
+                format
+            )))
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[test]
+fn encode_to_writes_decodable_png() {
+    let image = RgbaImage::from_pixel(3, 2, image::Rgba([10, 20, 30, 255]));
+
+    let mut buffer = Vec::new();
+    encode_to(image.clone(), &mut buffer, ImageFormat::Png).unwrap();
+
+    let decoded = image::load_from_memory_with_format(&buffer, ImageFormat::Png).unwrap();
+    assert_eq!(decoded.to_rgba8(), image);
+    assert!(encode_to(image, &mut buffer, ImageFormat::Gif).is_err());
+}
@@ -1,4 +1,4 @@
-use std::sync::PoisonError;
+use std::{error::Error as StdError, sync::PoisonError};
 
 use thiserror::Error;
 
@@ -6,8 +6,30 @@ use thiserror::Error;
 pub enum XCapError {
     #[error("{0}")]
     Error(String),
+    #[error("{message}")]
+    ErrorWithSource {
+        message: String,
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+    },
+    /// The operation isn't supported on this platform.
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+    /// The requested object wasn't found.
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// The process doesn't have the privileges the operation needs.
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+    /// The operation was cancelled with a [`CancellationToken`](crate::CancellationToken).
+    #[error("Cancelled")]
+    Cancelled,
     #[error("StdSyncPoisonError {0}")]
     StdSyncPoisonError(String),
+    #[error(transparent)]
+    ImageImageError(#[from] image::ImageError),
+    #[error(transparent)]
+    StdIOError(#[from] std::io::Error),
 
     #[cfg(target_os = "linux")]
     #[error(transparent)]
@@ -17,22 +39,16 @@ pub enum XCapError {
     XcbConnError(#[from] xcb::ConnError),
     #[cfg(target_os = "linux")]
     #[error(transparent)]
-    ImageImageError(#[from] image::ImageError),
-    #[cfg(target_os = "linux")]
-    #[error(transparent)]
     StdStringFromUtf8Error(#[from] std::string::FromUtf8Error),
     #[cfg(target_os = "linux")]
     #[error(transparent)]
     DbusError(#[from] dbus::Error),
     #[cfg(target_os = "linux")]
     #[error(transparent)]
-    StdIOError(#[from] std::io::Error),
-    #[cfg(target_os = "linux")]
-    #[error(transparent)]
     StdTimeSystemTimeError(#[from] std::time::SystemTimeError),
 
     #[cfg(target_os = "macos")]
-    #[error("Objc2CoreGraphicsCGError {:?}", 0)]
+    #[error("Objc2CoreGraphicsCGError {0:?}")]
     Objc2CoreGraphicsCGError(objc2_core_graphics::CGError),
 
     #[cfg(target_os = "windows")]
@@ -47,6 +63,17 @@ impl XCapError {
     pub fn new<S: ToString>(err: S) -> Self {
         XCapError::Error(err.to_string())
     }
+
+    /// Create an error with a message, keeping the underlying error as its `source()`.
+    pub fn with_source<S: ToString, E: Into<Box<dyn StdError + Send + Sync>>>(
+        message: S,
+        source: E,
+    ) -> Self {
+        XCapError::ErrorWithSource {
+            message: message.to_string(),
+            source: source.into(),
+        }
+    }
 }
 
 pub type XCapResult<T> = Result<T, XCapError>;
@@ -0,0 +1,264 @@
+use std::{ffi::CString, path::Path, ptr};
+
+use ffmpeg_sys_next::{
+    av_frame_alloc, av_frame_free, av_frame_get_buffer, av_frame_make_writable,
+    av_interleaved_write_frame, av_packet_alloc, av_packet_free, av_packet_rescale_ts,
+    av_write_trailer, avcodec_alloc_context3, avcodec_find_encoder, avcodec_free_context,
+    avcodec_open2, avcodec_parameters_from_context, avcodec_receive_packet, avcodec_send_frame,
+    avformat_alloc_output_context2, avformat_free_context, avformat_new_stream,
+    avformat_write_header, avio_closep, avio_open, sws_freeContext, sws_getContext, sws_scale,
+    AVCodecContext, AVCodecID, AVFormatContext, AVFrame, AVPacket, AVPixelFormat, AVRational,
+    AVStream, SwsContext, AVERROR, AVERROR_EOF, AVFMT_GLOBALHEADER, AVFMT_NOFILE, AVIO_FLAG_WRITE,
+    AV_CODEC_FLAG_GLOBAL_HEADER, SWS_BILINEAR,
+};
+use image::RgbaImage;
+
+use crate::{
+    error::{XCapError, XCapResult},
+    video_config::{VideoCodec, VideoConfig},
+    video_file::VideoEncoder,
+};
+
+fn check(operation: &str, code: i32) -> XCapResult<i32> {
+    if code < 0 {
+        return Err(XCapError::new(format!(
+            "{} failed, error code: {}",
+            operation, code
+        )));
+    }
+
+    Ok(code)
+}
+
+/// 使用 FFmpeg 编码，容器由文件扩展名决定
+pub(crate) struct FfmpegEncoder {
+    format_context: *mut AVFormatContext,
+    codec_context: *mut AVCodecContext,
+    stream: *mut AVStream,
+    sws_context: *mut SwsContext,
+    frame: *mut AVFrame,
+    packet: *mut AVPacket,
+}
+
+impl FfmpegEncoder {
+    pub fn new(
+        path: &Path,
+        width: u32,
+        height: u32,
+        config: &VideoConfig,
+    ) -> XCapResult<FfmpegEncoder> {
+        let filename = CString::new(path.to_string_lossy().as_bytes())
+            .map_err(|err| XCapError::with_source("Invalid video file path", err))?;
+
+        let codec_id = match config.codec {
+            VideoCodec::H264 => AVCodecID::AV_CODEC_ID_H264,
+            VideoCodec::Vp9 => AVCodecID::AV_CODEC_ID_VP9,
+        };
+
+        // 创建失败时由 Drop 释放已经创建的资源
+        let mut encoder = FfmpegEncoder {
+            format_context: ptr::null_mut(),
+            codec_context: ptr::null_mut(),
+            stream: ptr::null_mut(),
+            sws_context: ptr::null_mut(),
+            frame: ptr::null_mut(),
+            packet: ptr::null_mut(),
+        };
+
+        unsafe {
+            check(
+                "avformat_alloc_output_context2",
+                avformat_alloc_output_context2(
+                    &mut encoder.format_context,
+                    ptr::null(),
+                    ptr::null(),
+                    filename.as_ptr(),
+                ),
+            )?;
+            let output_flags = (*(*encoder.format_context).oformat).flags;
+
+            let codec = avcodec_find_encoder(codec_id);
+            if codec.is_null() {
+                return Err(XCapError::Unsupported(format!(
+                    "FFmpeg has no {:?} encoder",
+                    config.codec
+                )));
+            }
+
+            encoder.stream = avformat_new_stream(encoder.format_context, ptr::null());
+            if encoder.stream.is_null() {
+                return Err(XCapError::new("avformat_new_stream failed"));
+            }
+
+            encoder.codec_context = avcodec_alloc_context3(codec);
+            if encoder.codec_context.is_null() {
+                return Err(XCapError::new("avcodec_alloc_context3 failed"));
+            }
+
+            let codec_context = &mut *encoder.codec_context;
+            codec_context.width = width as i32;
+            codec_context.height = height as i32;
+            codec_context.time_base = AVRational {
+                num: 1,
+                den: config.fps as i32,
+            };
+            codec_context.framerate = AVRational {
+                num: config.fps as i32,
+                den: 1,
+            };
+            codec_context.bit_rate = config.bitrate as i64;
+            // 每 2 秒一个关键帧
+            codec_context.gop_size = config.fps as i32 * 2;
+            codec_context.pix_fmt = AVPixelFormat::AV_PIX_FMT_YUV420P;
+            if output_flags & AVFMT_GLOBALHEADER as i32 != 0 {
+                codec_context.flags |= AV_CODEC_FLAG_GLOBAL_HEADER as i32;
+            }
+
+            check(
+                "avcodec_open2",
+                avcodec_open2(encoder.codec_context, codec, ptr::null_mut()),
+            )?;
+            check(
+                "avcodec_parameters_from_context",
+                avcodec_parameters_from_context((*encoder.stream).codecpar, encoder.codec_context),
+            )?;
+            (*encoder.stream).time_base = codec_context.time_base;
+
+            if output_flags & AVFMT_NOFILE as i32 == 0 {
+                check(
+                    "avio_open",
+                    avio_open(
+                        &mut (*encoder.format_context).pb,
+                        filename.as_ptr(),
+                        AVIO_FLAG_WRITE as i32,
+                    ),
+                )?;
+            }
+
+            // 写入文件头时 muxer 可能修改 stream 的 time_base，写入帧时重新换算
+            check(
+                "avformat_write_header",
+                avformat_write_header(encoder.format_context, ptr::null_mut()),
+            )?;
+
+            encoder.frame = av_frame_alloc();
+            if encoder.frame.is_null() {
+                return Err(XCapError::new("av_frame_alloc failed"));
+            }
+            (*encoder.frame).format = AVPixelFormat::AV_PIX_FMT_YUV420P as i32;
+            (*encoder.frame).width = width as i32;
+            (*encoder.frame).height = height as i32;
+            check("av_frame_get_buffer", av_frame_get_buffer(encoder.frame, 0))?;
+
+            encoder.packet = av_packet_alloc();
+            if encoder.packet.is_null() {
+                return Err(XCapError::new("av_packet_alloc failed"));
+            }
+
+            encoder.sws_context = sws_getContext(
+                width as i32,
+                height as i32,
+                AVPixelFormat::AV_PIX_FMT_RGBA,
+                width as i32,
+                height as i32,
+                AVPixelFormat::AV_PIX_FMT_YUV420P,
+                SWS_BILINEAR as i32,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null(),
+            );
+            if encoder.sws_context.is_null() {
+                return Err(XCapError::new("sws_getContext failed"));
+            }
+        }
+
+        Ok(encoder)
+    }
+
+    /// 把编码器输出的数据包全部写入文件
+    fn write_packets(&mut self) -> XCapResult<()> {
+        unsafe {
+            loop {
+                let code = avcodec_receive_packet(self.codec_context, self.packet);
+                if code == AVERROR(libc::EAGAIN) || code == AVERROR_EOF {
+                    return Ok(());
+                }
+                check("avcodec_receive_packet", code)?;
+
+                av_packet_rescale_ts(
+                    self.packet,
+                    (*self.codec_context).time_base,
+                    (*self.stream).time_base,
+                );
+                (*self.packet).stream_index = (*self.stream).index;
+
+                // av_interleaved_write_frame 会释放数据包的引用
+                check(
+                    "av_interleaved_write_frame",
+                    av_interleaved_write_frame(self.format_context, self.packet),
+                )?;
+            }
+        }
+    }
+}
+
+impl VideoEncoder for FfmpegEncoder {
+    fn write_frame(&mut self, image: &RgbaImage, index: u64) -> XCapResult<()> {
+        unsafe {
+            check("av_frame_make_writable", av_frame_make_writable(self.frame))?;
+
+            let src_slice = [image.as_raw().as_ptr()];
+            let src_stride = [image.width() as i32 * 4];
+            sws_scale(
+                self.sws_context,
+                src_slice.as_ptr(),
+                src_stride.as_ptr(),
+                0,
+                image.height() as i32,
+                (*self.frame).data.as_ptr(),
+                (*self.frame).linesize.as_ptr(),
+            );
+            (*self.frame).pts = index as i64;
+
+            check(
+                "avcodec_send_frame",
+                avcodec_send_frame(self.codec_context, self.frame),
+            )?;
+        }
+
+        self.write_packets()
+    }
+
+    fn finish(mut self: Box<Self>) -> XCapResult<()> {
+        unsafe {
+            // 发送空帧，取出编码器中缓存的帧
+            check(
+                "avcodec_send_frame",
+                avcodec_send_frame(self.codec_context, ptr::null()),
+            )?;
+            self.write_packets()?;
+
+            check("av_write_trailer", av_write_trailer(self.format_context))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for FfmpegEncoder {
+    fn drop(&mut self) {
+        unsafe {
+            sws_freeContext(self.sws_context);
+            av_packet_free(&mut self.packet);
+            av_frame_free(&mut self.frame);
+            avcodec_free_context(&mut self.codec_context);
+
+            if !self.format_context.is_null() {
+                if (*(*self.format_context).oformat).flags & AVFMT_NOFILE as i32 == 0 {
+                    avio_closep(&mut (*self.format_context).pb);
+                }
+                avformat_free_context(self.format_context);
+            }
+        }
+    }
+}
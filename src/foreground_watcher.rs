@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{error::XCapResult, platform::impl_foreground_watcher::ImplForegroundWatcher, Window};
+
+/// Watches the foreground window, created by [`watch_foreground_window`].
+/// The watching stops when [`ForegroundWatcher::stop`] is called or the watcher is dropped.
+#[derive(Debug)]
+pub struct ForegroundWatcher {
+    impl_foreground_watcher: ImplForegroundWatcher,
+    is_stopped: AtomicBool,
+}
+
+impl ForegroundWatcher {
+    pub fn stop(&self) -> XCapResult<()> {
+        // 只停止一次，Windows 上监听线程退出后无法再发送消息
+        if self.is_stopped.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        self.impl_foreground_watcher.stop()
+    }
+}
+
+impl Drop for ForegroundWatcher {
+    fn drop(&mut self) {
+        if let Err(err) = self.stop() {
+            log::error!("Stop foreground watcher failed: {}", err);
+        }
+    }
+}
+
+/// Call `callback` with the new foreground window every time it changes.
+/// The callback runs on a background thread. On Windows it's driven by
+/// `SetWinEventHook(EVENT_SYSTEM_FOREGROUND)`, on Linux (X11) by `_NET_ACTIVE_WINDOW`
+/// property changes, and on MacOS by `NSWorkspaceDidActivateApplicationNotification`, which is
+/// only posted while the main thread runs its run loop, e.g. `NSApplication::run`.
+pub fn watch_foreground_window<F>(mut callback: F) -> XCapResult<ForegroundWatcher>
+where
+    F: FnMut(Window) + Send + 'static,
+{
+    let impl_foreground_watcher = ImplForegroundWatcher::new(Box::new(move |impl_window| {
+        callback(Window::new(impl_window))
+    }))?;
+
+    Ok(ForegroundWatcher {
+        impl_foreground_watcher,
+        is_stopped: AtomicBool::new(false),
+    })
+}
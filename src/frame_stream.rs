@@ -0,0 +1,36 @@
+use std::thread;
+
+use futures::{channel::mpsc::channel, executor::block_on, SinkExt, Stream};
+use image::RgbaImage;
+
+use crate::{error::XCapResult, raw_handle::RawMonitorHandle, Monitor};
+
+// 缓存的帧数，消费者处理不过来时截图线程阻塞
+const FRAME_STREAM_BUFFER: usize = 1;
+
+/// 在后台线程连续截图，通过有界 channel 发送，消费者读取后才截取下一帧
+pub(crate) fn monitor_frame_stream(
+    raw_monitor_handle: RawMonitorHandle,
+) -> impl Stream<Item = XCapResult<RgbaImage>> + Send + Unpin {
+    let (mut tx, rx) = channel(FRAME_STREAM_BUFFER);
+
+    // ImplMonitor 不能跨线程传递，在截图线程中重新创建
+    thread::spawn(move || {
+        let monitor = match Monitor::from_raw_handle(raw_monitor_handle) {
+            Ok(monitor) => monitor,
+            Err(err) => {
+                let _ = block_on(tx.send(Err(err)));
+                return;
+            }
+        };
+
+        loop {
+            // Stream 被丢弃后发送失败，结束线程
+            if block_on(tx.send(monitor.capture_image())).is_err() {
+                break;
+            }
+        }
+    });
+
+    rx
+}
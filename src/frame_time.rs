@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use crate::platform::clock::get_clock_time;
+
+/// Whether a frame was read back by the GPU or copied by the CPU.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Acceleration {
+    /// The GPU composited frame was read directly: ScreenCaptureKit on MacOS or the
+    /// compositor's screenshot on Linux (Wayland).
+    Hardware,
+    /// The CPU copied the frame: GDI on Windows, `CGWindowListCreateImage` on MacOS and
+    /// X11 `GetImage` on Linux.
+    #[default]
+    Software,
+}
+
+/// The time a capture started, read right before the screen is read. It's not the time the
+/// frame was presented, the screenshot paths don't report one, but it's read from the same
+/// clock the platform timestamps presented frames with, so the two can be compared:
+/// `QueryPerformanceCounter` on Windows (the clock of DXGI's `LastPresentTime`),
+/// `mach_absolute_time` on MacOS (the clock of CGDisplayStream) and `CLOCK_MONOTONIC` on
+/// Linux (the clock of PipeWire buffer timestamps).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FrameTime {
+    /// The raw clock value, in the clock's own ticks.
+    pub source: u64,
+    /// The clock value converted to a duration since the clock's origin (usually boot).
+    pub timestamp: Duration,
+    /// The capture path of the frame, [`Acceleration::Software`] tells that the platform fell
+    /// back to the slow path.
+    pub acceleration: Acceleration,
+}
+
+impl FrameTime {
+    pub(crate) fn now() -> FrameTime {
+        let (source, timestamp) = get_clock_time();
+
+        FrameTime {
+            source,
+            timestamp,
+            acceleration: Acceleration::default(),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+/// The gamma ramp of a monitor, one lookup table per channel mapping the input level to the
+/// output level. Values are scaled to `0..=65535`. The tables usually have 256 entries,
+/// but the length depends on the platform and the display driver.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct GammaRamp {
+    pub red: Vec<u16>,
+    pub green: Vec<u16>,
+    pub blue: Vec<u16>,
+}
+
+impl GammaRamp {
+    /// A ramp with `size` entries per channel that maps every level to itself, scaled by
+    /// `brightness` between 0.0 (black) and 1.0 (unchanged).
+    pub fn linear(size: usize, brightness: f32) -> GammaRamp {
+        let brightness = brightness.clamp(0.0, 1.0);
+        let max_index = size.saturating_sub(1).max(1) as f32;
+
+        let table: Vec<u16> = (0..size)
+            .map(|i| (i as f32 / max_index * 65535.0 * brightness).round() as u16)
+            .collect();
+
+        GammaRamp {
+            red: table.clone(),
+            green: table.clone(),
+            blue: table,
+        }
+    }
+}
+
+#[test]
+fn linear_gamma_ramp_is_scaled_by_brightness() {
+    let ramp = GammaRamp::linear(256, 0.5);
+
+    assert_eq!(ramp.red.len(), 256);
+    assert_eq!(ramp.red[0], 0);
+    assert_eq!(ramp.green[255], 32768);
+    assert_eq!(GammaRamp::linear(256, 1.0).blue[255], 65535);
+}
@@ -0,0 +1,117 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{error::XCapResult, platform::impl_hotkey_listener::ImplHotKeyListener};
+
+/// Triggers closer together than this are ignored, e.g. the key repeat of a held key.
+const THROTTLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The key of a [`HotKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HotKeyCode {
+    PrintScreen,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+}
+
+/// The modifier keys that must be held together with the [`HotKeyCode`].
+/// `meta` is the Windows key on Windows, Command on MacOS and Super (Mod4) on Linux.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct HotKeyModifiers {
+    pub control: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+/// A key combination registered with [`on_capture_hotkey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HotKey {
+    pub code: HotKeyCode,
+    pub modifiers: HotKeyModifiers,
+}
+
+impl HotKey {
+    pub fn new(code: HotKeyCode) -> HotKey {
+        HotKey {
+            code,
+            modifiers: HotKeyModifiers::default(),
+        }
+    }
+
+    pub fn with_modifiers(code: HotKeyCode, modifiers: HotKeyModifiers) -> HotKey {
+        HotKey { code, modifiers }
+    }
+}
+
+/// Listens for a hotkey, created by [`on_capture_hotkey`].
+/// The hotkey is unregistered when [`HotKeyListener::stop`] is called or the listener
+/// is dropped. On MacOS the hotkey only triggers while the main thread runs its run loop,
+/// e.g. `NSApplication::run` or `CFRunLoopRun`.
+#[derive(Debug)]
+pub struct HotKeyListener {
+    impl_hotkey_listener: ImplHotKeyListener,
+    is_stopped: AtomicBool,
+}
+
+impl HotKeyListener {
+    pub fn stop(&self) -> XCapResult<()> {
+        // 只停止一次，Windows 上监听线程退出后无法再发送消息
+        if self.is_stopped.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        self.impl_hotkey_listener.stop()
+    }
+}
+
+impl Drop for HotKeyListener {
+    fn drop(&mut self) {
+        if let Err(err) = self.stop() {
+            log::error!("Stop hotkey listener failed: {}", err);
+        }
+    }
+}
+
+/// Call `callback` every time `hotkey` is pressed, the callback decides what to capture.
+/// The callback runs on a background thread and is throttled, so a held key triggers it once.
+/// The hotkey is registered with `RegisterHotKey` on Windows, `RegisterEventHotKey` on MacOS
+/// and `XGrabKey` on Linux (X11), so other applications don't receive it. On MacOS the
+/// hotkey events are dispatched by the main thread's run loop, so they are only received
+/// while the application runs it.
+pub fn on_capture_hotkey<F>(hotkey: HotKey, mut callback: F) -> XCapResult<HotKeyListener>
+where
+    F: FnMut() + Send + 'static,
+{
+    let mut last_triggered_at: Option<Instant> = None;
+
+    let impl_hotkey_listener = ImplHotKeyListener::new(
+        hotkey,
+        Box::new(move || {
+            let now = Instant::now();
+            if last_triggered_at.is_some_and(|instant| now - instant < THROTTLE_INTERVAL) {
+                return;
+            }
+
+            last_triggered_at = Some(now);
+            callback();
+        }),
+    )?;
+
+    Ok(HotKeyListener {
+        impl_hotkey_listener,
+        is_stopped: AtomicBool::new(false),
+    })
+}
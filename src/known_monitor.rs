@@ -0,0 +1,12 @@
+use crate::Monitor;
+
+/// A display known to the system, see [`Monitor::all_including_inactive`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KnownMonitor {
+    /// The display name, e.g. the monitor's friendly name from its EDID.
+    pub name: String,
+    /// Whether the display is currently part of the desktop.
+    pub is_active: bool,
+    /// The monitor, available when the display is active.
+    pub monitor: Option<Monitor>,
+}
@@ -0,0 +1,49 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use crate::{error::XCapResult, Monitor, Window};
+
+/// Hash the current monitor layout: the id, position, resolution, rotation and scale factor
+/// of every monitor, and with `include_windows` the id, position, size and minimized state
+/// of every window in [`Window::all`]. When the fingerprint is unchanged since the last
+/// call, the cached monitor/window lists can be kept instead of enumerated again.
+///
+/// The value is only comparable within the same process. Including the windows makes the
+/// call about as costly as reading the geometry of every window.
+pub fn layout_fingerprint(include_windows: bool) -> XCapResult<u64> {
+    let mut hasher = DefaultHasher::new();
+
+    let monitors = Monitor::all()?;
+    monitors.len().hash(&mut hasher);
+    for monitor in monitors {
+        monitor.id()?.hash(&mut hasher);
+        monitor.x()?.hash(&mut hasher);
+        monitor.y()?.hash(&mut hasher);
+        monitor.width()?.hash(&mut hasher);
+        monitor.height()?.hash(&mut hasher);
+        monitor.rotation()?.to_bits().hash(&mut hasher);
+        monitor.scale_factor()?.to_bits().hash(&mut hasher);
+    }
+
+    if include_windows {
+        let windows = Window::all()?;
+        windows.len().hash(&mut hasher);
+        for window in windows {
+            window.id()?.hash(&mut hasher);
+            // 窗口可能在枚举后关闭，此时只记录 id
+            if let (Ok(x), Ok(y), Ok(width), Ok(height), Ok(is_minimized)) = (
+                window.x(),
+                window.y(),
+                window.width(),
+                window.height(),
+                window.is_minimized(),
+            ) {
+                (x, y, width, height, is_minimized).hash(&mut hasher);
+            }
+        }
+    }
+
+    Ok(hasher.finish())
+}
@@ -1,7 +1,56 @@
+mod alpha_mode;
+mod benchmark;
+mod cancellation_token;
+mod capture_config;
+mod capture_event;
+mod capture_limit;
+mod capture_quality;
+mod capture_session;
+mod capture_source;
+mod capture_warning;
+mod color;
+mod coordinate;
+mod cursor;
+mod desktop_layers;
+mod diagnostic;
+mod diff;
+mod display_mode;
+mod edid;
+#[cfg(feature = "encoding")]
+mod encoding;
 mod error;
+#[cfg(feature = "ffmpeg")]
+mod ffmpeg_encoder;
+mod foreground_watcher;
+#[cfg(feature = "futures")]
+mod frame_stream;
+mod frame_time;
+mod gamma_ramp;
+#[cfg(feature = "hotkey")]
+mod hotkey;
+mod known_monitor;
+mod layout_fingerprint;
 mod monitor;
+mod monitor_descriptor;
+mod native_image;
+mod rate_limited_capturer;
+mod raw_handle;
+mod rect;
+mod region;
+mod rotation;
+mod save;
+mod scale_factor;
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+mod scroll_capture;
+mod shutdown;
+mod sort_key;
+mod trim;
+mod video_config;
+mod video_file;
 mod video_recorder;
+mod visibility;
 mod window;
+mod window_activity;
 
 #[cfg(target_os = "macos")]
 #[path = "macos/mod.rs"]
@@ -17,8 +66,55 @@ mod platform;
 
 pub use image;
 
+pub use alpha_mode::{unpremultiply, AlphaMode};
+pub use benchmark::{benchmark_backends, BackendBenchmark, BenchmarkReport};
+pub use cancellation_token::CancellationToken;
+pub use capture_config::{Backend, CaptureConfig};
+pub use capture_event::{
+    recent_events, set_recent_events_capacity, CaptureEvent, DEFAULT_RECENT_EVENTS_CAPACITY,
+};
+pub use capture_limit::{max_capture_pixels, set_max_capture_pixels, DEFAULT_MAX_CAPTURE_PIXELS};
+pub use capture_quality::CaptureQuality;
+pub use capture_session::CaptureSession;
+pub use capture_source::{
+    all_sources, enumerate_sources_with_thumbnails, CaptureSource, CaptureSourceKind, SourceInfo,
+};
+pub use capture_warning::CaptureWarning;
+pub use cursor::{composite_cursor_path, cursor_image, cursor_position};
+pub use desktop_layers::{capture_desktop_layers, WindowLayer};
+pub use diagnostic::{clear_diagnostic_hook, set_diagnostic_hook, Diagnostic, DiagnosticTarget};
+pub use diff::{diff_images, DiffResult};
+pub use display_mode::DisplayMode;
 pub use error::{XCapError, XCapResult};
+pub use foreground_watcher::{watch_foreground_window, ForegroundWatcher};
+pub use frame_time::{Acceleration, FrameTime};
+pub use gamma_ramp::GammaRamp;
+#[cfg(feature = "hotkey")]
+pub use hotkey::{on_capture_hotkey, HotKey, HotKeyCode, HotKeyListener, HotKeyModifiers};
+pub use known_monitor::KnownMonitor;
+pub use layout_fingerprint::layout_fingerprint;
 pub use monitor::Monitor;
+pub use monitor_descriptor::MonitorDescriptor;
+pub use native_image::{NativeImage, PixelFormat};
+pub use rate_limited_capturer::{RateLimitMode, RateLimitedCapturer};
+pub use raw_handle::{RawMonitorHandle, RawWindowHandle};
+pub use rect::Rect;
+pub use region::{capture_region, virtual_desktop_bounds, PixelDensity};
+pub use rotation::Rotation;
+pub use scale_factor::ScaleFactorSource;
+pub use shutdown::shutdown;
+pub use sort_key::SortKey;
 pub use window::Window;
+pub use window_activity::track_window_activity;
 
+pub use video_config::{VideoCodec, VideoConfig};
 pub use video_recorder::VideoRecorder;
+
+#[cfg(target_os = "windows")]
+pub use platform::dpi_awareness::DpiAwarenessGuard;
+#[cfg(target_os = "windows")]
+pub use platform::impl_window::set_minimized_frame_cache_capacity;
+#[cfg(target_os = "linux")]
+pub use platform::restore_token::set_restore_token_path;
+#[cfg(target_os = "linux")]
+pub use platform::session::{session_type, set_session_type, SessionType};
@@ -1,50 +1,116 @@
-use image::RgbaImage;
-use std::env::var_os;
+use image::{imageops::crop_imm, RgbaImage};
 
-use crate::error::XCapResult;
+use crate::{
+    cancellation_token::CancellationToken,
+    capture_config::Backend,
+    error::{XCapError, XCapResult},
+};
 
 use super::{
     impl_monitor::ImplMonitor,
     impl_window::ImplWindow,
+    session::{session_type, SessionType},
     utils::{get_current_screen_buf, get_monitor_info_buf},
-    wayland_capture::wayland_capture,
+    wayland_capture::{
+        wayland_capture, wayland_capture_cancellable, wayland_capture_restored,
+        wayland_capture_window,
+    },
     xorg_capture::xorg_capture,
 };
 
-fn wayland_detect() -> bool {
-    let xdg_session_type = var_os("XDG_SESSION_TYPE")
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
+pub fn capture_monitor(impl_monitor: &ImplMonitor) -> XCapResult<RgbaImage> {
+    let monitor_info_buf = get_monitor_info_buf(impl_monitor.output)?;
+
+    capture_monitor_area(
+        impl_monitor,
+        0,
+        0,
+        monitor_info_buf.width() as u32,
+        monitor_info_buf.height() as u32,
+    )
+}
+
+pub fn capture_monitor_cancellable(
+    impl_monitor: &ImplMonitor,
+    token: &CancellationToken,
+) -> XCapResult<RgbaImage> {
+    let monitor_info_buf = get_monitor_info_buf(impl_monitor.output)?;
+
+    let x = monitor_info_buf.x() as i32;
+    let y = monitor_info_buf.y() as i32;
+    let width = monitor_info_buf.width() as u32;
+    let height = monitor_info_buf.height() as u32;
 
-    let wayland_display = var_os("WAYLAND_DISPLAY")
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
+    token.check()?;
 
-    xdg_session_type.eq("wayland") || wayland_display.to_lowercase().contains("wayland")
+    let image = if session_type() == SessionType::Wayland {
+        match wayland_capture_restored(&impl_monitor.connector()?) {
+            Some(image) => image,
+            None => wayland_capture_cancellable(x, y, width as i32, height as i32, token)?,
+        }
+    } else {
+        let screen_buf = get_current_screen_buf()?;
+
+        xorg_capture(screen_buf.root(), x, y, width, height)?
+    };
+
+    token.check()?;
+
+    Ok(image)
 }
 
-pub fn capture_monitor(impl_monitor: &ImplMonitor) -> XCapResult<RgbaImage> {
+/// 使用指定的方式截取整个显示器，不使用 ScreenCast 恢复令牌
+pub fn capture_monitor_with_backend(
+    impl_monitor: &ImplMonitor,
+    backend: Backend,
+) -> XCapResult<RgbaImage> {
+    let monitor_info_buf = get_monitor_info_buf(impl_monitor.output)?;
+
+    let x = monitor_info_buf.x() as i32;
+    let y = monitor_info_buf.y() as i32;
+    let width = monitor_info_buf.width() as u32;
+    let height = monitor_info_buf.height() as u32;
+
+    match backend {
+        Backend::X11 => {
+            let screen_buf = get_current_screen_buf()?;
+
+            xorg_capture(screen_buf.root(), x, y, width, height)
+        }
+        Backend::Wayland => wayland_capture(x, y, width as i32, height as i32),
+        _ => Err(XCapError::Unsupported(format!(
+            "{:?} is not available on Linux",
+            backend
+        ))),
+    }
+}
+
+/// x, y, width, height are physical pixels relative to the monitor
+pub fn capture_monitor_area(
+    impl_monitor: &ImplMonitor,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> XCapResult<RgbaImage> {
     let monitor_info_buf = get_monitor_info_buf(impl_monitor.output)?;
 
-    if wayland_detect() {
-        wayland_capture(
-            monitor_info_buf.x() as i32,
-            monitor_info_buf.y() as i32,
-            monitor_info_buf.width() as i32,
-            monitor_info_buf.height() as i32,
-        )
+    if session_type() == SessionType::Wayland {
+        // 有恢复令牌时通过 ScreenCast 截取整个显示器，不会弹出授权对话框
+        if let Some(image) = wayland_capture_restored(&impl_monitor.connector()?) {
+            return Ok(crop_imm(&image, x, y, width, height).to_image());
+        }
+    }
+
+    let x = monitor_info_buf.x() as i32 + x as i32;
+    let y = monitor_info_buf.y() as i32 + y as i32;
+
+    if session_type() == SessionType::Wayland {
+        wayland_capture(x, y, width as i32, height as i32)
     } else {
         let screen_buf = get_current_screen_buf()?;
 
-        xorg_capture(
-            screen_buf.root(),
-            monitor_info_buf.x() as i32,
-            monitor_info_buf.y() as i32,
-            monitor_info_buf.width() as u32,
-            monitor_info_buf.height() as u32,
-        )
+        xorg_capture(screen_buf.root(), x, y, width, height)
     }
 }
 
@@ -52,5 +118,25 @@ pub fn capture_window(impl_window: &ImplWindow) -> XCapResult<RgbaImage> {
     let width = impl_window.width()?;
     let height = impl_window.height()?;
 
+    if session_type() == SessionType::Wayland {
+        // 只有 XWayland 窗口可以通过 GetImage 截图，portal 不能按 id 选择窗口
+        return xorg_capture(impl_window.window, 0, 0, width, height).map_err(|err| {
+            XCapError::Unsupported(format!(
+                "Only XWayland windows can be captured on Wayland: {}",
+                err
+            ))
+        });
+    }
+
     xorg_capture(impl_window.window, 0, 0, width, height)
 }
+
+pub fn capture_window_with_picker() -> XCapResult<RgbaImage> {
+    if session_type() != SessionType::Wayland {
+        return Err(XCapError::Unsupported(
+            "The window picker is only available on Wayland".to_string(),
+        ));
+    }
+
+    wayland_capture_window()
+}
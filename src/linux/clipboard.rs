@@ -0,0 +1,49 @@
+use std::{
+    io::{Cursor, Write},
+    process::{Command, Stdio},
+};
+
+use image::{ImageFormat, RgbaImage};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::session::{session_type, SessionType};
+
+// X11 selection 和 Wayland 剪贴板都需要进程持续响应粘贴请求，
+// 所以交给 wl-copy/xclip 这类常驻的剪贴板工具处理
+pub(crate) fn set_clipboard_image(image: &RgbaImage) -> XCapResult<()> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+
+    let mut command = match session_type() {
+        SessionType::Wayland => {
+            let mut command = Command::new("wl-copy");
+            command.args(["--type", "image/png"]);
+            command
+        }
+        SessionType::Xorg => {
+            let mut command = Command::new("xclip");
+            command.args(["-selection", "clipboard", "-target", "image/png"]);
+            command
+        }
+    };
+
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| XCapError::new("Get clipboard process stdin failed"))?
+        .write_all(&png)?;
+
+    let status = child.wait()?;
+
+    if !status.success() {
+        return Err(XCapError::new(format!(
+            "Clipboard process exited with {}",
+            status
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use std::{os::raw::c_long, time::Duration};
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: c_long,
+    tv_nsec: c_long,
+}
+
+const CLOCK_MONOTONIC: i32 = 1;
+
+extern "C" {
+    fn clock_gettime(clock_id: i32, tp: *mut Timespec) -> i32;
+}
+
+pub fn get_clock_time() -> (u64, Duration) {
+    let mut timespec = Timespec::default();
+
+    unsafe {
+        clock_gettime(CLOCK_MONOTONIC, &mut timespec);
+    }
+
+    let timestamp = Duration::new(timespec.tv_sec as u64, timespec.tv_nsec as u32);
+
+    // PipeWire 的时间戳单位为纳秒
+    (timestamp.as_nanos() as u64, timestamp)
+}
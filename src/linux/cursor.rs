@@ -0,0 +1,69 @@
+use image::RgbaImage;
+use xcb::{x::QueryPointer, xfixes};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::{
+    impl_monitor::get_scale_factor,
+    utils::{get_current_screen_buf, get_xcb_connection_and_index},
+};
+
+pub fn get_cursor_position() -> XCapResult<(i32, i32)> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let screen_buf = get_current_screen_buf()?;
+
+    let query_pointer_cookie = conn.send_request(&QueryPointer {
+        window: screen_buf.root(),
+    });
+    let query_pointer_reply = conn.wait_for_reply(query_pointer_cookie)?;
+
+    // 与 Monitor 的坐标一致，转换为逻辑坐标
+    let scale_factor = get_scale_factor().unwrap_or(1.0);
+
+    Ok((
+        (query_pointer_reply.root_x() as f32 / scale_factor) as i32,
+        (query_pointer_reply.root_y() as f32 / scale_factor) as i32,
+    ))
+}
+
+pub fn get_cursor_image() -> XCapResult<(RgbaImage, i32, i32)> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+
+    // 使用 XFixes 前必须先协商版本
+    let query_version_cookie = conn.send_request(&xfixes::QueryVersion {
+        client_major_version: 4,
+        client_minor_version: 0,
+    });
+    conn.wait_for_reply(query_version_cookie)?;
+
+    let get_cursor_image_cookie = conn.send_request(&xfixes::GetCursorImage {});
+    let get_cursor_image_reply = conn.wait_for_reply(get_cursor_image_cookie)?;
+
+    let width = get_cursor_image_reply.width() as u32;
+    let height = get_cursor_image_reply.height() as u32;
+
+    // 每个像素为预乘透明度的 ARGB
+    let mut buffer = Vec::with_capacity((width * height * 4) as usize);
+    for &argb in get_cursor_image_reply.cursor_image() {
+        let [b, g, r, a] = argb.to_le_bytes();
+
+        let unpremultiply = |value: u8| {
+            if a == 0 {
+                0
+            } else {
+                (value as u32 * 255 / a as u32).min(255) as u8
+            }
+        };
+
+        buffer.extend_from_slice(&[unpremultiply(r), unpremultiply(g), unpremultiply(b), a]);
+    }
+
+    let image = RgbaImage::from_raw(width, height, buffer)
+        .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))?;
+
+    Ok((
+        image,
+        get_cursor_image_reply.xhot() as i32,
+        get_cursor_image_reply.yhot() as i32,
+    ))
+}
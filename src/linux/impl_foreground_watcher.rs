@@ -0,0 +1,123 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use xcb::{
+    x::{ChangeWindowAttributes, Cw, EventMask, GetProperty, Window, ATOM_WINDOW},
+    Connection, Event, Xid,
+};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::{impl_window::ImplWindow, utils::get_atom};
+
+type Callback = Box<dyn FnMut(ImplWindow) + Send>;
+
+fn get_active_window(conn: &Connection, root_window: Window) -> XCapResult<Option<Window>> {
+    let get_property_cookie = conn.send_request(&GetProperty {
+        delete: false,
+        window: root_window,
+        property: get_atom("_NET_ACTIVE_WINDOW")?,
+        r#type: ATOM_WINDOW,
+        long_offset: 0,
+        long_length: 1,
+    });
+
+    let get_property_reply = conn.wait_for_reply(get_property_cookie)?;
+
+    let active_window = get_property_reply
+        .value::<Window>()
+        .first()
+        .filter(|window| !window.is_none())
+        .copied();
+
+    Ok(active_window)
+}
+
+#[derive(Debug)]
+pub struct ImplForegroundWatcher {
+    running: Arc<AtomicBool>,
+}
+
+impl ImplForegroundWatcher {
+    pub fn new(mut callback: Callback) -> XCapResult<Self> {
+        // 使用单独的连接接收事件，避免影响全局连接上的请求
+        let (conn, index) = Connection::connect(None)?;
+
+        let root_window = conn
+            .get_setup()
+            .roots()
+            .nth(index as usize)
+            .ok_or_else(|| XCapError::new("Not found screen"))?
+            .root();
+
+        conn.send_and_check_request(&ChangeWindowAttributes {
+            window: root_window,
+            value_list: &[Cw::EventMask(EventMask::PROPERTY_CHANGE)],
+        })
+        .map_err(xcb::Error::from)?;
+
+        let active_window_atom = get_atom("_NET_ACTIVE_WINDOW")?;
+        let running = Arc::new(AtomicBool::new(true));
+        let running_flag = running.clone();
+
+        thread::spawn(move || {
+            let mut last_active_window = get_active_window(&conn, root_window).ok().flatten();
+
+            while running_flag.load(Ordering::Relaxed) {
+                let event = match conn.poll_for_event() {
+                    Ok(Some(event)) => event,
+                    Ok(None) => {
+                        thread::sleep(Duration::from_millis(50));
+                        continue;
+                    }
+                    Err(err) => {
+                        log::error!("poll_for_event failed: {}", err);
+                        break;
+                    }
+                };
+
+                let is_active_window_changed = matches!(
+                    event,
+                    Event::X(xcb::x::Event::PropertyNotify(ref property_notify))
+                        if property_notify.atom() == active_window_atom
+                );
+
+                if !is_active_window_changed {
+                    continue;
+                }
+
+                let active_window = match get_active_window(&conn, root_window) {
+                    Ok(active_window) => active_window,
+                    Err(err) => {
+                        log::error!("get_active_window failed: {}", err);
+                        continue;
+                    }
+                };
+
+                if active_window == last_active_window {
+                    continue;
+                }
+
+                last_active_window = active_window;
+
+                if let Some(window) = active_window {
+                    callback(ImplWindow::new(window));
+                }
+            }
+        });
+
+        Ok(ImplForegroundWatcher { running })
+    }
+
+    pub fn stop(&self) -> XCapResult<()> {
+        self.running.store(false, Ordering::Relaxed);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use xcb::{
+    x::{GetKeyboardMapping, GrabKey, GrabMode, Keycode, ModMask, UngrabKey, Window},
+    Connection, Event,
+};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    hotkey::{HotKey, HotKeyCode},
+};
+
+type Callback = Box<dyn FnMut() + Send>;
+
+fn get_keysym(code: HotKeyCode) -> u32 {
+    // X11/keysymdef.h
+    match code {
+        HotKeyCode::PrintScreen => 0xFF61,
+        HotKeyCode::F1 => 0xFFBE,
+        HotKeyCode::F2 => 0xFFBF,
+        HotKeyCode::F3 => 0xFFC0,
+        HotKeyCode::F4 => 0xFFC1,
+        HotKeyCode::F5 => 0xFFC2,
+        HotKeyCode::F6 => 0xFFC3,
+        HotKeyCode::F7 => 0xFFC4,
+        HotKeyCode::F8 => 0xFFC5,
+        HotKeyCode::F9 => 0xFFC6,
+        HotKeyCode::F10 => 0xFFC7,
+        HotKeyCode::F11 => 0xFFC8,
+        HotKeyCode::F12 => 0xFFC9,
+    }
+}
+
+fn get_keycode(conn: &Connection, keysym: u32) -> XCapResult<Keycode> {
+    let setup = conn.get_setup();
+    let min_keycode = setup.min_keycode();
+    let max_keycode = setup.max_keycode();
+
+    let get_keyboard_mapping_cookie = conn.send_request(&GetKeyboardMapping {
+        first_keycode: min_keycode,
+        count: max_keycode - min_keycode + 1,
+    });
+    let get_keyboard_mapping_reply = conn.wait_for_reply(get_keyboard_mapping_cookie)?;
+
+    let keysyms_per_keycode = get_keyboard_mapping_reply.keysyms_per_keycode() as usize;
+    let index = get_keyboard_mapping_reply
+        .keysyms()
+        .iter()
+        .position(|&val| val == keysym)
+        .ok_or_else(|| XCapError::new(format!("Not found keycode of keysym {:#x}", keysym)))?;
+
+    Ok(min_keycode + (index / keysyms_per_keycode) as u8)
+}
+
+fn get_mod_mask(hotkey: HotKey) -> ModMask {
+    let mut mod_mask = ModMask::empty();
+
+    if hotkey.modifiers.control {
+        mod_mask |= ModMask::CONTROL;
+    }
+    if hotkey.modifiers.alt {
+        mod_mask |= ModMask::N1;
+    }
+    if hotkey.modifiers.shift {
+        mod_mask |= ModMask::SHIFT;
+    }
+    if hotkey.modifiers.meta {
+        mod_mask |= ModMask::N4;
+    }
+
+    mod_mask
+}
+
+// 按键抓取要求修饰键完全一致，同时抓取 CapsLock 和 NumLock 开启时的组合
+fn get_lock_mod_masks(mod_mask: ModMask) -> [ModMask; 4] {
+    [
+        mod_mask,
+        mod_mask | ModMask::LOCK,
+        mod_mask | ModMask::N2,
+        mod_mask | ModMask::LOCK | ModMask::N2,
+    ]
+}
+
+#[derive(Debug)]
+pub struct ImplHotKeyListener {
+    running: Arc<AtomicBool>,
+}
+
+impl ImplHotKeyListener {
+    pub fn new(hotkey: HotKey, mut callback: Callback) -> XCapResult<Self> {
+        // 使用单独的连接接收事件，避免影响全局连接上的请求
+        let (conn, index) = Connection::connect(None)?;
+
+        let root_window: Window = conn
+            .get_setup()
+            .roots()
+            .nth(index as usize)
+            .ok_or_else(|| XCapError::new("Not found screen"))?
+            .root();
+
+        let keycode = get_keycode(&conn, get_keysym(hotkey.code))?;
+        let mod_masks = get_lock_mod_masks(get_mod_mask(hotkey));
+
+        for modifiers in mod_masks {
+            conn.send_and_check_request(&GrabKey {
+                owner_events: false,
+                grab_window: root_window,
+                modifiers,
+                key: keycode,
+                pointer_mode: GrabMode::Async,
+                keyboard_mode: GrabMode::Async,
+            })
+            .map_err(|err| {
+                XCapError::with_source(
+                    "GrabKey failed, the hotkey may be used by another application",
+                    xcb::Error::from(err),
+                )
+            })?;
+        }
+
+        let running = Arc::new(AtomicBool::new(true));
+        let running_flag = running.clone();
+
+        thread::spawn(move || {
+            while running_flag.load(Ordering::Relaxed) {
+                let event = match conn.poll_for_event() {
+                    Ok(Some(event)) => event,
+                    Ok(None) => {
+                        thread::sleep(Duration::from_millis(20));
+                        continue;
+                    }
+                    Err(err) => {
+                        log::error!("poll_for_event failed: {}", err);
+                        break;
+                    }
+                };
+
+                if let Event::X(xcb::x::Event::KeyPress(key_press)) = event {
+                    if key_press.detail() == keycode {
+                        callback();
+                    }
+                }
+            }
+
+            for modifiers in mod_masks {
+                conn.send_request(&UngrabKey {
+                    key: keycode,
+                    grab_window: root_window,
+                    modifiers,
+                });
+            }
+
+            if let Err(err) = conn.flush() {
+                log::error!("UngrabKey failed: {}", err);
+            }
+        });
+
+        Ok(ImplHotKeyListener { running })
+    }
+
+    pub fn stop(&self) -> XCapResult<()> {
+        self.running.store(false, Ordering::Relaxed);
+
+        Ok(())
+    }
+}
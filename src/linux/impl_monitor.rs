@@ -1,38 +1,55 @@
 use std::{ffi::CStr, sync::mpsc::Receiver};
 
-use image::RgbaImage;
+use image::{
+    imageops::{resize, FilterType},
+    RgbaImage,
+};
 use xcb::{
     randr::{
-        GetCrtcInfo, GetMonitors, GetOutputInfo, GetOutputProperty, GetScreenResources, Mode,
-        ModeFlag, ModeInfo, Output, Rotation,
+        Connection, GetCrtcGamma, GetCrtcGammaSize, GetCrtcInfo, GetMonitors, GetOutputInfo,
+        GetOutputProperty, GetScreenResources, Mode, ModeFlag, ModeInfo, Output, Rotation,
+        SetCrtcGamma,
     },
-    x::{GetProperty, ATOM_ANY, ATOM_RESOURCE_MANAGER, ATOM_STRING, CURRENT_TIME},
-    Xid,
+    x::{GetProperty, ATOM_ANY, ATOM_CARDINAL, ATOM_RESOURCE_MANAGER, ATOM_STRING, CURRENT_TIME},
+    Xid, XidNew,
 };
 
 use crate::{
+    cancellation_token::CancellationToken,
+    capture_config::Backend,
+    capture_quality::CaptureQuality,
+    display_mode::DisplayMode,
+    edid::parse_edid_id,
     error::{XCapError, XCapResult},
+    frame_time::Acceleration,
+    gamma_ramp::GammaRamp,
+    native_image::{NativeImage, PixelFormat},
+    raw_handle::RawMonitorHandle,
+    rect::Rect,
+    scale_factor::{get_scale_factor_override, ScaleFactorSource},
     video_recorder::Frame,
 };
 
 use super::{
-    capture::capture_monitor,
+    capture::{
+        capture_monitor, capture_monitor_area, capture_monitor_cancellable,
+        capture_monitor_with_backend,
+    },
     impl_video_recorder::ImplVideoRecorder,
+    restore_token::{load_restore_token, save_restore_token},
+    session::{session_type, SessionType},
     utils::{get_atom, get_current_screen_buf, get_monitor_info_buf, get_xcb_connection_and_index},
+    vblank::wait_for_vblank,
+    wayland_capture::wayland_request_monitor_permission,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct ImplMonitor {
     pub output: Output,
 }
 
 // per https://gitlab.freedesktop.org/xorg/app/xrandr/-/blob/master/xrandr.c#L576
-fn get_current_frequency(mode_infos: Vec<ModeInfo>, mode: Mode) -> f32 {
-    let mode_info = match mode_infos.iter().find(|m| m.id == mode.resource_id()) {
-        Some(mode_info) => mode_info,
-        None => return 0.0,
-    };
-
+fn get_mode_frequency(mode_info: &ModeInfo) -> f32 {
     let vtotal = {
         let mut val = mode_info.vtotal;
         if mode_info.mode_flags.contains(ModeFlag::DOUBLE_SCAN) {
@@ -51,7 +68,14 @@ fn get_current_frequency(mode_infos: Vec<ModeInfo>, mode: Mode) -> f32 {
     }
 }
 
-fn get_scale_factor() -> XCapResult<f32> {
+fn get_current_frequency(mode_infos: Vec<ModeInfo>, mode: Mode) -> f32 {
+    match mode_infos.iter().find(|m| m.id == mode.resource_id()) {
+        Some(mode_info) => get_mode_frequency(mode_info),
+        None => 0.0,
+    }
+}
+
+pub(super) fn get_scale_factor() -> XCapResult<f32> {
     let (conn, _) = get_xcb_connection_and_index()?;
 
     let screen_buf = get_current_screen_buf()?;
@@ -78,7 +102,9 @@ fn get_scale_factor() -> XCapResult<f32> {
         .strip_prefix(xft_dpi_prefix)
         .ok_or_else(|| XCapError::new("Xft.dpi parse failed"))?;
 
-    let dpi = xft_dpi.parse::<f32>().map_err(XCapError::new)?;
+    let dpi = xft_dpi
+        .parse::<f32>()
+        .map_err(|err| XCapError::with_source("Xft.dpi parse failed", err))?;
 
     Ok(dpi / 96.0)
 }
@@ -150,6 +176,44 @@ fn get_output_edid(output: Output) -> XCapResult<Vec<u8>> {
     Ok(edid)
 }
 
+// https://specifications.freedesktop.org/wm-spec/1.5/ar01s03.html#id-1.4.10
+fn get_current_work_area() -> XCapResult<(i32, i32, i32, i32)> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+
+    let screen_buf = get_current_screen_buf()?;
+
+    let current_desktop_cookie = conn.send_request(&GetProperty {
+        delete: false,
+        window: screen_buf.root(),
+        property: get_atom("_NET_CURRENT_DESKTOP")?,
+        r#type: ATOM_CARDINAL,
+        long_offset: 0,
+        long_length: 1,
+    });
+    let current_desktop_reply = conn.wait_for_reply(current_desktop_cookie)?;
+    let current_desktop = current_desktop_reply
+        .value::<u32>()
+        .first()
+        .copied()
+        .unwrap_or(0);
+
+    // _NET_WORKAREA 为每个桌面一组 x, y, width, height
+    let work_area_cookie = conn.send_request(&GetProperty {
+        delete: false,
+        window: screen_buf.root(),
+        property: get_atom("_NET_WORKAREA")?,
+        r#type: ATOM_CARDINAL,
+        long_offset: current_desktop * 4,
+        long_length: 4,
+    });
+    let work_area_reply = conn.wait_for_reply(work_area_cookie)?;
+
+    match work_area_reply.value::<u32>() {
+        &[x, y, width, height] => Ok((x as i32, y as i32, width as i32, height as i32)),
+        _ => Err(XCapError::new("Get _NET_WORKAREA failed")),
+    }
+}
+
 fn is_builtin_edid(edid: &[u8]) -> bool {
     const DESCRIPTOR_OFFSET: usize = 0x36;
 
@@ -179,6 +243,20 @@ impl ImplMonitor {
         ImplMonitor { output }
     }
 
+    pub fn from_raw_handle(raw_handle: RawMonitorHandle) -> XCapResult<ImplMonitor> {
+        let RawMonitorHandle::RandR { output } = raw_handle else {
+            return Err(XCapError::new("Not a RandR output"));
+        };
+
+        Ok(ImplMonitor::new(Output::new(output)))
+    }
+
+    pub fn raw_handle(&self) -> RawMonitorHandle {
+        RawMonitorHandle::RandR {
+            output: self.output.resource_id(),
+        }
+    }
+
     pub fn all() -> XCapResult<Vec<ImplMonitor>> {
         let (conn, _) = get_xcb_connection_and_index()?;
 
@@ -204,6 +282,52 @@ impl ImplMonitor {
         Ok(impl_monitors)
     }
 
+    pub fn all_including_inactive() -> XCapResult<Vec<(String, bool, Option<ImplMonitor>)>> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let screen_buf = get_current_screen_buf()?;
+
+        let get_screen_resources_cookie = conn.send_request(&GetScreenResources {
+            window: screen_buf.root(),
+        });
+        let get_screen_resources_reply = conn.wait_for_reply(get_screen_resources_cookie)?;
+
+        let active_impl_monitors = ImplMonitor::all()?;
+        let mut known_monitors = Vec::new();
+
+        for &output in get_screen_resources_reply.outputs() {
+            let get_output_info_cookie = conn.send_request(&GetOutputInfo {
+                output,
+                config_timestamp: CURRENT_TIME,
+            });
+            let get_output_info_reply = conn.wait_for_reply(get_output_info_cookie)?;
+
+            // X11 不会记录断开的显示器，未连接的输出只是显卡上的接口
+            if get_output_info_reply.connection() != Connection::Connected {
+                continue;
+            }
+
+            let name = String::from_utf8(get_output_info_reply.name().to_vec())?;
+            let impl_monitor = ImplMonitor::new(output);
+            let is_active = active_impl_monitors.contains(&impl_monitor);
+
+            known_monitors.push((name, is_active, is_active.then_some(impl_monitor)));
+        }
+
+        Ok(known_monitors)
+    }
+
+    pub fn virtual_desktop_bounds() -> XCapResult<Rect> {
+        // RandR 显示器都位于根窗口中，根窗口的大小就是整个虚拟桌面
+        let screen_buf = get_current_screen_buf()?;
+
+        Ok(Rect::new(
+            0,
+            0,
+            screen_buf.width_in_pixels() as u32,
+            screen_buf.height_in_pixels() as u32,
+        ))
+    }
+
     pub fn from_point(x: i32, y: i32) -> XCapResult<ImplMonitor> {
         let (conn, _) = get_xcb_connection_and_index()?;
 
@@ -257,6 +381,17 @@ impl ImplMonitor {
         Ok(name)
     }
 
+    pub fn connector(&self) -> XCapResult<String> {
+        // RandR 输出的名称就是接口名称，例如 HDMI-1
+        self.name()
+    }
+
+    pub fn edid_id(&self) -> XCapResult<Option<String>> {
+        let edid = get_output_edid(self.output)?;
+
+        Ok(parse_edid_id(&edid))
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         let x = get_monitor_info_buf(self.output)?.x();
         let scale_factor = self.scale_factor()?;
@@ -293,17 +428,83 @@ impl ImplMonitor {
     }
 
     pub fn scale_factor(&self) -> XCapResult<f32> {
+        if let Some(scale_factor) = get_scale_factor_override(self.id()?) {
+            return Ok(scale_factor);
+        }
+
         let scale_factor = get_scale_factor().unwrap_or(1.0);
 
         Ok(scale_factor)
     }
 
+    pub fn pixel_scale(&self) -> XCapResult<f32> {
+        // 坐标就是物理像素
+        Ok(1.0)
+    }
+
+    pub fn scale_factor_source(&self) -> XCapResult<ScaleFactorSource> {
+        if get_scale_factor_override(self.id()?).is_some() {
+            return Ok(ScaleFactorSource::Override);
+        }
+
+        let scale_factor_source = match get_scale_factor() {
+            Ok(_) => ScaleFactorSource::Detected,
+            Err(_) => ScaleFactorSource::Fallback,
+        };
+
+        Ok(scale_factor_source)
+    }
+
     pub fn frequency(&self) -> XCapResult<f32> {
         let mode_infos = get_mode_infos()?;
         let (_, frequency) = get_rotation_frequency(mode_infos, &self.output).unwrap_or((0.0, 0.0));
         Ok(frequency)
     }
 
+    pub fn bits_per_pixel(&self) -> XCapResult<u32> {
+        // X11 所有显示器共用屏幕的色深
+        Ok(get_current_screen_buf()?.root_depth() as u32)
+    }
+
+    pub fn is_hdr(&self) -> XCapResult<bool> {
+        // X11 不支持 HDR 输出
+        Ok(false)
+    }
+
+    pub fn available_modes(&self) -> XCapResult<Vec<DisplayMode>> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let mode_infos = get_mode_infos()?;
+        // X11 所有模式共用屏幕的色深
+        let bit_depth = get_current_screen_buf()?.root_depth() as u32;
+
+        let get_output_info_cookie = conn.send_request(&GetOutputInfo {
+            output: self.output,
+            config_timestamp: CURRENT_TIME,
+        });
+        let get_output_info_reply = conn.wait_for_reply(get_output_info_cookie)?;
+
+        let mut modes = Vec::new();
+        for mode in get_output_info_reply.modes() {
+            let mode_info = match mode_infos.iter().find(|m| m.id == mode.resource_id()) {
+                Some(mode_info) => mode_info,
+                None => continue,
+            };
+
+            let mode = DisplayMode {
+                width: mode_info.width as u32,
+                height: mode_info.height as u32,
+                refresh_rate: get_mode_frequency(mode_info),
+                bit_depth,
+            };
+
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+
+        Ok(modes)
+    }
+
     pub fn is_primary(&self) -> XCapResult<bool> {
         let primary = get_monitor_info_buf(self.output)?.primary();
 
@@ -322,10 +523,271 @@ impl ImplMonitor {
         Ok(is_builtin_edid(&edid))
     }
 
+    pub fn gamma_ramp(&self) -> XCapResult<GammaRamp> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let get_output_info_cookie = conn.send_request(&GetOutputInfo {
+            output: self.output,
+            config_timestamp: CURRENT_TIME,
+        });
+        let get_output_info_reply = conn.wait_for_reply(get_output_info_cookie)?;
+
+        let get_crtc_gamma_cookie = conn.send_request(&GetCrtcGamma {
+            crtc: get_output_info_reply.crtc(),
+        });
+        let get_crtc_gamma_reply = conn.wait_for_reply(get_crtc_gamma_cookie)?;
+
+        Ok(GammaRamp {
+            red: get_crtc_gamma_reply.red().to_vec(),
+            green: get_crtc_gamma_reply.green().to_vec(),
+            blue: get_crtc_gamma_reply.blue().to_vec(),
+        })
+    }
+
+    pub fn set_gamma_ramp(&self, gamma_ramp: &GammaRamp) -> XCapResult<()> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let get_output_info_cookie = conn.send_request(&GetOutputInfo {
+            output: self.output,
+            config_timestamp: CURRENT_TIME,
+        });
+        let get_output_info_reply = conn.wait_for_reply(get_output_info_cookie)?;
+        let crtc = get_output_info_reply.crtc();
+
+        let get_crtc_gamma_size_cookie = conn.send_request(&GetCrtcGammaSize { crtc });
+        let gamma_size = conn.wait_for_reply(get_crtc_gamma_size_cookie)?.size() as usize;
+
+        if gamma_ramp.red.len() != gamma_size {
+            return Err(XCapError::new(format!(
+                "The CRTC gamma ramp needs {} entries per channel",
+                gamma_size
+            )));
+        }
+
+        conn.send_and_check_request(&SetCrtcGamma {
+            crtc,
+            red: &gamma_ramp.red,
+            green: &gamma_ramp.green,
+            blue: &gamma_ramp.blue,
+        })
+        .map_err(xcb::Error::from)?;
+
+        Ok(())
+    }
+
+    pub fn work_area(&self) -> XCapResult<Rect> {
+        let monitor_info_buf = get_monitor_info_buf(self.output)?;
+        let scale_factor = self.scale_factor()?;
+
+        let monitor_x = monitor_info_buf.x() as i32;
+        let monitor_y = monitor_info_buf.y() as i32;
+        let monitor_width = monitor_info_buf.width() as i32;
+        let monitor_height = monitor_info_buf.height() as i32;
+
+        // _NET_WORKAREA 是所有显示器的工作区，需要与当前显示器求交集
+        let (left, top, right, bottom) = match get_current_work_area() {
+            Ok((x, y, width, height)) => (
+                x.max(monitor_x),
+                y.max(monitor_y),
+                (x + width).min(monitor_x + monitor_width),
+                (y + height).min(monitor_y + monitor_height),
+            ),
+            Err(err) => {
+                log::info!("get_current_work_area failed: {}", err);
+                (
+                    monitor_x,
+                    monitor_y,
+                    monitor_x + monitor_width,
+                    monitor_y + monitor_height,
+                )
+            }
+        };
+
+        Ok(Rect::new(
+            (left as f32 / scale_factor) as i32,
+            (top as f32 / scale_factor) as i32,
+            ((right - left).max(0) as f32 / scale_factor) as u32,
+            ((bottom - top).max(0) as f32 / scale_factor) as u32,
+        ))
+    }
+
+    pub fn is_secure_desktop(&self) -> XCapResult<bool> {
+        Ok(false)
+    }
+
+    pub fn has_capture_permission(&self) -> bool {
+        // X11 截图不需要授权，Wayland 下有恢复令牌时 ScreenCast 不会弹出对话框
+        if session_type() == SessionType::Xorg {
+            return true;
+        }
+
+        cfg!(feature = "pipewire")
+            && self
+                .connector()
+                .is_ok_and(|connector| load_restore_token(&connector).is_some())
+    }
+
+    pub fn request_capture_permission(&self) -> XCapResult<()> {
+        if session_type() == SessionType::Xorg {
+            return Ok(());
+        }
+
+        let connector = self.connector()?;
+        let (position, restore_token) = wayland_request_monitor_permission()?;
+
+        // 用户可能在对话框中选择了其他显示器，令牌保存到实际选择的显示器上
+        let selected_connector = match position {
+            Some((x, y)) => ImplMonitor::all()?
+                .into_iter()
+                .find(|impl_monitor| {
+                    impl_monitor.x().is_ok_and(|item| item == x)
+                        && impl_monitor.y().is_ok_and(|item| item == y)
+                })
+                .map(|impl_monitor| impl_monitor.connector())
+                .transpose()?
+                .unwrap_or_else(|| connector.clone()),
+            None => connector.clone(),
+        };
+
+        save_restore_token(&selected_connector, Some(&restore_token))?;
+
+        if selected_connector != connector {
+            return Err(XCapError::new(format!(
+                "Selected monitor {} in the ScreenCast dialog instead of {}",
+                selected_connector, connector
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn forget_capture_permission(&self) -> XCapResult<()> {
+        save_restore_token(&self.connector()?, None)
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         capture_monitor(self)
     }
 
+    pub fn capture_image_with_acceleration(&self) -> XCapResult<(RgbaImage, Acceleration)> {
+        // Wayland 下由合成器读取 GPU 合成的画面，X11 的 GetImage 由 X server 复制
+        let acceleration = if session_type() == SessionType::Wayland {
+            Acceleration::Hardware
+        } else {
+            Acceleration::Software
+        };
+
+        Ok((self.capture_image()?, acceleration))
+    }
+
+    pub fn wait_for_vblank(&self) -> XCapResult<()> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let screen_buf = get_current_screen_buf()?;
+
+        let get_screen_resources_cookie = conn.send_request(&GetScreenResources {
+            window: screen_buf.root(),
+        });
+        let get_screen_resources_reply = conn.wait_for_reply(get_screen_resources_cookie)?;
+
+        let get_output_info_cookie = conn.send_request(&GetOutputInfo {
+            output: self.output,
+            config_timestamp: CURRENT_TIME,
+        });
+        let get_output_info_reply = conn.wait_for_reply(get_output_info_cookie)?;
+
+        // CRTC 在屏幕资源中的序号即 DRM 的 pipe
+        let crtc = get_output_info_reply.crtc();
+        let pipe = get_screen_resources_reply
+            .crtcs()
+            .iter()
+            .position(|&item| item == crtc)
+            .ok_or_else(|| XCapError::new("Not found CRTC of the monitor"))?;
+
+        wait_for_vblank(pipe as u32)
+    }
+
+    pub fn capture_image_cancellable(&self, token: &CancellationToken) -> XCapResult<RgbaImage> {
+        capture_monitor_cancellable(self, token)
+    }
+
+    pub fn capture_backends(&self) -> Vec<(&'static str, CaptureQuality)> {
+        // 所有质量都使用同一种截图方式
+        let backend = if session_type() == SessionType::Wayland {
+            "Wayland D-Bus screenshot"
+        } else {
+            "X11 GetImage"
+        };
+
+        vec![(backend, CaptureQuality::Balanced)]
+    }
+
+    pub fn default_backend_order() -> Vec<Backend> {
+        if session_type() == SessionType::Wayland {
+            vec![Backend::Wayland]
+        } else {
+            vec![Backend::X11]
+        }
+    }
+
+    pub fn capture_image_with_backend(&self, backend: Backend) -> XCapResult<RgbaImage> {
+        capture_monitor_with_backend(self, backend)
+    }
+
+    pub fn capture_image_with_quality(&self, _quality: CaptureQuality) -> XCapResult<RgbaImage> {
+        // X11 只有 GetImage 一种截图方式
+        self.capture_image()
+    }
+
+    pub fn capture_image_native(&self) -> XCapResult<NativeImage> {
+        // X11 的像素格式和字节序各不相同，总是需要逐像素转换，直接使用 RGBA
+        let image = self.capture_image()?;
+        let (width, height) = image.dimensions();
+
+        Ok(NativeImage::new(
+            width,
+            height,
+            PixelFormat::Rgba8,
+            image.into_raw(),
+        ))
+    }
+
+    pub fn capture_secure_desktop(&self) -> XCapResult<RgbaImage> {
+        Err(XCapError::Unsupported(
+            "Capturing the lock screen is only supported on Windows".to_string(),
+        ))
+    }
+
+    pub fn capture_area(&self, area: Rect) -> XCapResult<RgbaImage> {
+        // right/bottom 是饱和计算的，超出 i32 范围的区域也会被裁剪到显示器内
+        let area = Rect::new(0, 0, self.width()?, self.height()?)
+            .clamp_relative(&area)
+            .ok_or_else(|| XCapError::new("Capture area is outside the monitor"))?;
+        let scale_factor = self.scale_factor()?;
+
+        capture_monitor_area(
+            self,
+            (area.x as f32 * scale_factor) as u32,
+            (area.y as f32 * scale_factor) as u32,
+            (area.width as f32 * scale_factor) as u32,
+            (area.height as f32 * scale_factor) as u32,
+        )
+    }
+
+    pub fn capture_area_scaled(
+        &self,
+        area: Rect,
+        width: u32,
+        height: u32,
+    ) -> XCapResult<RgbaImage> {
+        let image = self.capture_area(area)?;
+
+        Ok(resize(&image, width, height, FilterType::Triangle))
+    }
+
+    pub fn capture_scaled(&self, width: u32, height: u32) -> XCapResult<RgbaImage> {
+        let image = self.capture_image()?;
+
+        Ok(resize(&image, width, height, FilterType::Triangle))
+    }
+
     pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
         ImplVideoRecorder::new(self.clone())
     }
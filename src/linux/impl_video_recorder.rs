@@ -1,10 +1,12 @@
 use super::impl_monitor::ImplMonitor;
+use crate::diagnostic::{report, Diagnostic, DiagnosticTarget};
 use crate::error::{XCapError, XCapResult};
 use crate::video_recorder::{Frame, RecorderWaker};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
+use xcb::Xid;
 
 #[derive(Debug, Clone)]
 pub struct ImplVideoRecorder {
@@ -62,11 +64,15 @@ impl ImplVideoRecorder {
                     let frame = Frame::new(width, height, raw);
                     if let Err(e) = sender.send(frame) {
                         eprintln!("Failed to send frame: {:?}", e);
-                        break Err(XCapError::new(&format!("Failed to send frame: {}", e)));
+                        break Err(XCapError::new(format!("Failed to send frame: {}", e)));
                     }
                 }
                 Err(e) => {
-                    eprintln!("Failed to capture frame: {:?}", e);
+                    report(Diagnostic::new(
+                        DiagnosticTarget::Monitor(monitor.output.resource_id()),
+                        "capture_image",
+                        e,
+                    ));
                     thread::sleep(Duration::from_millis(10));
                     continue;
                 }
@@ -1,22 +1,30 @@
+use std::fs;
+
 use image::RgbaImage;
 use xcb::{
     x::{
-        Atom, Drawable, GetGeometry, GetProperty, GetPropertyReply, QueryPointer,
-        TranslateCoordinates, Window, ATOM_ATOM, ATOM_CARDINAL, ATOM_NONE, ATOM_STRING,
-        ATOM_WM_CLASS, ATOM_WM_NAME,
+        Atom, ClientMessageData, ClientMessageEvent, Drawable, EventMask, GetGeometry, GetProperty,
+        GetPropertyReply, QueryPointer, SendEvent, SendEventDest, TranslateCoordinates, Window,
+        ATOM_ATOM, ATOM_CARDINAL, ATOM_NONE, ATOM_STRING, ATOM_WM_CLASS, ATOM_WM_NAME,
+        CURRENT_TIME,
     },
-    Xid,
+    Xid, XidNew,
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    alpha_mode::AlphaMode,
+    error::{XCapError, XCapResult},
+    raw_handle::RawWindowHandle,
+    rect::Rect,
+};
 
 use super::{
-    capture::capture_window,
+    capture::{capture_window, capture_window_with_picker},
     impl_monitor::ImplMonitor,
-    utils::{get_atom, get_xcb_connection_and_index},
+    utils::{get_atom, get_monitor_info_buf, get_xcb_connection_and_index},
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct ImplWindow {
     pub window: Window,
 }
@@ -56,6 +64,19 @@ pub fn get_window_pid(window: &Window) -> XCapResult<u32> {
         .copied()
 }
 
+fn get_process_name(pid: u32) -> XCapResult<String> {
+    // /proc/<pid>/exe 可能因为权限不足无法读取，此时回退到 /proc/<pid>/comm
+    if let Ok(path) = fs::read_link(format!("/proc/{}/exe", pid)) {
+        if let Some(file_name) = path.file_name() {
+            return Ok(file_name.to_string_lossy().to_string());
+        }
+    }
+
+    let comm = fs::read_to_string(format!("/proc/{}/comm", pid))?;
+
+    Ok(comm.trim_end().to_string())
+}
+
 fn get_active_window_id() -> XCapResult<u32> {
     let (conn, _) = get_xcb_connection_and_index()?;
     let active_window_atom = get_atom("_NET_ACTIVE_WINDOW")?;
@@ -106,15 +127,11 @@ fn get_window_state(window: &Window) -> XCapResult<(bool, bool)> {
     let wm_state_reply = get_window_property(*window, wm_state_atom, ATOM_ATOM, 0, 12)?;
     let wm_state = wm_state_reply.value::<Atom>();
 
-    let is_minimized = wm_state.iter().any(|&state| state == wm_state_hidden_atom);
+    let is_minimized = wm_state.contains(&wm_state_hidden_atom);
 
-    let is_maximized_vert = wm_state
-        .iter()
-        .any(|&state| state == wm_state_maximized_vert_atom);
+    let is_maximized_vert = wm_state.contains(&wm_state_maximized_vert_atom);
 
-    let is_maximized_horz = wm_state
-        .iter()
-        .any(|&state| state == wm_state_maximized_horz_atom);
+    let is_maximized_horz = wm_state.contains(&wm_state_maximized_horz_atom);
 
     Ok((
         is_minimized,
@@ -123,10 +140,35 @@ fn get_window_state(window: &Window) -> XCapResult<(bool, bool)> {
 }
 
 impl ImplWindow {
-    fn new(window: Window) -> ImplWindow {
+    pub fn new(window: Window) -> ImplWindow {
         ImplWindow { window }
     }
 
+    pub fn from_raw_handle(raw_handle: RawWindowHandle) -> XCapResult<ImplWindow> {
+        let RawWindowHandle::Xcb { window } = raw_handle else {
+            return Err(XCapError::new("Not an X11 window"));
+        };
+
+        Ok(ImplWindow::new(Window::new(window)))
+    }
+
+    pub fn from_automation_id(_automation_id: &str) -> XCapResult<ImplWindow> {
+        Err(XCapError::Unsupported(
+            "UI Automation is only supported on Windows".to_string(),
+        ))
+    }
+
+    pub fn raw_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Xcb {
+            window: self.window.resource_id(),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        // 窗口销毁后 GetGeometry 返回 BadDrawable
+        get_position_and_size(&self.window).is_ok()
+    }
+
     pub fn all() -> XCapResult<Vec<ImplWindow>> {
         let (conn, _) = get_xcb_connection_and_index()?;
 
@@ -168,6 +210,51 @@ impl ImplWindow {
 
         Ok(impl_windows)
     }
+
+    pub fn all_limited(max: usize) -> XCapResult<Vec<ImplWindow>> {
+        // _NET_CLIENT_LIST_STACKING 一次返回全部窗口，且从底层开始排列，
+        // 构造 ImplWindow 不会发送请求，所以直接截取前面的窗口
+        let mut impl_windows = ImplWindow::all()?;
+        impl_windows.truncate(max);
+
+        Ok(impl_windows)
+    }
+
+    pub fn shell_surfaces() -> XCapResult<Vec<ImplWindow>> {
+        let window_type_atom = get_atom("_NET_WM_WINDOW_TYPE")?;
+        let dock_atom = get_atom("_NET_WM_WINDOW_TYPE_DOCK")?;
+
+        // 面板的窗口类型为 _NET_WM_WINDOW_TYPE_DOCK
+        let impl_windows = ImplWindow::all()?
+            .into_iter()
+            .filter(|impl_window| {
+                get_window_property(impl_window.window, window_type_atom, ATOM_ATOM, 0, 1024)
+                    .map(|reply| reply.value::<Atom>().contains(&dock_atom))
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        Ok(impl_windows)
+    }
+
+    pub fn all_spaces() -> XCapResult<Vec<ImplWindow>> {
+        ImplWindow::all()
+    }
+
+    pub fn all_including_untitled() -> XCapResult<Vec<ImplWindow>> {
+        ImplWindow::all()
+    }
+
+    pub fn foreground() -> XCapResult<Option<ImplWindow>> {
+        let active_window_id = get_active_window_id()?;
+
+        // 没有活动窗口时 _NET_ACTIVE_WINDOW 为 None (0)
+        if active_window_id == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(ImplWindow::new(Window::new(active_window_id))))
+    }
 }
 
 impl ImplWindow {
@@ -194,6 +281,10 @@ impl ImplWindow {
         Ok(app_name)
     }
 
+    pub fn process_name(&self) -> XCapResult<String> {
+        get_process_name(self.pid()?)
+    }
+
     pub fn title(&self) -> XCapResult<String> {
         let get_title_reply = get_window_property(self.window, ATOM_WM_NAME, ATOM_STRING, 0, 1024)?;
 
@@ -209,29 +300,11 @@ impl ImplWindow {
             .ok_or(XCapError::new("Get screen info failed"))?
             .to_owned();
 
-        let (x, y, width, height) = get_position_and_size(&self.window)?;
-
-        let mut max_area = 0;
+        let mut max_coverage = 0.0;
         // window与哪一个monitor交集最大就属于那个monitor
-        for impl_monitor in impl_monitors {
-            let monitor_x = impl_monitor.x()?;
-            let monitor_y = impl_monitor.y()?;
-            let monitor_width = impl_monitor.width()?;
-            let monitor_height = impl_monitor.height()?;
-
-            let left = x.max(monitor_x);
-            let top = y.max(monitor_x);
-            let right = (x + width as i32).min(monitor_x + monitor_width as i32);
-            let bottom = (y + height as i32).min(monitor_y + monitor_height as i32);
-
-            // 与0比较，如果小于0则表示两个矩形无交集
-            let width = (right - left).max(0);
-            let height = (bottom - top).max(0);
-
-            let overlap_area = width * height;
-            // 获取最大的面积
-            if overlap_area > max_area {
-                max_area = overlap_area;
+        for (impl_monitor, coverage) in self.monitor_coverage()? {
+            if coverage > max_coverage {
+                max_coverage = coverage;
                 find_result = impl_monitor;
             }
         }
@@ -239,6 +312,35 @@ impl ImplWindow {
         Ok(find_result)
     }
 
+    pub fn monitor_coverage(&self) -> XCapResult<Vec<(ImplMonitor, f32)>> {
+        let (x, y, width, height) = get_position_and_size(&self.window)?;
+        let window_rect = Rect::new(x, y, width, height);
+        let window_area = window_rect.area();
+
+        let mut monitor_coverage = Vec::new();
+
+        if window_area == 0 {
+            return Ok(monitor_coverage);
+        }
+
+        for impl_monitor in ImplMonitor::all()? {
+            // 窗口坐标为物理像素，所以使用显示器的物理像素坐标计算交集
+            let monitor_info_buf = get_monitor_info_buf(impl_monitor.output)?;
+            let monitor_rect = Rect::new(
+                monitor_info_buf.x() as i32,
+                monitor_info_buf.y() as i32,
+                monitor_info_buf.width() as u32,
+                monitor_info_buf.height() as u32,
+            );
+
+            if let Some(overlap) = window_rect.intersection(&monitor_rect) {
+                monitor_coverage.push((impl_monitor, overlap.area() as f32 / window_area as f32));
+            }
+        }
+
+        Ok(monitor_coverage)
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         let (x, _, _, _) = get_position_and_size(&self.window)?;
 
@@ -294,7 +396,143 @@ impl ImplWindow {
         Ok(active_window_id == self.id()?)
     }
 
+    pub fn focus(&self) -> XCapResult<()> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let active_window_atom = get_atom("_NET_ACTIVE_WINDOW")?;
+        let get_geometry_cookie = conn.send_request(&GetGeometry {
+            drawable: Drawable::Window(self.window),
+        });
+        let root_window = conn.wait_for_reply(get_geometry_cookie)?.root();
+
+        // https://specifications.freedesktop.org/wm-spec/1.5/ar01s03.html#id-1.4.10
+        // source indication 为 1 表示请求来自普通应用程序
+        let client_message_event = ClientMessageEvent::new(
+            self.window,
+            active_window_atom,
+            ClientMessageData::Data32([1, CURRENT_TIME, 0, 0, 0]),
+        );
+
+        conn.send_and_check_request(&SendEvent {
+            propagate: false,
+            destination: SendEventDest::Window(root_window),
+            event_mask: EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event: &client_message_event,
+        })
+        .map_err(xcb::Error::from)?;
+
+        Ok(())
+    }
+
+    pub fn is_capture_protected(&self) -> XCapResult<bool> {
+        // X11 的窗口不能阻止截图
+        Ok(false)
+    }
+
+    pub fn alpha_mode(&self) -> XCapResult<AlphaMode> {
+        // 截图时 alpha 总是被设置为 255
+        Ok(AlphaMode::Straight)
+    }
+
+    pub fn opacity(&self) -> XCapResult<f32> {
+        // 没有窗口设置过透明度时，该 atom 可能不存在
+        let wm_window_opacity_atom = match get_atom("_NET_WM_WINDOW_OPACITY") {
+            Ok(atom) => atom,
+            Err(_) => return Ok(1.0),
+        };
+
+        let reply = get_window_property(self.window, wm_window_opacity_atom, ATOM_CARDINAL, 0, 1)?;
+
+        // 未设置该属性时窗口不透明，0xffffffff 表示完全不透明
+        let opacity = reply
+            .value::<u32>()
+            .first()
+            .map(|&opacity| opacity as f64 / u32::MAX as f64)
+            .unwrap_or(1.0);
+
+        Ok(opacity as f32)
+    }
+
+    pub fn frame_offset(&self) -> XCapResult<(i32, i32)> {
+        // 截图的范围就是窗口的范围
+        Ok((0, 0))
+    }
+
+    pub fn child_rect_at(&self, x: i32, y: i32) -> XCapResult<Rect> {
+        // X11 的坐标是 16 位的，超出范围时截断会得到错误的位置
+        let (src_x, src_y) = match (i16::try_from(x), i16::try_from(y)) {
+            (Ok(src_x), Ok(src_y)) => (src_x, src_y),
+            _ => return Err(XCapError::new("Point is outside the X11 coordinate range")),
+        };
+
+        let (conn, _) = get_xcb_connection_and_index()?;
+
+        // TranslateCoordinates 只返回直接子窗口，需要逐层向下查找
+        let mut window = self.window;
+        loop {
+            let translate_coordinates_cookie = conn.send_request(&TranslateCoordinates {
+                src_window: self.window,
+                dst_window: window,
+                src_x,
+                src_y,
+            });
+            let translate_coordinates_reply = conn.wait_for_reply(translate_coordinates_cookie)?;
+
+            let child = translate_coordinates_reply.child();
+            if child.is_none() {
+                break;
+            }
+
+            window = child;
+        }
+
+        if window == self.window {
+            return Err(XCapError::new("Not found child window"));
+        }
+
+        let (window_x, window_y, _, _) = get_position_and_size(&self.window)?;
+        let (child_x, child_y, child_width, child_height) = get_position_and_size(&window)?;
+
+        Ok(Rect::new(
+            child_x - window_x,
+            child_y - window_y,
+            child_width,
+            child_height,
+        ))
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         capture_window(self)
     }
+
+    pub fn capture_with_picker() -> XCapResult<RgbaImage> {
+        capture_window_with_picker()
+    }
+
+    pub fn capture_image_snapped(&self) -> XCapResult<(RgbaImage, Rect)> {
+        // X11 的窗口坐标已经是整数像素
+        let (x, y, _, _) = get_position_and_size(&self.window)?;
+        let image = self.capture_image()?;
+
+        let pixel_rect = Rect::new(x, y, image.width(), image.height());
+
+        Ok((image, pixel_rect))
+    }
+
+    pub fn capture_scrollable(&self) -> XCapResult<RgbaImage> {
+        // AT-SPI 没有统一的滚动接口
+        Err(XCapError::Unsupported(
+            "Capturing scrollable content is not supported on Linux".to_string(),
+        ))
+    }
+    pub fn clear_cache() -> XCapResult<()> {
+        // 没有缓存的截图
+        Ok(())
+    }
+
+    pub fn capture_all(impl_windows: &[ImplWindow]) -> XCapResult<Vec<RgbaImage>> {
+        impl_windows
+            .iter()
+            .map(|impl_window| impl_window.capture_image())
+            .collect()
+    }
 }
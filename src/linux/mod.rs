@@ -1,8 +1,20 @@
 mod capture;
+#[cfg(feature = "pipewire")]
+mod screen_cast;
 mod utils;
+mod vblank;
 mod wayland_capture;
 mod xorg_capture;
 
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+pub mod clock;
+pub mod cursor;
+pub mod impl_foreground_watcher;
+#[cfg(feature = "hotkey")]
+pub mod impl_hotkey_listener;
 pub mod impl_monitor;
 pub mod impl_video_recorder;
 pub mod impl_window;
+pub mod restore_token;
+pub mod session;
@@ -0,0 +1,91 @@
+use std::{collections::BTreeMap, env::var_os, fs, path::PathBuf, sync::Mutex};
+
+use crate::error::{XCapError, XCapResult};
+
+static RESTORE_TOKEN_PATH_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+fn default_restore_token_path() -> Option<PathBuf> {
+    // https://specifications.freedesktop.org/basedir-spec/latest/
+    let state_home = var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|state_home| state_home.is_absolute())
+        .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+
+    Some(state_home.join("xcap").join("restore_tokens"))
+}
+
+fn restore_token_path() -> Option<PathBuf> {
+    if let Ok(restore_token_path_override) = RESTORE_TOKEN_PATH_OVERRIDE.lock() {
+        if let Some(path) = restore_token_path_override.as_ref() {
+            return Some(path.clone());
+        }
+    }
+
+    default_restore_token_path()
+}
+
+/// Set the file where the ScreenCast portal restore tokens are saved, `None` restores the
+/// default `$XDG_STATE_HOME/xcap/restore_tokens`. Use a path owned by the application when
+/// several applications using xcap shouldn't share their permissions.
+pub fn set_restore_token_path(path: Option<PathBuf>) {
+    match RESTORE_TOKEN_PATH_OVERRIDE.lock() {
+        Ok(mut restore_token_path_override) => *restore_token_path_override = path,
+        Err(err) => log::error!("Set restore token path failed: {}", err),
+    }
+}
+
+// 每行一个显示器：接口名称\t令牌
+fn parse_restore_tokens(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(connector, token)| (connector.to_string(), token.to_string()))
+        .collect()
+}
+
+fn format_restore_tokens(restore_tokens: &BTreeMap<String, String>) -> String {
+    restore_tokens
+        .iter()
+        .map(|(connector, token)| format!("{}\t{}\n", connector, token))
+        .collect()
+}
+
+pub(super) fn load_restore_token(connector: &str) -> Option<String> {
+    let content = fs::read_to_string(restore_token_path()?).ok()?;
+
+    parse_restore_tokens(&content).remove(connector)
+}
+
+/// `None` 删除显示器的令牌
+pub(super) fn save_restore_token(connector: &str, token: Option<&str>) -> XCapResult<()> {
+    let path = restore_token_path()
+        .ok_or_else(|| XCapError::new("Get restore token path failed, HOME is not set"))?;
+
+    let mut restore_tokens = fs::read_to_string(&path)
+        .map(|content| parse_restore_tokens(&content))
+        .unwrap_or_default();
+
+    match token {
+        Some(token) => restore_tokens.insert(connector.to_string(), token.to_string()),
+        None => restore_tokens.remove(connector),
+    };
+
+    if let Some(dirname) = path.parent() {
+        fs::create_dir_all(dirname)?;
+    }
+    fs::write(&path, format_restore_tokens(&restore_tokens))?;
+
+    Ok(())
+}
+
+#[test]
+fn restore_tokens_round_trip() {
+    let mut restore_tokens = BTreeMap::new();
+    restore_tokens.insert("HDMI-1".to_string(), "token-a".to_string());
+    restore_tokens.insert("eDP-1".to_string(), "token-b".to_string());
+
+    let content = format_restore_tokens(&restore_tokens);
+
+    assert_eq!(content, "HDMI-1\ttoken-a\neDP-1\ttoken-b\n");
+    assert_eq!(parse_restore_tokens(&content), restore_tokens);
+}
@@ -0,0 +1,520 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::Cursor,
+    os::fd::{FromRawFd, OwnedFd},
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use dbus::{
+    arg::{self, PropMap, RefArg, Variant},
+    blocking::{stdintf::org_freedesktop_dbus::Properties, Connection, Proxy},
+    message::MatchRule,
+    Path,
+};
+use image::RgbaImage;
+use pipewire::{
+    context::Context,
+    main_loop::MainLoop,
+    properties::properties,
+    spa::{
+        param::{
+            format::{FormatProperties, MediaSubtype, MediaType},
+            video::{VideoFormat, VideoInfoRaw},
+            ParamType,
+        },
+        pod::{serialize::PodSerializer, Pod, Value},
+        utils::{Direction, Fraction, Rectangle, SpaTypes},
+    },
+    stream::{Stream, StreamFlags},
+};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::wayland_capture::OrgFreedesktopPortalRequestResponse;
+
+// org.freedesktop.portal.ScreenCast 的 SourceType
+const SOURCE_TYPE_MONITOR: u32 = 1;
+const SOURCE_TYPE_WINDOW: u32 = 2;
+
+// 授权一直有效，直到用户撤销
+const PERSIST_MODE_PERSISTENT: u32 = 2;
+
+// 等待 PipeWire 推送第一帧的时间
+const FRAME_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default)]
+struct PortalResponse {
+    status: u32,
+    session_handle: Option<String>,
+    node_id: Option<u32>,
+    position: Option<(i32, i32)>,
+    restore_token: Option<String>,
+}
+
+/// 一次 ScreenCast 的结果
+#[derive(Debug)]
+pub struct ScreenCastFrame {
+    pub image: RgbaImage,
+    /// 源在合成器坐标系中的位置，只有显示器源才有
+    pub position: Option<(i32, i32)>,
+    /// 下一次使用的恢复令牌，旧令牌在使用后失效
+    pub restore_token: Option<String>,
+}
+
+fn request_path(conn: &Connection, token: &str) -> XCapResult<Path<'static>> {
+    // https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Request.html
+    let sender = conn.unique_name().trim_start_matches(':').replace('.', "_");
+
+    Path::new(format!(
+        "/org/freedesktop/portal/desktop/request/{}/{}",
+        sender, token
+    ))
+    .map_err(XCapError::new)
+}
+
+fn new_token(name: &str) -> XCapResult<String> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+    Ok(format!("xcap_{}_{}", name, timestamp.as_micros()))
+}
+
+/// streams 的类型为 a(ua{sv})，只请求了一个源，取第一个 stream
+fn first_stream(results: &PropMap) -> Option<(u32, Option<(i32, i32)>)> {
+    let mut stream = results.get("streams")?.0.as_iter()?.next()?.as_iter()?;
+    let node_id = stream.next()?.as_u64()? as u32;
+
+    let mut position = None;
+    if let Some(mut properties) = stream.next().and_then(|properties| properties.as_iter()) {
+        while let (Some(key), Some(value)) = (properties.next(), properties.next()) {
+            if key.as_str() != Some("position") {
+                continue;
+            }
+
+            // position 的类型为 (ii)
+            let mut value = value
+                .as_iter()
+                .and_then(|mut value| value.next()?.as_iter());
+            position = value.as_mut().and_then(|value| {
+                Some((
+                    value.next()?.as_i64()? as i32,
+                    value.next()?.as_i64()? as i32,
+                ))
+            });
+        }
+    }
+
+    Some((node_id, position))
+}
+
+/// 调用 portal 方法并等待对应 Request 的 Response 信号
+fn call_with_response<F>(conn: &Connection, token: &str, call: F) -> XCapResult<PortalResponse>
+where
+    F: FnOnce() -> XCapResult<()>,
+{
+    let response: Arc<Mutex<Option<PortalResponse>>> = Arc::new(Mutex::new(None));
+    let response_res = response.clone();
+
+    // 先监听再调用，避免信号在拿到 Request 路径之前就已经发出
+    let match_rule = MatchRule::new_signal("org.freedesktop.portal.Request", "Response")
+        .with_path(request_path(conn, token)?);
+    let match_token = conn.add_match(
+        match_rule,
+        move |res: OrgFreedesktopPortalRequestResponse, _conn, _msg| {
+            let session_handle = res
+                .results
+                .get("session_handle")
+                .and_then(|session_handle| session_handle.as_str())
+                .map(String::from);
+
+            let stream = first_stream(&res.results);
+
+            let restore_token = res
+                .results
+                .get("restore_token")
+                .and_then(|restore_token| restore_token.as_str())
+                .map(String::from);
+
+            if let Ok(mut response) = response.lock() {
+                *response = Some(PortalResponse {
+                    status: res.status,
+                    session_handle,
+                    node_id: stream.map(|(node_id, _)| node_id),
+                    position: stream.and_then(|(_, position)| position),
+                    restore_token,
+                });
+            }
+
+            true
+        },
+    )?;
+
+    call()?;
+
+    // wait 60 seconds for user interaction
+    for _ in 0..60 {
+        conn.process(Duration::from_millis(1000))?;
+        let response = response_res
+            .lock()
+            .map_err(|_| XCapError::new("Get response lock failed"))?;
+
+        if response.is_some() {
+            break;
+        }
+    }
+
+    conn.remove_match(match_token)?;
+
+    let response = response_res
+        .lock()
+        .map_err(|_| XCapError::new("Get response lock failed"))?
+        .take()
+        .ok_or_else(|| XCapError::new("Wait for portal response timeout"))?;
+
+    if response.status != 0 {
+        return Err(XCapError::new(format!(
+            "ScreenCast request failed or canceled, status: {}",
+            response.status
+        )));
+    }
+
+    Ok(response)
+}
+
+fn to_rgba_image(
+    bytes: &[u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: VideoFormat,
+) -> Option<RgbaImage> {
+    let row_len = width as usize * 4;
+    let stride = if stride == 0 { row_len } else { stride };
+
+    if width == 0 || height == 0 || bytes.len() < stride * (height as usize - 1) + row_len {
+        return None;
+    }
+
+    let mut buffer = Vec::with_capacity(row_len * height as usize);
+    for row in bytes.chunks(stride).take(height as usize) {
+        for pixel in row[..row_len].chunks_exact(4) {
+            let rgba = match format {
+                VideoFormat::BGRA => [pixel[2], pixel[1], pixel[0], pixel[3]],
+                VideoFormat::BGRx => [pixel[2], pixel[1], pixel[0], 255],
+                VideoFormat::RGBA => [pixel[0], pixel[1], pixel[2], pixel[3]],
+                VideoFormat::RGBx => [pixel[0], pixel[1], pixel[2], 255],
+                _ => return None,
+            };
+            buffer.extend_from_slice(&rgba);
+        }
+    }
+
+    RgbaImage::from_raw(width, height, buffer)
+}
+
+/// 连接 portal 提供的 PipeWire remote，读取 node 的第一帧
+fn read_pipewire_frame(fd: OwnedFd, node_id: u32) -> XCapResult<RgbaImage> {
+    pipewire::init();
+
+    let main_loop = MainLoop::new(None)
+        .map_err(|err| XCapError::with_source("Create PipeWire main loop failed", err))?;
+    let context = Context::new(&main_loop)
+        .map_err(|err| XCapError::with_source("Create PipeWire context failed", err))?;
+    let core = context
+        .connect_fd(fd, None)
+        .map_err(|err| XCapError::with_source("Connect PipeWire remote failed", err))?;
+
+    let stream = Stream::new(
+        &core,
+        "xcap",
+        properties! {
+            *pipewire::keys::MEDIA_TYPE => "Video",
+            *pipewire::keys::MEDIA_CATEGORY => "Capture",
+            *pipewire::keys::MEDIA_ROLE => "Screen",
+        },
+    )
+    .map_err(|err| XCapError::with_source("Create PipeWire stream failed", err))?;
+
+    let frame: Rc<RefCell<Option<RgbaImage>>> = Rc::new(RefCell::new(None));
+    let frame_res = frame.clone();
+    let frame_loop = main_loop.clone();
+
+    let _listener = stream
+        .add_local_listener_with_user_data(VideoInfoRaw::default())
+        .param_changed(|_, video_info, id, param| {
+            let Some(param) = param else {
+                return;
+            };
+            if id != ParamType::Format.as_raw() {
+                return;
+            }
+
+            if let Err(err) = video_info.parse(param) {
+                log::error!("Parse PipeWire video format failed: {:?}", err);
+            }
+        })
+        .process(move |stream, video_info| {
+            let Some(mut buffer) = stream.dequeue_buffer() else {
+                return;
+            };
+            let Some(data) = buffer.datas_mut().first_mut() else {
+                return;
+            };
+
+            let chunk = data.chunk();
+            // 只更新了光标等元数据的 buffer 没有图像
+            if chunk.size() == 0 {
+                return;
+            }
+            let (offset, stride) = (chunk.offset() as usize, chunk.stride() as usize);
+
+            let Some(bytes) = data.data() else {
+                return;
+            };
+
+            let size = video_info.size();
+            if let Some(image) = to_rgba_image(
+                &bytes[offset..],
+                size.width,
+                size.height,
+                stride,
+                video_info.format(),
+            ) {
+                *frame.borrow_mut() = Some(image);
+                frame_loop.quit();
+            }
+        })
+        .register()
+        .map_err(|err| XCapError::with_source("Register PipeWire stream listener failed", err))?;
+
+    let format_object = pipewire::spa::pod::object!(
+        SpaTypes::ObjectParamFormat,
+        ParamType::EnumFormat,
+        pipewire::spa::pod::property!(FormatProperties::MediaType, Id, MediaType::Video),
+        pipewire::spa::pod::property!(FormatProperties::MediaSubtype, Id, MediaSubtype::Raw),
+        pipewire::spa::pod::property!(
+            FormatProperties::VideoFormat,
+            Choice,
+            Enum,
+            Id,
+            VideoFormat::BGRx,
+            VideoFormat::BGRx,
+            VideoFormat::BGRA,
+            VideoFormat::RGBx,
+            VideoFormat::RGBA
+        ),
+        pipewire::spa::pod::property!(
+            FormatProperties::VideoSize,
+            Choice,
+            Range,
+            Rectangle,
+            Rectangle {
+                width: 1920,
+                height: 1080
+            },
+            Rectangle {
+                width: 1,
+                height: 1
+            },
+            Rectangle {
+                width: 8192,
+                height: 8192
+            }
+        ),
+        pipewire::spa::pod::property!(
+            FormatProperties::VideoFramerate,
+            Choice,
+            Range,
+            Fraction,
+            Fraction { num: 30, denom: 1 },
+            Fraction { num: 0, denom: 1 },
+            Fraction {
+                num: 1000,
+                denom: 1
+            }
+        ),
+    );
+    let format_bytes =
+        PodSerializer::serialize(Cursor::new(Vec::new()), &Value::Object(format_object))
+            .map_err(|err| XCapError::new(format!("Serialize PipeWire format failed: {:?}", err)))?
+            .0
+            .into_inner();
+    let mut params = [Pod::from_bytes(&format_bytes)
+        .ok_or_else(|| XCapError::new("Create PipeWire format pod failed"))?];
+
+    stream
+        .connect(
+            Direction::Input,
+            Some(node_id),
+            StreamFlags::AUTOCONNECT | StreamFlags::MAP_BUFFERS,
+            &mut params,
+        )
+        .map_err(|err| XCapError::with_source("Connect PipeWire stream failed", err))?;
+
+    let timeout_loop = main_loop.clone();
+    let timer = main_loop.loop_().add_timer(move |_| timeout_loop.quit());
+    let _ = timer.update_timer(Some(FRAME_TIMEOUT), None);
+
+    main_loop.run();
+
+    let image = frame_res.borrow_mut().take();
+
+    image.ok_or_else(|| XCapError::new("Wait for PipeWire frame timeout"))
+}
+
+/// 通过 org.freedesktop.portal.ScreenCast 截取合成器对话框中选择的窗口
+pub fn screen_cast_window(conn: &Connection) -> XCapResult<RgbaImage> {
+    screen_cast(conn, SOURCE_TYPE_WINDOW, None).map(|frame| frame.image)
+}
+
+/// 通过 org.freedesktop.portal.ScreenCast 截取显示器，有恢复令牌时 portal 直接使用上次授权的
+/// 显示器，不会弹出对话框
+pub fn screen_cast_monitor(
+    conn: &Connection,
+    restore_token: Option<&str>,
+) -> XCapResult<ScreenCastFrame> {
+    screen_cast(conn, SOURCE_TYPE_MONITOR, restore_token)
+}
+
+fn screen_cast(
+    conn: &Connection,
+    source_type: u32,
+    restore_token: Option<&str>,
+) -> XCapResult<ScreenCastFrame> {
+    let proxy = conn.with_proxy(
+        "org.freedesktop.portal.Desktop",
+        "/org/freedesktop/portal/desktop",
+        Duration::from_millis(10000),
+    );
+
+    let available_source_types: u32 =
+        proxy.get("org.freedesktop.portal.ScreenCast", "AvailableSourceTypes")?;
+    if available_source_types & source_type == 0 {
+        return Err(XCapError::Unsupported(format!(
+            "The compositor doesn't allow ScreenCast source type {}",
+            source_type
+        )));
+    }
+
+    let token = new_token("create_session")?;
+    let response = call_with_response(conn, &token, || {
+        let mut options: PropMap = HashMap::new();
+        options.insert(
+            String::from("handle_token"),
+            Variant(Box::new(token.clone())),
+        );
+        options.insert(
+            String::from("session_handle_token"),
+            Variant(Box::new(new_token("session")?)),
+        );
+
+        proxy.method_call::<(Path,), (PropMap,), &str, &str>(
+            "org.freedesktop.portal.ScreenCast",
+            "CreateSession",
+            (options,),
+        )?;
+
+        Ok(())
+    })?;
+
+    let session_handle = response
+        .session_handle
+        .ok_or_else(|| XCapError::new("Get ScreenCast session handle failed"))?;
+    let session_path = Path::new(session_handle).map_err(XCapError::new)?;
+
+    let res = start_screen_cast(conn, &proxy, &session_path, source_type, restore_token);
+
+    let session_proxy = conn.with_proxy(
+        "org.freedesktop.portal.Desktop",
+        session_path,
+        Duration::from_millis(10000),
+    );
+    if let Err(err) = session_proxy.method_call::<(), (), &str, &str>(
+        "org.freedesktop.portal.Session",
+        "Close",
+        (),
+    ) {
+        log::error!("Close ScreenCast session failed: {}", err);
+    }
+
+    res
+}
+
+fn start_screen_cast(
+    conn: &Connection,
+    proxy: &Proxy<&Connection>,
+    session_path: &Path,
+    source_type: u32,
+    restore_token: Option<&str>,
+) -> XCapResult<ScreenCastFrame> {
+    let token = new_token("select_sources")?;
+    call_with_response(conn, &token, || {
+        let mut options: PropMap = HashMap::new();
+        options.insert(
+            String::from("handle_token"),
+            Variant(Box::new(token.clone())),
+        );
+        options.insert(String::from("types"), Variant(Box::new(source_type)));
+        options.insert(String::from("multiple"), Variant(Box::new(false)));
+
+        if source_type == SOURCE_TYPE_MONITOR {
+            options.insert(
+                String::from("persist_mode"),
+                Variant(Box::new(PERSIST_MODE_PERSISTENT)),
+            );
+        }
+        if let Some(restore_token) = restore_token {
+            options.insert(
+                String::from("restore_token"),
+                Variant(Box::new(restore_token.to_string())),
+            );
+        }
+
+        proxy.method_call::<(Path,), (&Path, PropMap), &str, &str>(
+            "org.freedesktop.portal.ScreenCast",
+            "SelectSources",
+            (session_path, options),
+        )?;
+
+        Ok(())
+    })?;
+
+    // 用户在这一步的对话框中选择源，恢复令牌有效时不会弹出对话框
+    let token = new_token("start")?;
+    let response = call_with_response(conn, &token, || {
+        let mut options: PropMap = HashMap::new();
+        options.insert(
+            String::from("handle_token"),
+            Variant(Box::new(token.clone())),
+        );
+
+        proxy.method_call::<(Path,), (&Path, &str, PropMap), &str, &str>(
+            "org.freedesktop.portal.ScreenCast",
+            "Start",
+            (session_path, "", options),
+        )?;
+
+        Ok(())
+    })?;
+
+    let node_id = response
+        .node_id
+        .ok_or_else(|| XCapError::new("Get ScreenCast stream failed"))?;
+
+    let (fd,) = proxy.method_call::<(arg::OwnedFd,), (&Path, PropMap), &str, &str>(
+        "org.freedesktop.portal.ScreenCast",
+        "OpenPipeWireRemote",
+        (session_path, HashMap::new()),
+    )?;
+    let fd = unsafe { OwnedFd::from_raw_fd(fd.into_fd()) };
+
+    let image = read_pipewire_frame(fd, node_id)?;
+
+    Ok(ScreenCastFrame {
+        image,
+        position: response.position,
+        restore_token: response.restore_token,
+    })
+}
@@ -0,0 +1,54 @@
+use std::{env::var_os, sync::Mutex};
+
+/// The display server protocol used for capturing on Linux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionType {
+    Xorg,
+    Wayland,
+}
+
+static SESSION_TYPE_OVERRIDE: Mutex<Option<SessionType>> = Mutex::new(None);
+
+fn get_env_var(key: &str) -> String {
+    var_os(key)
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+fn session_type_detect() -> SessionType {
+    let xdg_session_type = get_env_var("XDG_SESSION_TYPE");
+    let wayland_display = get_env_var("WAYLAND_DISPLAY");
+
+    if xdg_session_type.eq("wayland") || wayland_display.to_lowercase().contains("wayland") {
+        SessionType::Wayland
+    } else {
+        SessionType::Xorg
+    }
+}
+
+/// The session type used for capturing. In priority order it is the value set with
+/// [`set_session_type`], the `XCAP_SESSION_TYPE` environment variable (`xorg`, `x11` or
+/// `wayland`), and finally detected from `XDG_SESSION_TYPE` and `WAYLAND_DISPLAY`.
+/// Under XWayland both Xorg and Wayland may be usable, forcing `Xorg` avoids the portal.
+pub fn session_type() -> SessionType {
+    if let Ok(session_type_override) = SESSION_TYPE_OVERRIDE.lock() {
+        if let Some(session_type) = *session_type_override {
+            return session_type;
+        }
+    }
+
+    match get_env_var("XCAP_SESSION_TYPE").to_lowercase().as_str() {
+        "xorg" | "x11" => SessionType::Xorg,
+        "wayland" => SessionType::Wayland,
+        _ => session_type_detect(),
+    }
+}
+
+/// Force the session type used for capturing, `None` restores the default behavior.
+pub fn set_session_type(session_type: Option<SessionType>) {
+    match SESSION_TYPE_OVERRIDE.lock() {
+        Ok(mut session_type_override) => *session_type_override = session_type,
+        Err(err) => log::error!("Set session type failed: {}", err),
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    fs::OpenOptions,
+    io, mem,
+    os::{
+        fd::AsRawFd,
+        raw::{c_int, c_long, c_ulong},
+    },
+};
+
+use crate::error::{XCapError, XCapResult};
+
+// 对应 drm.h 中的 union drm_wait_vblank，reply 是两者中较大的一个
+#[repr(C)]
+#[derive(Default)]
+struct DrmWaitVblank {
+    r#type: u32,
+    sequence: u32,
+    tval_sec: c_long,
+    tval_usec: c_long,
+}
+
+const DRM_VBLANK_RELATIVE: u32 = 0x1;
+const DRM_VBLANK_HIGH_CRTC_SHIFT: u32 = 1;
+const DRM_VBLANK_HIGH_CRTC_MASK: u32 = 0x3e;
+const DRM_VBLANK_SECONDARY: u32 = 0x20000000;
+
+// DRM_IOWR(0x3A, union drm_wait_vblank)
+const DRM_IOCTL_WAIT_VBLANK: c_ulong = (3 << 30)
+    | ((mem::size_of::<DrmWaitVblank>() as c_ulong) << 16)
+    | ((b'd' as c_ulong) << 8)
+    | 0x3a;
+
+extern "C" {
+    fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+}
+
+fn pipe_flags(pipe: u32) -> u32 {
+    match pipe {
+        0 => 0,
+        1 => DRM_VBLANK_SECONDARY,
+        _ => (pipe << DRM_VBLANK_HIGH_CRTC_SHIFT) & DRM_VBLANK_HIGH_CRTC_MASK,
+    }
+}
+
+/// 阻塞直到 pipe 对应的 CRTC 进入下一次垂直消隐。
+/// 假设第一张显卡 /dev/dri/card0 驱动所有输出，且 RandR 的 CRTC 顺序与 DRM 的 pipe 顺序一致
+pub fn wait_for_vblank(pipe: u32) -> XCapResult<()> {
+    let card = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/dri/card0")
+        .map_err(|err| XCapError::with_source("Open /dev/dri/card0 failed", err))?;
+
+    let mut vblank = DrmWaitVblank {
+        r#type: DRM_VBLANK_RELATIVE | pipe_flags(pipe),
+        sequence: 1,
+        ..Default::default()
+    };
+
+    loop {
+        let result = unsafe {
+            ioctl(
+                card.as_raw_fd(),
+                DRM_IOCTL_WAIT_VBLANK,
+                &mut vblank as *mut DrmWaitVblank,
+            )
+        };
+
+        if result == 0 {
+            return Ok(());
+        }
+
+        // 被信号中断时与 libdrm 一样重试
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(XCapError::with_source("DRM_IOCTL_WAIT_VBLANK failed", err));
+        }
+    }
+}
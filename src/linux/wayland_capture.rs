@@ -2,6 +2,7 @@ use dbus::{
     arg::{AppendAll, Iter, IterAppend, PropMap, ReadAll, RefArg, TypeMismatchError, Variant},
     blocking::Connection,
     message::{MatchRule, SignalArgs},
+    Path,
 };
 use image::RgbaImage;
 use percent_encoding::percent_decode;
@@ -13,14 +14,22 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    cancellation_token::CancellationToken,
+    error::{XCapError, XCapResult},
+};
 
 use super::utils::png_to_rgba_image;
+#[cfg(feature = "pipewire")]
+use super::{
+    restore_token::{load_restore_token, save_restore_token},
+    screen_cast::{screen_cast_monitor, screen_cast_window},
+};
 
 #[derive(Debug)]
-struct OrgFreedesktopPortalRequestResponse {
-    status: u32,
-    results: PropMap,
+pub(super) struct OrgFreedesktopPortalRequestResponse {
+    pub(super) status: u32,
+    pub(super) results: PropMap,
 }
 
 impl AppendAll for OrgFreedesktopPortalRequestResponse {
@@ -87,6 +96,7 @@ fn org_freedesktop_portal_screenshot(
     y: i32,
     width: i32,
     height: i32,
+    token: &CancellationToken,
 ) -> XCapResult<RgbaImage> {
     let status: Arc<Mutex<Option<u32>>> = Arc::new(Mutex::new(None));
     let status_res = status.clone();
@@ -124,7 +134,7 @@ fn org_freedesktop_portal_screenshot(
     options.insert(String::from("modal"), Variant(Box::new(true)));
     options.insert(String::from("interactive"), Variant(Box::new(false)));
 
-    proxy.method_call::<(), (&str, PropMap), &str, &str>(
+    let (request_path,) = proxy.method_call::<(Path,), (&str, PropMap), &str, &str>(
         "org.freedesktop.portal.Screenshot",
         "Screenshot",
         ("", options),
@@ -132,6 +142,22 @@ fn org_freedesktop_portal_screenshot(
 
     // wait 60 seconds for user interaction
     for _ in 0..60 {
+        if token.is_cancelled() {
+            // 关闭 Request 时 portal 会同时关闭对话框
+            let request_proxy = conn.with_proxy(
+                "org.freedesktop.portal.Desktop",
+                request_path,
+                Duration::from_millis(10000),
+            );
+            request_proxy.method_call::<(), (), &str, &str>(
+                "org.freedesktop.portal.Request",
+                "Close",
+                (),
+            )?;
+
+            return Err(XCapError::Cancelled);
+        }
+
         let result = conn.process(Duration::from_millis(1000))?;
         let status = status_res
             .lock()
@@ -161,7 +187,7 @@ fn org_freedesktop_portal_screenshot(
 
     let filename = percent_decode(path.as_bytes())
         .decode_utf8()
-        .map_err(XCapError::new)?
+        .map_err(|err| XCapError::with_source("Decode screenshot path failed", err))?
         .to_string();
     let rgba_image = png_to_rgba_image(&filename, x, y, width, height)?;
 
@@ -173,35 +199,123 @@ fn org_freedesktop_portal_screenshot(
 static DBUS_LOCK: Mutex<()> = Mutex::new(());
 
 pub fn wayland_capture(x: i32, y: i32, width: i32, height: i32) -> XCapResult<RgbaImage> {
+    wayland_capture_cancellable(x, y, width, height, &CancellationToken::new())
+}
+
+pub fn wayland_capture_cancellable(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    token: &CancellationToken,
+) -> XCapResult<RgbaImage> {
     let lock = DBUS_LOCK.lock();
 
     let conn = Connection::new_session()?;
-    let res = org_gnome_shell_screenshot(&conn, x, y, width, height)
-        .or_else(|_| org_freedesktop_portal_screenshot(&conn, x, y, width, height));
+    // 两种方式都失败时保留各自的错误信息，便于判断是合成器不支持还是用户拒绝了授权
+    let res = token
+        .check()
+        .and_then(|_| org_gnome_shell_screenshot(&conn, x, y, width, height))
+        .or_else(|gnome_err| {
+            token.check()?;
+
+            org_freedesktop_portal_screenshot(&conn, x, y, width, height, token).map_err(
+                |portal_err| match portal_err {
+                    XCapError::Cancelled => XCapError::Cancelled,
+                    portal_err => XCapError::new(format!(
+                        "org.gnome.Shell.Screenshot failed: {}; org.freedesktop.portal.Screenshot failed: {}",
+                        gnome_err, portal_err
+                    )),
+                },
+            )
+        });
 
     drop(lock);
 
     res
 }
-#[test]
-fn screnshot_multithreaded() {
-    fn make_screenshots() {
-        let monitors = crate::monitor::Monitor::all().unwrap();
-        for monitor in monitors {
-            monitor.capture_image().unwrap();
+
+/// Capture the window the user picks in the compositor's ScreenCast dialog, the portal has no
+/// way to select a window by id.
+#[cfg(feature = "pipewire")]
+pub fn wayland_capture_window() -> XCapResult<RgbaImage> {
+    let lock = DBUS_LOCK.lock();
+
+    let conn = Connection::new_session()?;
+    let res = screen_cast_window(&conn);
+
+    drop(lock);
+
+    res
+}
+
+#[cfg(not(feature = "pipewire"))]
+pub fn wayland_capture_window() -> XCapResult<RgbaImage> {
+    Err(XCapError::Unsupported(
+        "Window capture on Wayland requires the pipewire feature".to_string(),
+    ))
+}
+
+/// Capture the whole monitor with the ScreenCast restore token saved for `connector`, without
+/// showing a dialog. `None` when there is no token or the portal rejected it.
+#[cfg(feature = "pipewire")]
+pub fn wayland_capture_restored(connector: &str) -> Option<RgbaImage> {
+    let restore_token = load_restore_token(connector)?;
+
+    let lock = DBUS_LOCK.lock();
+
+    let res = Connection::new_session()
+        .map_err(XCapError::from)
+        .and_then(|conn| screen_cast_monitor(&conn, Some(&restore_token)));
+
+    drop(lock);
+
+    // 令牌只能使用一次，保存 portal 返回的新令牌；失败时旧令牌已经失效，删除后使用截图接口
+    let (image, restore_token) = match res {
+        Ok(frame) => (Some(frame.image), frame.restore_token),
+        Err(err) => {
+            log::error!("Capture with restore token failed: {}", err);
+            (None, None)
         }
+    };
+
+    if let Err(err) = save_restore_token(connector, restore_token.as_deref()) {
+        log::error!("Save restore token failed: {}", err);
     }
-    // Try making screenshots in paralel. If this times out, then this means that there is a threading issue.
-    const PARALELISM: usize = 10;
-    let handles: Vec<_> = (0..PARALELISM)
-        .map(|_| {
-            std::thread::spawn(|| {
-                make_screenshots();
-            })
-        })
-        .collect();
-    make_screenshots();
-    handles
-        .into_iter()
-        .for_each(|handle| handle.join().unwrap());
+
+    image
+}
+
+#[cfg(not(feature = "pipewire"))]
+pub fn wayland_capture_restored(_connector: &str) -> Option<RgbaImage> {
+    None
+}
+
+/// Show the compositor's ScreenCast dialog for a monitor, returns the position of the monitor
+/// the user picked, if the portal reports it, and the restore token.
+#[cfg(feature = "pipewire")]
+pub fn wayland_request_monitor_permission() -> XCapResult<(Option<(i32, i32)>, String)> {
+    let lock = DBUS_LOCK.lock();
+
+    let conn = Connection::new_session()?;
+    let res = screen_cast_monitor(&conn, None);
+
+    drop(lock);
+
+    let frame = res?;
+    let restore_token = frame.restore_token.ok_or_else(|| {
+        XCapError::Unsupported(
+            "The ScreenCast portal doesn't support restore tokens, version 4 is required"
+                .to_string(),
+        )
+    })?;
+
+    Ok((frame.position, restore_token))
+}
+
+#[cfg(not(feature = "pipewire"))]
+pub fn wayland_request_monitor_permission() -> XCapResult<(Option<(i32, i32)>, String)> {
+    Err(XCapError::Unsupported(
+        "Persisting the capture permission on Wayland requires the pipewire feature".to_string(),
+    ))
 }
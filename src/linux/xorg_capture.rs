@@ -4,7 +4,10 @@ use xcb::{
     Connection,
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    capture_limit::check_capture_size,
+    error::{XCapError, XCapResult},
+};
 
 fn get_pixel8_rgba(
     bytes: &[u8],
@@ -76,6 +79,8 @@ pub fn xorg_capture(
     width: u32,
     height: u32,
 ) -> XCapResult<RgbaImage> {
+    check_capture_size(width, height)?;
+
     let (conn, _) = Connection::connect(None)?;
 
     let setup = conn.get_setup();
@@ -0,0 +1,284 @@
+use std::{collections::VecDeque, ffi::c_void, ptr::NonNull};
+
+use image::RgbaImage;
+use objc2_core_foundation::{
+    CFArray, CFArrayGetCount, CFArrayGetValueAtIndex, CFNumber, CFNumberGetValue, CFNumberType,
+    CFRetained, CFString, CFType, CGPoint, CGRect, CGSize,
+};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    scroll_capture::{capture_scrolled, ScrollContainer},
+};
+
+// AXError 和 AXValueType 的取值
+const AX_ERROR_SUCCESS: i32 = 0;
+const AX_VALUE_CG_POINT_TYPE: u32 = 1;
+const AX_VALUE_CG_SIZE_TYPE: u32 = 2;
+
+// 查找可滚动区域时最多遍历的元素数量，避免在复杂的界面中耗时过长
+const MAX_VISITED_ELEMENTS: usize = 2000;
+// 每次滚动视口高度的比例，保留重叠部分用于拼接
+const SCROLL_PAGE_RATIO: f64 = 0.8;
+// 无法获取内容高度时每次滚动的比例
+const DEFAULT_SCROLL_STEP: f64 = 0.1;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXUIElementCreateApplication(pid: i32) -> *mut CFType;
+    fn AXUIElementCopyAttributeValue(
+        element: &CFType,
+        attribute: &CFString,
+        value: *mut *const CFType,
+    ) -> i32;
+    fn AXUIElementSetAttributeValue(element: &CFType, attribute: &CFString, value: &CFType) -> i32;
+    fn AXValueGetValue(value: &CFType, value_type: u32, value_ptr: *mut c_void) -> bool;
+}
+
+fn copy_attribute_value(element: &CFType, attribute: &str) -> XCapResult<CFRetained<CFType>> {
+    unsafe {
+        let mut value: *const CFType = std::ptr::null();
+        let error =
+            AXUIElementCopyAttributeValue(element, &CFString::from_str(attribute), &mut value);
+
+        if error != AX_ERROR_SUCCESS {
+            return Err(XCapError::new(format!(
+                "AXUIElementCopyAttributeValue {} failed, error code: {}",
+                attribute, error
+            )));
+        }
+
+        NonNull::new(value as *mut CFType)
+            .map(|value| CFRetained::from_raw(value))
+            .ok_or_else(|| XCapError::new(format!("{} has no value", attribute)))
+    }
+}
+
+fn get_children(element: &CFType, attribute: &str) -> XCapResult<Vec<CFRetained<CFType>>> {
+    let value = copy_attribute_value(element, attribute)?;
+
+    unsafe {
+        let cf_array = &*(value.as_ref() as *const CFType as *const CFArray);
+
+        let children = (0..CFArrayGetCount(cf_array))
+            .filter_map(|i| NonNull::new(CFArrayGetValueAtIndex(cf_array, i) as *mut CFType))
+            .map(|child| CFRetained::retain(child))
+            .collect();
+
+        Ok(children)
+    }
+}
+
+fn get_role(element: &CFType) -> XCapResult<String> {
+    let value = copy_attribute_value(element, "AXRole")?;
+    let cf_string = unsafe { &*(value.as_ref() as *const CFType as *const CFString) };
+
+    Ok(cf_string.to_string())
+}
+
+fn get_frame(element: &CFType) -> XCapResult<CGRect> {
+    let mut origin = CGPoint::default();
+    let mut size = CGSize::default();
+
+    unsafe {
+        let position = copy_attribute_value(element, "AXPosition")?;
+        let is_success = AXValueGetValue(
+            &position,
+            AX_VALUE_CG_POINT_TYPE,
+            &mut origin as *mut CGPoint as *mut c_void,
+        );
+
+        let size_value = copy_attribute_value(element, "AXSize")?;
+        if !is_success
+            || !AXValueGetValue(
+                &size_value,
+                AX_VALUE_CG_SIZE_TYPE,
+                &mut size as *mut CGSize as *mut c_void,
+            )
+        {
+            return Err(XCapError::new("AXValueGetValue failed"));
+        }
+    }
+
+    Ok(CGRect { origin, size })
+}
+
+fn get_scroll_value(scroll_bar: &CFType) -> XCapResult<f64> {
+    let value = copy_attribute_value(scroll_bar, "AXValue")?;
+
+    let mut scroll_value: f64 = 0.0;
+    let is_success = unsafe {
+        let cf_number = &*(value.as_ref() as *const CFType as *const CFNumber);
+        CFNumberGetValue(
+            cf_number,
+            CFNumberType::DoubleType,
+            &mut scroll_value as *mut f64 as *mut c_void,
+        )
+    };
+
+    if !is_success {
+        return Err(XCapError::new("Get AXValue CFNumberGetValue failed"));
+    }
+
+    Ok(scroll_value)
+}
+
+fn set_scroll_value(scroll_bar: &CFType, scroll_value: f64) -> XCapResult<()> {
+    let cf_number = CFNumber::new_f64(scroll_value.clamp(0.0, 1.0));
+
+    let error = unsafe {
+        AXUIElementSetAttributeValue(scroll_bar, &CFString::from_str("AXValue"), &cf_number)
+    };
+
+    if error != AX_ERROR_SUCCESS {
+        return Err(XCapError::new(format!(
+            "AXUIElementSetAttributeValue AXValue failed, error code: {}",
+            error
+        )));
+    }
+
+    Ok(())
+}
+
+fn area(frame: &CGRect) -> f64 {
+    frame.size.width * frame.size.height
+}
+
+/// 应用中位置和大小与 CGWindow 相同的窗口元素
+fn find_window_element(pid: i32, window_frame: CGRect) -> XCapResult<CFRetained<CFType>> {
+    let application = unsafe {
+        NonNull::new(AXUIElementCreateApplication(pid))
+            .map(|application| CFRetained::from_raw(application))
+            .ok_or_else(|| XCapError::new("AXUIElementCreateApplication failed"))?
+    };
+
+    // 没有辅助功能权限时 AXUIElementCopyAttributeValue 返回 kAXErrorAPIDisabled
+    get_children(&application, "AXWindows")?
+        .into_iter()
+        .find(|window| {
+            get_frame(window).is_ok_and(|frame| {
+                (frame.origin.x - window_frame.origin.x).abs() < 1.0
+                    && (frame.origin.y - window_frame.origin.y).abs() < 1.0
+                    && (frame.size.width - window_frame.size.width).abs() < 1.0
+                    && (frame.size.height - window_frame.size.height).abs() < 1.0
+            })
+        })
+        .ok_or_else(|| XCapError::NotFound("Not found the accessibility window".to_string()))
+}
+
+/// 窗口中面积最大的有纵向滚动条的 AXScrollArea，返回滚动区域和滚动条
+fn find_vertical_scroll_area(
+    window: CFRetained<CFType>,
+) -> XCapResult<(CFRetained<CFType>, CFRetained<CFType>)> {
+    let mut largest_scroll_area: Option<(f64, CFRetained<CFType>, CFRetained<CFType>)> = None;
+    let mut queue = VecDeque::from([window]);
+    let mut visited = 0;
+
+    while let Some(element) = queue.pop_front() {
+        visited += 1;
+        if visited > MAX_VISITED_ELEMENTS {
+            break;
+        }
+
+        if get_role(&element).is_ok_and(|role| role == "AXScrollArea") {
+            if let (Ok(scroll_bar), Ok(frame)) = (
+                copy_attribute_value(&element, "AXVerticalScrollBar"),
+                get_frame(&element),
+            ) {
+                match largest_scroll_area {
+                    Some((largest_area, _, _)) if largest_area >= area(&frame) => {}
+                    _ => largest_scroll_area = Some((area(&frame), element.clone(), scroll_bar)),
+                }
+            }
+        }
+
+        queue.extend(get_children(&element, "AXChildren").unwrap_or_default());
+    }
+
+    largest_scroll_area
+        .map(|(_, scroll_area, scroll_bar)| (scroll_area, scroll_bar))
+        .ok_or_else(|| XCapError::Unsupported("The window has no scrollable area".to_string()))
+}
+
+struct AxScrollContainer<F> {
+    scroll_area: CFRetained<CFType>,
+    scroll_bar: CFRetained<CFType>,
+    original_value: f64,
+    capture_viewport: F,
+}
+
+impl<F> AxScrollContainer<F> {
+    /// 滚动一页对应的滚动条值的变化，滚动条的值为 0 到 1
+    fn scroll_step(&self) -> f64 {
+        let viewport_height = match get_frame(&self.scroll_area) {
+            Ok(frame) => frame.size.height,
+            Err(_) => return DEFAULT_SCROLL_STEP,
+        };
+
+        let content_height = get_children(&self.scroll_area, "AXContents")
+            .ok()
+            .and_then(|contents| contents.first().and_then(|content| get_frame(content).ok()))
+            .map(|frame| frame.size.height);
+
+        match content_height {
+            Some(content_height) if content_height > viewport_height => {
+                viewport_height * SCROLL_PAGE_RATIO / (content_height - viewport_height)
+            }
+            _ => DEFAULT_SCROLL_STEP,
+        }
+    }
+}
+
+impl<F> ScrollContainer for AxScrollContainer<F>
+where
+    F: FnMut(CGRect) -> XCapResult<RgbaImage>,
+{
+    fn capture_viewport(&mut self) -> XCapResult<RgbaImage> {
+        let viewport = get_frame(&self.scroll_area)?;
+
+        (self.capture_viewport)(viewport)
+    }
+
+    fn scroll_to_top(&mut self) -> XCapResult<()> {
+        set_scroll_value(&self.scroll_bar, 0.0)
+    }
+
+    fn scroll_down(&mut self) -> XCapResult<bool> {
+        let scroll_value = get_scroll_value(&self.scroll_bar)?;
+        if scroll_value >= 1.0 {
+            return Ok(false);
+        }
+
+        set_scroll_value(&self.scroll_bar, scroll_value + self.scroll_step())?;
+
+        Ok(get_scroll_value(&self.scroll_bar)? != scroll_value)
+    }
+
+    fn restore(&mut self) -> XCapResult<()> {
+        set_scroll_value(&self.scroll_bar, self.original_value)
+    }
+}
+
+/// 通过辅助功能 API 滚动窗口中最大的 AXScrollArea 并拼接截图，需要辅助功能权限，
+/// `capture_viewport` 截取屏幕坐标（点）中的可滚动区域
+pub(super) fn capture_scrollable<F>(
+    pid: i32,
+    window_frame: CGRect,
+    capture_viewport: F,
+) -> XCapResult<RgbaImage>
+where
+    F: FnMut(CGRect) -> XCapResult<RgbaImage>,
+{
+    let window = find_window_element(pid, window_frame)?;
+    let (scroll_area, scroll_bar) = find_vertical_scroll_area(window)?;
+    let original_value = get_scroll_value(&scroll_bar)?;
+
+    let mut scroll_container = AxScrollContainer {
+        scroll_area,
+        scroll_bar,
+        original_value,
+        capture_viewport,
+    };
+
+    capture_scrolled(&mut scroll_container)
+}
@@ -1,32 +1,180 @@
 use image::RgbaImage;
-use objc2_core_foundation::CGRect;
+use objc2_core_foundation::{CGPoint, CGRect};
 use objc2_core_graphics::{
-    CGDataProviderCopyData, CGImageGetBytesPerRow, CGImageGetDataProvider, CGImageGetHeight,
-    CGImageGetWidth, CGWindowID, CGWindowImageOption, CGWindowListCreateImage, CGWindowListOption,
+    CGDataProviderCopyData, CGDirectDisplayID, CGDisplayBounds, CGImage, CGImageGetBytesPerRow,
+    CGImageGetDataProvider, CGImageGetHeight, CGImageGetWidth, CGWindowID, CGWindowImageOption,
+    CGWindowListCreateImage, CGWindowListOption,
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    capture_limit::check_capture_size,
+    error::{XCapError, XCapResult},
+    frame_time::Acceleration,
+    native_image::{NativeImage, PixelFormat},
+};
+
+use super::{impl_window::get_cursor_effect_window, screen_capture_kit};
+
+/// Capture an area of the display, `cg_rect` is in global coordinates. Uses ScreenCaptureKit
+/// when available and falls back to `CGWindowListCreateImage`.
+pub fn capture_display(display_id: CGDirectDisplayID, cg_rect: CGRect) -> XCapResult<RgbaImage> {
+    capture_display_native(display_id, cg_rect)?.into_rgba_image()
+}
+
+/// The same as `capture_display`, without converting the BGRA pixels.
+pub fn capture_display_native(
+    display_id: CGDirectDisplayID,
+    cg_rect: CGRect,
+) -> XCapResult<NativeImage> {
+    let (native_image, _) = capture_display_with_acceleration(display_id, cg_rect)?;
+
+    Ok(native_image)
+}
+
+/// The same as `capture_display_native`, also returning whether ScreenCaptureKit was used.
+pub fn capture_display_with_acceleration(
+    display_id: CGDirectDisplayID,
+    cg_rect: CGRect,
+) -> XCapResult<(NativeImage, Acceleration)> {
+    if screen_capture_kit::is_available() {
+        let display_bounds = unsafe { CGDisplayBounds(display_id) };
+        let source_rect = CGRect::new(
+            CGPoint::new(
+                cg_rect.origin.x - display_bounds.origin.x,
+                cg_rect.origin.y - display_bounds.origin.y,
+            ),
+            cg_rect.size,
+        );
+
+        match screen_capture_kit::capture_display(display_id, Some(source_rect), false) {
+            Ok(native_image) => return Ok((native_image, Acceleration::Hardware)),
+            Err(err) => log::info!("ScreenCaptureKit capture display failed: {}", err),
+        }
+    }
+
+    let native_image = capture_native(
+        cg_rect,
+        CGWindowListOption::OptionAll,
+        0,
+        CGWindowImageOption::Default,
+    )?;
+
+    Ok((native_image, Acceleration::Software))
+}
+
+/// Capture a window, uses ScreenCaptureKit when available and falls back to
+/// `CGWindowListCreateImage`.
+pub fn capture_window(window_id: CGWindowID, cg_rect: CGRect) -> XCapResult<RgbaImage> {
+    if screen_capture_kit::is_available() {
+        match screen_capture_kit::capture_window(window_id).and_then(NativeImage::into_rgba_image) {
+            Ok(image) => return Ok(image),
+            Err(err) => log::info!("ScreenCaptureKit capture window failed: {}", err),
+        }
+    }
+
+    capture(
+        cg_rect,
+        CGWindowListOption::OptionIncludingWindow,
+        window_id,
+    )
+}
+
+/// Capture the whole display converted to sRGB with ScreenCaptureKit, falls back to
+/// `CGWindowListCreateImage` when ScreenCaptureKit isn't available.
+pub fn capture_display_srgb(display_id: CGDirectDisplayID) -> XCapResult<RgbaImage> {
+    if screen_capture_kit::is_available() {
+        match screen_capture_kit::capture_display(display_id, None, true)
+            .and_then(NativeImage::into_rgba_image)
+        {
+            Ok(image) => return Ok(image),
+            Err(err) => log::info!("ScreenCaptureKit capture display failed: {}", err),
+        }
+    }
+
+    let cg_rect = unsafe { CGDisplayBounds(display_id) };
+
+    capture(cg_rect, CGWindowListOption::OptionAll, 0)
+}
+
+/// Capture an area of the display with `CGWindowListCreateImage`, without trying
+/// ScreenCaptureKit first.
+pub fn capture_display_cg_window_list(cg_rect: CGRect) -> XCapResult<RgbaImage> {
+    capture(cg_rect, CGWindowListOption::OptionAll, 0)
+}
+
+/// Capture an area of the display at its nominal resolution, one pixel per point,
+/// which is faster on Retina displays.
+pub fn capture_display_nominal(cg_rect: CGRect) -> XCapResult<RgbaImage> {
+    capture_with_image_option(
+        cg_rect,
+        CGWindowListOption::OptionAll,
+        0,
+        CGWindowImageOption::NominalResolution,
+    )
+}
 
 pub fn capture(
     cg_rect: CGRect,
     list_option: CGWindowListOption,
     window_id: CGWindowID,
 ) -> XCapResult<RgbaImage> {
+    capture_with_image_option(
+        cg_rect,
+        list_option,
+        window_id,
+        CGWindowImageOption::Default,
+    )
+}
+
+fn capture_with_image_option(
+    cg_rect: CGRect,
+    list_option: CGWindowListOption,
+    window_id: CGWindowID,
+    image_option: CGWindowImageOption,
+) -> XCapResult<RgbaImage> {
+    capture_native(cg_rect, list_option, window_id, image_option)?.into_rgba_image()
+}
+
+fn capture_native(
+    cg_rect: CGRect,
+    list_option: CGWindowListOption,
+    window_id: CGWindowID,
+    image_option: CGWindowImageOption,
+) -> XCapResult<NativeImage> {
+    // 截取整个屏幕时不包含光标阴影和点击波纹，只截取这些效果所在窗口下面的窗口
+    let effect_window_id = (list_option == CGWindowListOption::OptionAll)
+        .then(get_cursor_effect_window)
+        .flatten();
+    let (list_option, window_id) = match effect_window_id {
+        Some(effect_window_id) => (
+            CGWindowListOption::OptionOnScreenBelowWindow,
+            effect_window_id,
+        ),
+        None => (list_option, window_id),
+    };
+
     unsafe {
-        let cg_image = CGWindowListCreateImage(
-            cg_rect,
-            list_option,
-            window_id,
-            CGWindowImageOption::Default,
-        );
+        let cg_image = CGWindowListCreateImage(cg_rect, list_option, window_id, image_option);
+
+        cg_image_to_native_image(cg_image.as_deref())
+    }
+}
 
-        let width = CGImageGetWidth(cg_image.as_deref());
-        let height = CGImageGetHeight(cg_image.as_deref());
-        let data_provider = CGImageGetDataProvider(cg_image.as_deref());
+pub(super) fn cg_image_to_rgba_image(cg_image: Option<&CGImage>) -> XCapResult<RgbaImage> {
+    cg_image_to_native_image(cg_image)?.into_rgba_image()
+}
+
+pub(super) fn cg_image_to_native_image(cg_image: Option<&CGImage>) -> XCapResult<NativeImage> {
+    unsafe {
+        let width = CGImageGetWidth(cg_image);
+        let height = CGImageGetHeight(cg_image);
+        check_capture_size(width as u32, height as u32)?;
+
+        let data_provider = CGImageGetDataProvider(cg_image);
         let data = CGDataProviderCopyData(data_provider.as_deref())
             .ok_or_else(|| XCapError::new("Failed to copy data"))?
             .to_vec();
-        let bytes_per_row = CGImageGetBytesPerRow(cg_image.as_deref());
+        let bytes_per_row = CGImageGetBytesPerRow(cg_image);
 
         // Some platforms e.g. MacOS can have extra bytes at the end of each row.
         // See
@@ -37,11 +185,11 @@ pub fn capture(
             buffer.extend_from_slice(&row[..width * 4]);
         }
 
-        for bgra in buffer.chunks_exact_mut(4) {
-            bgra.swap(0, 2);
-        }
-
-        RgbaImage::from_raw(width as u32, height as u32, buffer)
-            .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))
+        Ok(NativeImage::new(
+            width as u32,
+            height as u32,
+            PixelFormat::Bgra8,
+            buffer,
+        ))
     }
 }
@@ -0,0 +1,25 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, RgbaImage};
+use objc2_app_kit::{NSPasteboard, NSPasteboardTypePNG};
+use objc2_foundation::NSData;
+
+use crate::error::{XCapError, XCapResult};
+
+pub(crate) fn set_clipboard_image(image: &RgbaImage) -> XCapResult<()> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+
+    unsafe {
+        let data = NSData::with_bytes(&png);
+        let pasteboard = NSPasteboard::generalPasteboard();
+
+        pasteboard.clearContents();
+
+        if !pasteboard.setData_forType(Some(&data), NSPasteboardTypePNG) {
+            return Err(XCapError::new("NSPasteboard setData failed"));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+#[repr(C)]
+#[derive(Default)]
+struct MachTimebaseInfo {
+    numer: u32,
+    denom: u32,
+}
+
+extern "C" {
+    fn mach_absolute_time() -> u64;
+    fn mach_timebase_info(info: *mut MachTimebaseInfo) -> i32;
+}
+
+pub fn get_clock_time() -> (u64, Duration) {
+    let mut timebase_info = MachTimebaseInfo::default();
+
+    let ticks = unsafe {
+        mach_timebase_info(&mut timebase_info);
+        mach_absolute_time()
+    };
+
+    // Apple Silicon 上每个 tick 不是 1 纳秒，需要按 timebase 换算
+    let nanos = ticks as u128 * timebase_info.numer as u128 / (timebase_info.denom as u128).max(1);
+
+    (ticks, Duration::from_nanos(nanos as u64))
+}
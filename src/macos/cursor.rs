@@ -0,0 +1,47 @@
+use image::RgbaImage;
+use objc2_app_kit::NSCursor;
+use objc2_core_graphics::{CGEventCreate, CGEventGetLocation};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::capture::cg_image_to_rgba_image;
+
+pub fn get_cursor_position() -> XCapResult<(i32, i32)> {
+    unsafe {
+        // CGEvent 的坐标原点在主屏幕左上角，与 Monitor 的坐标一致
+        let event = CGEventCreate(None).ok_or_else(|| XCapError::new("CGEventCreate failed"))?;
+        let location = CGEventGetLocation(Some(&event));
+
+        Ok((location.x as i32, location.y as i32))
+    }
+}
+
+pub fn get_cursor_image() -> XCapResult<(RgbaImage, i32, i32)> {
+    unsafe {
+        let cursor = NSCursor::currentSystemCursor()
+            .ok_or_else(|| XCapError::new("NSCursor::currentSystemCursor failed"))?;
+
+        let ns_image = cursor.image();
+        let ns_size = ns_image.size();
+        let hot_spot = cursor.hotSpot();
+
+        let cg_image = ns_image
+            .CGImageForProposedRect_context_hints(std::ptr::null_mut(), None, None)
+            .ok_or_else(|| XCapError::new("NSImage::CGImageForProposedRect failed"))?;
+
+        let image = cg_image_to_rgba_image(Some(&cg_image))?;
+
+        // hotSpot 的单位是点，需要换算为图片的像素
+        let scale_factor = if ns_size.width > 0.0 {
+            image.width() as f64 / ns_size.width
+        } else {
+            1.0
+        };
+
+        Ok((
+            image,
+            (hot_spot.x * scale_factor) as i32,
+            (hot_spot.y * scale_factor) as i32,
+        ))
+    }
+}
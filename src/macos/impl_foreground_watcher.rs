@@ -0,0 +1,103 @@
+use std::{ptr::NonNull, sync::mpsc, thread};
+
+use block2::RcBlock;
+use objc2::{rc::Retained, runtime::ProtocolObject};
+use objc2_app_kit::{NSWorkspace, NSWorkspaceDidActivateApplicationNotification};
+use objc2_foundation::{NSNotification, NSObjectProtocol};
+
+use crate::error::XCapResult;
+
+use super::impl_window::ImplWindow;
+
+type Callback = Box<dyn FnMut(ImplWindow) + Send>;
+
+pub(super) fn get_foreground_window() -> XCapResult<Option<ImplWindow>> {
+    let frontmost_application_pid = unsafe {
+        match NSWorkspace::sharedWorkspace().frontmostApplication() {
+            Some(application) => application.processIdentifier(),
+            None => return Ok(None),
+        }
+    };
+
+    // ImplWindow::all 返回的窗口从顶层到最底层排列
+    for impl_window in ImplWindow::all()? {
+        if impl_window.pid()? as i32 == frontmost_application_pid {
+            return Ok(Some(impl_window));
+        }
+    }
+
+    Ok(None)
+}
+
+// 通知中心是线程安全的，可以在任意线程中移除观察者
+#[derive(Debug)]
+struct Observer(Retained<ProtocolObject<dyn NSObjectProtocol>>);
+
+unsafe impl Send for Observer {}
+unsafe impl Sync for Observer {}
+
+#[derive(Debug)]
+pub struct ImplForegroundWatcher {
+    observer: Observer,
+}
+
+impl ImplForegroundWatcher {
+    pub fn new(mut callback: Callback) -> XCapResult<Self> {
+        let (tx, rx) = mpsc::channel::<()>();
+
+        // 通知在主线程中发送，回调在后台线程中执行
+        thread::spawn(move || {
+            let mut last_foreground_window = get_foreground_window().ok().flatten();
+
+            while rx.recv().is_ok() {
+                let foreground_window = match get_foreground_window() {
+                    Ok(foreground_window) => foreground_window,
+                    Err(err) => {
+                        log::error!("get_foreground_window failed: {}", err);
+                        continue;
+                    }
+                };
+
+                if foreground_window == last_foreground_window {
+                    continue;
+                }
+
+                last_foreground_window = foreground_window.clone();
+
+                if let Some(impl_window) = foreground_window {
+                    callback(impl_window);
+                }
+            }
+        });
+
+        // 移除观察者后 block 被释放，sender 随之释放，后台线程退出
+        let block = RcBlock::new(move |_notification: NonNull<NSNotification>| {
+            let _ = tx.send(());
+        });
+
+        let observer = unsafe {
+            NSWorkspace::sharedWorkspace()
+                .notificationCenter()
+                .addObserverForName_object_queue_usingBlock(
+                    Some(NSWorkspaceDidActivateApplicationNotification),
+                    None,
+                    None,
+                    &block,
+                )
+        };
+
+        Ok(ImplForegroundWatcher {
+            observer: Observer(observer),
+        })
+    }
+
+    pub fn stop(&self) -> XCapResult<()> {
+        unsafe {
+            NSWorkspace::sharedWorkspace()
+                .notificationCenter()
+                .removeObserver(self.observer.0.as_ref());
+        }
+
+        Ok(())
+    }
+}
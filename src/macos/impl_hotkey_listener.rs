@@ -0,0 +1,298 @@
+use std::{
+    ffi::c_void,
+    mem, ptr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use dispatch2::Queue;
+use objc2::MainThreadMarker;
+
+use crate::{
+    error::XCapResult,
+    hotkey::{HotKey, HotKeyCode},
+};
+
+type Callback = Box<dyn FnMut() + Send>;
+
+type EventHandlerProcPtr =
+    extern "C" fn(next_handler: *mut c_void, event: *mut c_void, user_data: *mut c_void) -> i32;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct EventHotKeyID {
+    signature: u32,
+    id: u32,
+}
+
+#[repr(C)]
+struct EventTypeSpec {
+    event_class: u32,
+    event_kind: u32,
+}
+
+const NO_ERR: i32 = 0;
+const EVENT_NOT_HANDLED_ERR: i32 = -9874;
+
+// 'keyb'
+const K_EVENT_CLASS_KEYBOARD: u32 = 0x6B65_7962;
+const K_EVENT_HOT_KEY_PRESSED: u32 = 5;
+// '----'
+const K_EVENT_PARAM_DIRECT_OBJECT: u32 = 0x2D2D_2D2D;
+// 'hkid'
+const TYPE_EVENT_HOT_KEY_ID: u32 = 0x686B_6964;
+// 'xcap'，区分其他库注册的热键
+const HOTKEY_SIGNATURE: u32 = 0x7863_6170;
+
+const CMD_KEY: u32 = 0x0100;
+const SHIFT_KEY: u32 = 0x0200;
+const OPTION_KEY: u32 = 0x0800;
+const CONTROL_KEY: u32 = 0x1000;
+
+static NEXT_HOTKEY_ID: AtomicU32 = AtomicU32::new(1);
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn GetApplicationEventTarget() -> *mut c_void;
+    fn InstallEventHandler(
+        target: *mut c_void,
+        handler: EventHandlerProcPtr,
+        num_types: u32,
+        list: *const EventTypeSpec,
+        user_data: *mut c_void,
+        out_ref: *mut *mut c_void,
+    ) -> i32;
+    fn RemoveEventHandler(handler_ref: *mut c_void) -> i32;
+    fn RegisterEventHotKey(
+        key_code: u32,
+        modifiers: u32,
+        hotkey_id: EventHotKeyID,
+        target: *mut c_void,
+        options: u32,
+        out_ref: *mut *mut c_void,
+    ) -> i32;
+    fn UnregisterEventHotKey(hotkey_ref: *mut c_void) -> i32;
+    fn GetEventParameter(
+        event: *mut c_void,
+        name: u32,
+        desired_type: u32,
+        actual_type: *mut u32,
+        buffer_size: usize,
+        actual_size: *mut usize,
+        data: *mut c_void,
+    ) -> i32;
+}
+
+fn get_key_code(code: HotKeyCode) -> u32 {
+    match code {
+        // Mac 键盘没有 PrintScreen，PC 键盘的 PrintScreen 对应 F13
+        HotKeyCode::PrintScreen => 0x69,
+        HotKeyCode::F1 => 0x7A,
+        HotKeyCode::F2 => 0x78,
+        HotKeyCode::F3 => 0x63,
+        HotKeyCode::F4 => 0x76,
+        HotKeyCode::F5 => 0x60,
+        HotKeyCode::F6 => 0x61,
+        HotKeyCode::F7 => 0x62,
+        HotKeyCode::F8 => 0x64,
+        HotKeyCode::F9 => 0x65,
+        HotKeyCode::F10 => 0x6D,
+        HotKeyCode::F11 => 0x67,
+        HotKeyCode::F12 => 0x6F,
+    }
+}
+
+fn get_hotkey_modifiers(hotkey: HotKey) -> u32 {
+    let mut modifiers = 0;
+
+    if hotkey.modifiers.control {
+        modifiers |= CONTROL_KEY;
+    }
+    if hotkey.modifiers.alt {
+        modifiers |= OPTION_KEY;
+    }
+    if hotkey.modifiers.shift {
+        modifiers |= SHIFT_KEY;
+    }
+    if hotkey.modifiers.meta {
+        modifiers |= CMD_KEY;
+    }
+
+    modifiers
+}
+
+struct HandlerData {
+    id: u32,
+    sender: Sender<()>,
+}
+
+extern "C" fn hotkey_event_handler(
+    _next_handler: *mut c_void,
+    event: *mut c_void,
+    user_data: *mut c_void,
+) -> i32 {
+    let mut hotkey_id = EventHotKeyID::default();
+
+    let status = unsafe {
+        GetEventParameter(
+            event,
+            K_EVENT_PARAM_DIRECT_OBJECT,
+            TYPE_EVENT_HOT_KEY_ID,
+            ptr::null_mut(),
+            mem::size_of::<EventHotKeyID>(),
+            ptr::null_mut(),
+            (&mut hotkey_id as *mut EventHotKeyID).cast(),
+        )
+    };
+
+    let handler_data = unsafe { &*(user_data as *const HandlerData) };
+
+    // 不是当前监听的热键，交给下一个处理函数
+    if status != NO_ERR
+        || hotkey_id.signature != HOTKEY_SIGNATURE
+        || hotkey_id.id != handler_data.id
+    {
+        return EVENT_NOT_HANDLED_ERR;
+    }
+
+    let _ = handler_data.sender.send(());
+
+    NO_ERR
+}
+
+#[derive(Debug)]
+struct Registration {
+    hotkey_ref: *mut c_void,
+    handler_ref: *mut c_void,
+    handler_data: *mut HandlerData,
+}
+
+// Registration 只在主线程中创建和释放
+unsafe impl Send for Registration {}
+
+impl Registration {
+    fn new(hotkey: HotKey, handler_data: HandlerData) -> Option<Registration> {
+        let id = handler_data.id;
+        let handler_data = Box::into_raw(Box::new(handler_data));
+        let event_type_spec = EventTypeSpec {
+            event_class: K_EVENT_CLASS_KEYBOARD,
+            event_kind: K_EVENT_HOT_KEY_PRESSED,
+        };
+
+        unsafe {
+            let target = GetApplicationEventTarget();
+
+            let mut handler_ref = ptr::null_mut();
+            let status = InstallEventHandler(
+                target,
+                hotkey_event_handler,
+                1,
+                &event_type_spec,
+                handler_data.cast(),
+                &mut handler_ref,
+            );
+            if status != NO_ERR {
+                log::error!("InstallEventHandler failed: {}", status);
+                drop(Box::from_raw(handler_data));
+                return None;
+            }
+
+            let mut hotkey_ref = ptr::null_mut();
+            let status = RegisterEventHotKey(
+                get_key_code(hotkey.code),
+                get_hotkey_modifiers(hotkey),
+                EventHotKeyID {
+                    signature: HOTKEY_SIGNATURE,
+                    id,
+                },
+                target,
+                0,
+                &mut hotkey_ref,
+            );
+            if status != NO_ERR {
+                log::error!("RegisterEventHotKey failed: {}", status);
+                RemoveEventHandler(handler_ref);
+                drop(Box::from_raw(handler_data));
+                return None;
+            }
+
+            Some(Registration {
+                hotkey_ref,
+                handler_ref,
+                handler_data,
+            })
+        }
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        unsafe {
+            UnregisterEventHotKey(self.hotkey_ref);
+            RemoveEventHandler(self.handler_ref);
+            // 释放 sender 后，回调线程退出
+            drop(Box::from_raw(self.handler_data));
+        }
+    }
+}
+
+/// Carbon 事件只能在主线程中注册，主线程阻塞时不能同步等待
+fn run_on_main_thread<F>(work: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    if MainThreadMarker::new().is_some() {
+        work();
+    } else {
+        Queue::main().exec_async(work);
+    }
+}
+
+#[derive(Debug)]
+pub struct ImplHotKeyListener {
+    registration: Arc<Mutex<Option<Registration>>>,
+}
+
+impl ImplHotKeyListener {
+    pub fn new(hotkey: HotKey, mut callback: Callback) -> XCapResult<Self> {
+        let (tx, rx) = mpsc::channel();
+        let id = NEXT_HOTKEY_ID.fetch_add(1, Ordering::Relaxed);
+
+        // 热键事件在主线程中分发，回调在后台线程中执行
+        thread::spawn(move || {
+            while rx.recv().is_ok() {
+                callback();
+            }
+        });
+
+        let registration = Arc::new(Mutex::new(None));
+        let registration_slot = registration.clone();
+
+        run_on_main_thread(move || {
+            let handler_data = HandlerData { id, sender: tx };
+
+            match registration_slot.lock() {
+                Ok(mut slot) => *slot = Registration::new(hotkey, handler_data),
+                Err(err) => log::error!("Register hotkey failed: {}", err),
+            }
+        });
+
+        Ok(ImplHotKeyListener { registration })
+    }
+
+    pub fn stop(&self) -> XCapResult<()> {
+        let registration = self.registration.clone();
+
+        // 主线程队列是串行的，注销总是在注册之后执行
+        run_on_main_thread(move || match registration.lock() {
+            Ok(mut slot) => drop(slot.take()),
+            Err(err) => log::error!("Unregister hotkey failed: {}", err),
+        });
+
+        Ok(())
+    }
+}
@@ -1,29 +1,83 @@
-use std::sync::mpsc::Receiver;
+use std::{ptr::NonNull, sync::mpsc::Receiver};
 
-use image::RgbaImage;
-use objc2::MainThreadMarker;
+use image::{
+    imageops::{resize, FilterType},
+    RgbaImage,
+};
+use objc2::{rc::Retained, MainThreadMarker};
 use objc2_app_kit::NSScreen;
-use objc2_core_foundation::CGPoint;
+use objc2_core_foundation::{
+    CFArrayGetCount, CFArrayGetValueAtIndex, CFRetained, CFString, CGPoint, CGRect, CGSize,
+};
 use objc2_core_graphics::{
-    CGDirectDisplayID, CGDisplayBounds, CGDisplayCopyDisplayMode, CGDisplayIsActive,
-    CGDisplayIsBuiltin, CGDisplayIsMain, CGDisplayModeGetPixelWidth, CGDisplayModeGetRefreshRate,
-    CGDisplayRotation, CGError, CGGetActiveDisplayList, CGGetDisplaysWithPoint, CGWindowListOption,
+    CGDirectDisplayID, CGDisplayBounds, CGDisplayCopyAllDisplayModes, CGDisplayCopyDisplayMode,
+    CGDisplayGammaTableCapacity, CGDisplayIsActive, CGDisplayIsBuiltin, CGDisplayIsMain,
+    CGDisplayMode, CGDisplayModeGetPixelHeight, CGDisplayModeGetPixelWidth,
+    CGDisplayModeGetRefreshRate, CGDisplayModelNumber, CGDisplayRotation, CGDisplaySerialNumber,
+    CGDisplayVendorNumber, CGError, CGGetActiveDisplayList, CGGetDisplayTransferByTable,
+    CGGetDisplaysWithPoint, CGMainDisplayID, CGSetDisplayTransferByTable,
 };
 use objc2_foundation::{NSNumber, NSString};
 
 use crate::{
+    cancellation_token::CancellationToken,
+    capture_config::Backend,
+    capture_quality::CaptureQuality,
+    coordinate::{appkit_to_global_y, global_point_to_i32},
+    display_mode::DisplayMode,
+    edid::format_edid_id,
     error::{XCapError, XCapResult},
+    frame_time::Acceleration,
+    gamma_ramp::GammaRamp,
+    native_image::NativeImage,
+    raw_handle::RawMonitorHandle,
+    rect::Rect,
+    scale_factor::{get_scale_factor_override, ScaleFactorSource},
     video_recorder::Frame,
 };
 
-use super::{capture::capture, impl_video_recorder::ImplVideoRecorder};
+use super::{
+    capture::{
+        capture_display, capture_display_cg_window_list, capture_display_native,
+        capture_display_nominal, capture_display_srgb, capture_display_with_acceleration,
+    },
+    impl_video_recorder::ImplVideoRecorder,
+    screen_capture_kit,
+    vblank::wait_for_vblank,
+};
+
+// objc2-core-graphics 没有导出已弃用的 CGDisplayModeCopyPixelEncoding
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGDisplayModeCopyPixelEncoding(mode: *const CGDisplayMode) -> *mut CFString;
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+    // objc2-core-graphics 也没有导出 CGGetOnlineDisplayList
+    fn CGGetOnlineDisplayList(
+        max_displays: u32,
+        online_displays: *mut CGDirectDisplayID,
+        display_count: *mut u32,
+    ) -> CGError;
+}
 
-#[derive(Debug, Clone)]
+/// 像素编码形如 `--------RRRRRRRRGGGGGGGGBBBBBBBB`，每个字符对应一位
+fn get_display_mode_bit_depth(mode: &CGDisplayMode) -> u32 {
+    unsafe {
+        let pixel_encoding = match NonNull::new(CGDisplayModeCopyPixelEncoding(mode)) {
+            Some(pixel_encoding) => CFRetained::from_raw(pixel_encoding),
+            None => return 0,
+        };
+
+        pixel_encoding.to_string().len() as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct ImplMonitor {
     pub cg_direct_display_id: CGDirectDisplayID,
 }
 
-fn get_display_friendly_name(display_id: CGDirectDisplayID) -> XCapResult<String> {
+fn get_ns_screen(display_id: CGDirectDisplayID) -> XCapResult<Retained<NSScreen>> {
     let screens = NSScreen::screens(unsafe { MainThreadMarker::new_unchecked() });
     for screen in screens {
         let device_description = screen.deviceDescription();
@@ -37,22 +91,42 @@ fn get_display_friendly_name(display_id: CGDirectDisplayID) -> XCapResult<String
             .unsignedIntValue();
 
         if screen_id == display_id {
-            unsafe { return Ok(screen.localizedName().to_string()) };
+            return Ok(screen);
         }
     }
 
     Err(XCapError::new(format!(
-        "Get display {} friendly name failed",
+        "Get display {} screen failed",
         display_id
     )))
 }
 
+fn get_display_friendly_name(display_id: CGDirectDisplayID) -> XCapResult<String> {
+    let screen = get_ns_screen(display_id)?;
+
+    unsafe { Ok(screen.localizedName().to_string()) }
+}
+
 impl ImplMonitor {
     pub fn new(cg_direct_display_id: CGDirectDisplayID) -> ImplMonitor {
         ImplMonitor {
             cg_direct_display_id,
         }
     }
+
+    pub fn from_raw_handle(raw_handle: RawMonitorHandle) -> XCapResult<ImplMonitor> {
+        let RawMonitorHandle::CoreGraphics { display_id } = raw_handle else {
+            return Err(XCapError::new("Not a CoreGraphics display id"));
+        };
+
+        Ok(ImplMonitor::new(display_id))
+    }
+
+    pub fn raw_handle(&self) -> RawMonitorHandle {
+        RawMonitorHandle::CoreGraphics {
+            display_id: self.cg_direct_display_id,
+        }
+    }
     pub fn all() -> XCapResult<Vec<ImplMonitor>> {
         let max_displays: u32 = 16;
         let mut active_displays: Vec<CGDirectDisplayID> = vec![0; max_displays as usize];
@@ -84,6 +158,66 @@ impl ImplMonitor {
         Ok(impl_monitors)
     }
 
+    pub fn all_including_inactive() -> XCapResult<Vec<(String, bool, Option<ImplMonitor>)>> {
+        let max_displays: u32 = 16;
+        let mut online_displays: Vec<CGDirectDisplayID> = vec![0; max_displays as usize];
+        let mut display_count: u32 = 0;
+
+        // 在线的显示器包括休眠和镜像的显示器，MacOS 没有公开已断开显示器的列表
+        let cg_error = unsafe {
+            CGGetOnlineDisplayList(
+                max_displays,
+                online_displays.as_mut_ptr(),
+                &mut display_count,
+            )
+        };
+
+        if cg_error != CGError::Success {
+            return Err(XCapError::new(format!(
+                "CGGetOnlineDisplayList failed: {:?}",
+                cg_error
+            )));
+        }
+
+        online_displays.truncate(display_count as usize);
+
+        let mut known_monitors = Vec::with_capacity(online_displays.len());
+
+        for display in online_displays {
+            let impl_monitor = ImplMonitor::new(display);
+            let is_active = unsafe { CGDisplayIsActive(display) };
+
+            known_monitors.push((
+                impl_monitor.name()?,
+                is_active,
+                is_active.then_some(impl_monitor),
+            ));
+        }
+
+        Ok(known_monitors)
+    }
+
+    pub fn virtual_desktop_bounds() -> XCapResult<Rect> {
+        let mut bounds: Option<Rect> = None;
+
+        for impl_monitor in ImplMonitor::all()? {
+            let cg_rect = unsafe { CGDisplayBounds(impl_monitor.cg_direct_display_id) };
+            let display_bounds = Rect::new(
+                cg_rect.origin.x as i32,
+                cg_rect.origin.y as i32,
+                cg_rect.size.width as u32,
+                cg_rect.size.height as u32,
+            );
+
+            bounds = Some(match bounds {
+                Some(bounds) => bounds.union(&display_bounds),
+                None => display_bounds,
+            });
+        }
+
+        bounds.ok_or_else(|| XCapError::new("Not found active display"))
+    }
+
     pub fn from_point(x: i32, y: i32) -> XCapResult<ImplMonitor> {
         let point = CGPoint {
             x: x as f64,
@@ -137,6 +271,40 @@ impl ImplMonitor {
         Ok(name)
     }
 
+    pub fn connector(&self) -> XCapResult<String> {
+        // MacOS 没有公开显示器的接口信息，使用 EDID 中的厂商、型号和序列号标识显示器
+        let (vendor, model, serial) = unsafe {
+            (
+                CGDisplayVendorNumber(self.cg_direct_display_id),
+                CGDisplayModelNumber(self.cg_direct_display_id),
+                CGDisplaySerialNumber(self.cg_direct_display_id),
+            )
+        };
+
+        Ok(format!("{:04x}-{:04x}-{}", vendor, model, serial))
+    }
+
+    pub fn edid_id(&self) -> XCapResult<Option<String>> {
+        let (vendor, model, serial) = unsafe {
+            (
+                CGDisplayVendorNumber(self.cg_direct_display_id),
+                CGDisplayModelNumber(self.cg_direct_display_id),
+                CGDisplaySerialNumber(self.cg_direct_display_id),
+            )
+        };
+
+        // 没有序列号时无法区分同型号的显示器
+        if serial == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(format_edid_id(
+            vendor as u16,
+            model as u16,
+            &serial.to_string(),
+        )))
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         let rect = unsafe { CGDisplayBounds(self.cg_direct_display_id) };
 
@@ -167,7 +335,30 @@ impl ImplMonitor {
         Ok(rotation as f32)
     }
 
+    pub fn bits_per_pixel(&self) -> XCapResult<u32> {
+        let display_mode = unsafe { CGDisplayCopyDisplayMode(self.cg_direct_display_id) }
+            .ok_or_else(|| XCapError::new("CGDisplayCopyDisplayMode failed"))?;
+
+        Ok(get_display_mode_bit_depth(&display_mode))
+    }
+
+    pub fn is_hdr(&self) -> XCapResult<bool> {
+        let ns_screen = get_ns_screen(self.cg_direct_display_id)?;
+
+        // 显示器正在显示 EDR（HDR）内容时，最大分量值大于 1.0
+        Ok(ns_screen.maximumExtendedDynamicRangeColorComponentValue() > 1.0)
+    }
+
     pub fn scale_factor(&self) -> XCapResult<f32> {
+        if let Some(scale_factor) = get_scale_factor_override(self.cg_direct_display_id) {
+            return Ok(scale_factor);
+        }
+
+        self.pixel_scale()
+    }
+
+    pub fn pixel_scale(&self) -> XCapResult<f32> {
+        // 坐标单位为点，截图为像素，不受缩放比例覆盖的影响
         let display_mode = unsafe { CGDisplayCopyDisplayMode(self.cg_direct_display_id) };
         let pixel_width = unsafe { CGDisplayModeGetPixelWidth(display_mode.as_deref()) };
         let width = self.width()?;
@@ -175,6 +366,14 @@ impl ImplMonitor {
         Ok(pixel_width as f32 / width as f32)
     }
 
+    pub fn scale_factor_source(&self) -> XCapResult<ScaleFactorSource> {
+        if get_scale_factor_override(self.cg_direct_display_id).is_some() {
+            return Ok(ScaleFactorSource::Override);
+        }
+
+        Ok(ScaleFactorSource::Detected)
+    }
+
     pub fn frequency(&self) -> XCapResult<f32> {
         let frequency = unsafe {
             let display_mode = CGDisplayCopyDisplayMode(self.cg_direct_display_id);
@@ -184,6 +383,40 @@ impl ImplMonitor {
         Ok(frequency as f32)
     }
 
+    pub fn available_modes(&self) -> XCapResult<Vec<DisplayMode>> {
+        let mut modes = Vec::new();
+
+        unsafe {
+            let cf_array = match CGDisplayCopyAllDisplayModes(self.cg_direct_display_id, None) {
+                Some(cf_array) => cf_array,
+                None => return Ok(modes),
+            };
+
+            for i in 0..CFArrayGetCount(&cf_array) {
+                let display_mode_ref = CFArrayGetValueAtIndex(&cf_array, i) as *const CGDisplayMode;
+
+                if display_mode_ref.is_null() {
+                    continue;
+                }
+
+                let display_mode = &*display_mode_ref;
+
+                let mode = DisplayMode {
+                    width: CGDisplayModeGetPixelWidth(Some(display_mode)) as u32,
+                    height: CGDisplayModeGetPixelHeight(Some(display_mode)) as u32,
+                    refresh_rate: CGDisplayModeGetRefreshRate(Some(display_mode)) as f32,
+                    bit_depth: get_display_mode_bit_depth(display_mode),
+                };
+
+                if !modes.contains(&mode) {
+                    modes.push(mode);
+                }
+            }
+        }
+
+        Ok(modes)
+    }
+
     pub fn is_primary(&self) -> XCapResult<bool> {
         let is_primary = unsafe { CGDisplayIsMain(self.cg_direct_display_id) };
 
@@ -196,10 +429,251 @@ impl ImplMonitor {
         Ok(is_builtin)
     }
 
+    pub fn gamma_ramp(&self) -> XCapResult<GammaRamp> {
+        let capacity = unsafe { CGDisplayGammaTableCapacity(self.cg_direct_display_id) };
+
+        let mut red = vec![0.0; capacity as usize];
+        let mut green = vec![0.0; capacity as usize];
+        let mut blue = vec![0.0; capacity as usize];
+        let mut sample_count = 0;
+
+        let cg_error = unsafe {
+            CGGetDisplayTransferByTable(
+                self.cg_direct_display_id,
+                capacity,
+                red.as_mut_ptr(),
+                green.as_mut_ptr(),
+                blue.as_mut_ptr(),
+                &mut sample_count,
+            )
+        };
+
+        if cg_error != CGError::Success {
+            return Err(XCapError::new(format!(
+                "CGGetDisplayTransferByTable failed: {:?}",
+                cg_error
+            )));
+        }
+
+        // CGGammaValue 的范围为 0.0 到 1.0
+        let to_u16 = |table: Vec<f32>| {
+            table[..sample_count as usize]
+                .iter()
+                .map(|&value| (value.clamp(0.0, 1.0) * 65535.0).round() as u16)
+                .collect()
+        };
+
+        Ok(GammaRamp {
+            red: to_u16(red),
+            green: to_u16(green),
+            blue: to_u16(blue),
+        })
+    }
+
+    pub fn set_gamma_ramp(&self, gamma_ramp: &GammaRamp) -> XCapResult<()> {
+        // CGGammaValue 的范围为 0.0 到 1.0
+        let to_f32 = |table: &[u16]| -> Vec<f32> {
+            table.iter().map(|&value| value as f32 / 65535.0).collect()
+        };
+
+        let red = to_f32(&gamma_ramp.red);
+        let green = to_f32(&gamma_ramp.green);
+        let blue = to_f32(&gamma_ramp.blue);
+
+        let cg_error = unsafe {
+            CGSetDisplayTransferByTable(
+                self.cg_direct_display_id,
+                red.len() as u32,
+                red.as_ptr(),
+                green.as_ptr(),
+                blue.as_ptr(),
+            )
+        };
+
+        if cg_error != CGError::Success {
+            return Err(XCapError::new(format!(
+                "CGSetDisplayTransferByTable failed: {:?}",
+                cg_error
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn work_area(&self) -> XCapResult<Rect> {
+        let screen = get_ns_screen(self.cg_direct_display_id)?;
+        let visible_frame = screen.visibleFrame();
+
+        // visibleFrame 的原点在主屏幕左下角，需要转换为原点在左上角的坐标
+        let main_display_bounds = unsafe { CGDisplayBounds(CGMainDisplayID()) };
+        let (x, y) = global_point_to_i32(
+            visible_frame.origin.x,
+            appkit_to_global_y(
+                visible_frame.origin.y,
+                visible_frame.size.height,
+                main_display_bounds.size.height,
+            ),
+        );
+
+        Ok(Rect::new(
+            x,
+            y,
+            visible_frame.size.width as u32,
+            visible_frame.size.height as u32,
+        ))
+    }
+
+    pub fn is_secure_desktop(&self) -> XCapResult<bool> {
+        Ok(false)
+    }
+
+    pub fn has_capture_permission(&self) -> bool {
+        // 屏幕录制权限是整个应用的，不区分显示器
+        unsafe { CGPreflightScreenCaptureAccess() }
+    }
+
+    pub fn request_capture_permission(&self) -> XCapResult<()> {
+        if unsafe { CGRequestScreenCaptureAccess() } {
+            return Ok(());
+        }
+
+        Err(XCapError::PermissionDenied(
+            "Screen recording permission is not granted".to_string(),
+        ))
+    }
+
+    pub fn forget_capture_permission(&self) -> XCapResult<()> {
+        // 屏幕录制权限只能在系统设置中撤销
+        Ok(())
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         let cg_rect = unsafe { CGDisplayBounds(self.cg_direct_display_id) };
 
-        capture(cg_rect, CGWindowListOption::OptionAll, 0)
+        capture_display(self.cg_direct_display_id, cg_rect)
+    }
+
+    pub fn capture_image_with_acceleration(&self) -> XCapResult<(RgbaImage, Acceleration)> {
+        let cg_rect = unsafe { CGDisplayBounds(self.cg_direct_display_id) };
+        let (native_image, acceleration) =
+            capture_display_with_acceleration(self.cg_direct_display_id, cg_rect)?;
+
+        Ok((native_image.into_rgba_image()?, acceleration))
+    }
+
+    pub fn wait_for_vblank(&self) -> XCapResult<()> {
+        wait_for_vblank(self.cg_direct_display_id)
+    }
+
+    pub fn capture_image_cancellable(&self, token: &CancellationToken) -> XCapResult<RgbaImage> {
+        // ScreenCaptureKit 截图有自己的超时，只在开始前和返回前检查
+        token.check()?;
+        let image = self.capture_image()?;
+        token.check()?;
+
+        Ok(image)
+    }
+
+    pub fn capture_backends(&self) -> Vec<(&'static str, CaptureQuality)> {
+        if !screen_capture_kit::is_available() {
+            return vec![
+                ("CGWindowListCreateImage", CaptureQuality::Balanced),
+                ("CGWindowListCreateImage nominal", CaptureQuality::Fast),
+            ];
+        }
+
+        vec![
+            ("ScreenCaptureKit", CaptureQuality::Balanced),
+            ("ScreenCaptureKit sRGB", CaptureQuality::HighFidelity),
+            ("CGWindowListCreateImage nominal", CaptureQuality::Fast),
+        ]
+    }
+
+    pub fn default_backend_order() -> Vec<Backend> {
+        vec![Backend::ScreenCaptureKit, Backend::CgWindowList]
+    }
+
+    pub fn capture_image_with_backend(&self, backend: Backend) -> XCapResult<RgbaImage> {
+        match backend {
+            Backend::ScreenCaptureKit => {
+                if !screen_capture_kit::is_available() {
+                    return Err(XCapError::Unsupported(
+                        "ScreenCaptureKit requires MacOS 14.0 or later".to_string(),
+                    ));
+                }
+
+                screen_capture_kit::capture_display(self.cg_direct_display_id, None, false)?
+                    .into_rgba_image()
+            }
+            Backend::CgWindowList => {
+                let cg_rect = unsafe { CGDisplayBounds(self.cg_direct_display_id) };
+
+                capture_display_cg_window_list(cg_rect)
+            }
+            _ => Err(XCapError::Unsupported(format!(
+                "{:?} is not available on MacOS",
+                backend
+            ))),
+        }
+    }
+
+    pub fn capture_image_with_quality(&self, quality: CaptureQuality) -> XCapResult<RgbaImage> {
+        match quality {
+            CaptureQuality::Fast => {
+                let cg_rect = unsafe { CGDisplayBounds(self.cg_direct_display_id) };
+
+                capture_display_nominal(cg_rect)
+            }
+            CaptureQuality::Balanced => self.capture_image(),
+            CaptureQuality::HighFidelity => capture_display_srgb(self.cg_direct_display_id),
+        }
+    }
+
+    pub fn capture_image_native(&self) -> XCapResult<NativeImage> {
+        let cg_rect = unsafe { CGDisplayBounds(self.cg_direct_display_id) };
+
+        capture_display_native(self.cg_direct_display_id, cg_rect)
+    }
+
+    pub fn capture_secure_desktop(&self) -> XCapResult<RgbaImage> {
+        Err(XCapError::Unsupported(
+            "Capturing the lock screen is only supported on Windows".to_string(),
+        ))
+    }
+
+    pub fn capture_area(&self, area: Rect) -> XCapResult<RgbaImage> {
+        // right/bottom 是饱和计算的，超出 i32 范围的区域也会被裁剪到显示器内
+        let area = Rect::new(0, 0, self.width()?, self.height()?)
+            .clamp_relative(&area)
+            .ok_or_else(|| XCapError::new("Capture area is outside the monitor"))?;
+        let cg_rect = unsafe { CGDisplayBounds(self.cg_direct_display_id) };
+
+        let cg_rect = CGRect::new(
+            CGPoint::new(
+                cg_rect.origin.x + area.x as f64,
+                cg_rect.origin.y + area.y as f64,
+            ),
+            CGSize::new(area.width as f64, area.height as f64),
+        );
+
+        capture_display(self.cg_direct_display_id, cg_rect)
+    }
+
+    pub fn capture_area_scaled(
+        &self,
+        area: Rect,
+        width: u32,
+        height: u32,
+    ) -> XCapResult<RgbaImage> {
+        let image = self.capture_area(area)?;
+
+        Ok(resize(&image, width, height, FilterType::Triangle))
+    }
+
+    pub fn capture_scaled(&self, width: u32, height: u32) -> XCapResult<RgbaImage> {
+        let image = self.capture_image()?;
+
+        Ok(resize(&image, width, height, FilterType::Triangle))
     }
 
     pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
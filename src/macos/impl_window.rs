@@ -1,22 +1,34 @@
 use std::ffi::c_void;
 
-use image::RgbaImage;
-use objc2_app_kit::NSWorkspace;
+use image::{imageops::crop_imm, RgbaImage};
+use objc2_app_kit::{NSApplicationActivationOptions, NSRunningApplication, NSWorkspace};
 use objc2_core_foundation::{
     CFArrayGetCount, CFArrayGetValueAtIndex, CFBoolean, CFBooleanGetValue, CFDictionary,
     CFDictionaryCreateCopy, CFDictionaryGetValue, CFNumber, CFNumberGetValue, CFNumberType,
-    CFRetained, CFString, CGPoint, CGRect,
+    CFRetained, CFString, CGPoint, CGRect, CGSize,
 };
 use objc2_core_graphics::{
     CGDisplayBounds, CGMainDisplayID, CGRectContainsPoint, CGRectIntersectsRect,
     CGRectMakeWithDictionaryRepresentation, CGWindowListCopyWindowInfo, CGWindowListOption,
 };
 
-use crate::{error::XCapResult, XCapError};
+use crate::{
+    alpha_mode::AlphaMode,
+    coordinate::{global_point_to_i32, snap_to_pixel_grid},
+    error::XCapResult,
+    raw_handle::RawWindowHandle,
+    rect::Rect,
+    XCapError,
+};
 
-use super::{capture::capture, impl_monitor::ImplMonitor};
+use super::{
+    accessibility::capture_scrollable,
+    capture::{capture, capture_window},
+    impl_foreground_watcher::get_foreground_window,
+    impl_monitor::ImplMonitor,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct ImplWindow {
     pub window_id: u32,
 }
@@ -66,6 +78,28 @@ fn get_cf_number_i32_value(cf_dictionary: &CFDictionary, key: &str) -> XCapResul
     }
 }
 
+fn get_cf_number_f64_value(cf_dictionary: &CFDictionary, key: &str) -> XCapResult<f64> {
+    unsafe {
+        let cf_number = get_cf_dictionary_get_value(cf_dictionary, key)? as *const CFNumber;
+
+        let mut value: f64 = 0.0;
+        let is_success = CFNumberGetValue(
+            &*cf_number,
+            CFNumberType::DoubleType,
+            &mut value as *mut _ as *mut c_void,
+        );
+
+        if !is_success {
+            return Err(XCapError::new(format!(
+                "Get {} CFNumberGetValue failed",
+                key
+            )));
+        }
+
+        Ok(value)
+    }
+}
+
 fn get_cf_string_value(cf_dictionary: &CFDictionary, key: &str) -> XCapResult<String> {
     let value_ref = get_cf_dictionary_get_value(cf_dictionary, key)? as *const CFString;
     let value = unsafe { (*value_ref).to_string() };
@@ -98,8 +132,13 @@ fn get_window_cg_rect(window_cf_dictionary: &CFDictionary) -> XCapResult<CGRect>
     }
 }
 
-fn get_window_id(window_cf_dictionary: &CFDictionary) -> XCapResult<u32> {
-    let window_name = get_cf_string_value(window_cf_dictionary, "kCGWindowName")?;
+fn get_window_id(window_cf_dictionary: &CFDictionary, include_untitled: bool) -> XCapResult<u32> {
+    // 没有屏幕录制权限时，大部分窗口都读取不到 kCGWindowName
+    let window_name = match get_cf_string_value(window_cf_dictionary, "kCGWindowName") {
+        Ok(window_name) => window_name,
+        Err(_) if include_untitled => String::new(),
+        Err(err) => return Err(err),
+    };
 
     let window_owner_name = get_cf_string_value(window_cf_dictionary, "kCGWindowOwnerName")?;
 
@@ -122,9 +161,9 @@ fn get_window_id(window_cf_dictionary: &CFDictionary) -> XCapResult<u32> {
 pub fn get_window_cf_dictionary(window_id: u32) -> XCapResult<CFRetained<CFDictionary>> {
     unsafe {
         // CGWindowListCopyWindowInfo 返回窗口顺序为从顶层到最底层
-        // 即在前面的窗口在数组前面
+        // 即在前面的窗口在数组前面，使用 OptionAll 以便查找其他桌面空间中的窗口
         let cf_array = match CGWindowListCopyWindowInfo(
-            CGWindowListOption::OptionOnScreenOnly | CGWindowListOption::ExcludeDesktopElements,
+            CGWindowListOption::OptionAll | CGWindowListOption::ExcludeDesktopElements,
             0,
         ) {
             Some(cf_array) => cf_array,
@@ -142,7 +181,7 @@ pub fn get_window_cf_dictionary(window_id: u32) -> XCapResult<CFRetained<CFDicti
             }
             let window_cf_dictionary = &*window_cf_dictionary_ref;
 
-            let current_window_id = match get_window_id(window_cf_dictionary) {
+            let current_window_id = match get_window_id(window_cf_dictionary, true) {
                 Ok(val) => val,
                 Err(_) => continue,
             };
@@ -157,47 +196,182 @@ pub fn get_window_cf_dictionary(window_id: u32) -> XCapResult<CFRetained<CFDicti
     }
 }
 
+// kCGDockWindowLevel, kCGMainMenuWindowLevel, kCGStatusWindowLevel
+const SHELL_WINDOW_LAYERS: [i32; 3] = [20, 24, 25];
+
+fn get_shell_windows() -> XCapResult<Vec<ImplWindow>> {
+    unsafe {
+        let mut impl_windows = Vec::new();
+
+        let cf_array = match CGWindowListCopyWindowInfo(CGWindowListOption::OptionOnScreenOnly, 0) {
+            Some(cf_array) => cf_array,
+            None => return Ok(impl_windows),
+        };
+
+        for i in 0..CFArrayGetCount(&cf_array) {
+            let window_cf_dictionary_ref =
+                CFArrayGetValueAtIndex(&cf_array, i) as *const CFDictionary;
+
+            if window_cf_dictionary_ref.is_null() {
+                continue;
+            }
+
+            let window_cf_dictionary = &*window_cf_dictionary_ref;
+
+            // 没有屏幕录制权限时读取不到 kCGWindowName，通过窗口层级识别 Dock 与菜单栏
+            let is_shell_window = get_cf_number_i32_value(window_cf_dictionary, "kCGWindowLayer")
+                .map(|layer| SHELL_WINDOW_LAYERS.contains(&layer))
+                .unwrap_or(false);
+
+            if !is_shell_window {
+                continue;
+            }
+
+            if let Ok(window_id) = get_window_id(window_cf_dictionary, true) {
+                impl_windows.push(ImplWindow::new(window_id));
+            }
+        }
+
+        Ok(impl_windows)
+    }
+}
+
+// kCGCursorWindowLevel，点击动画等跟随光标的效果绘制在这一层及以上的窗口中
+const CURSOR_WINDOW_LAYER: i32 = 2147483630;
+
+/// 从顶层到底层排列的 (窗口 id, 层级) 中，层级不低于 `layer` 的最底层的窗口
+fn get_lowest_window_at_or_above(windows: &[(u32, i32)], layer: i32) -> Option<u32> {
+    windows
+        .iter()
+        .take_while(|(_, window_layer)| *window_layer >= layer)
+        .last()
+        .map(|(window_id, _)| *window_id)
+}
+
+/// 光标阴影、点击波纹等临时效果所在的最底层的窗口，截图时只截取它下面的窗口
+pub(super) fn get_cursor_effect_window() -> Option<u32> {
+    unsafe {
+        let cf_array = CGWindowListCopyWindowInfo(CGWindowListOption::OptionOnScreenOnly, 0)?;
+
+        let mut windows = Vec::new();
+        for i in 0..CFArrayGetCount(&cf_array) {
+            let window_cf_dictionary_ref =
+                CFArrayGetValueAtIndex(&cf_array, i) as *const CFDictionary;
+
+            if window_cf_dictionary_ref.is_null() {
+                continue;
+            }
+
+            let window_cf_dictionary = &*window_cf_dictionary_ref;
+
+            let layer = get_cf_number_i32_value(window_cf_dictionary, "kCGWindowLayer");
+            let window_id = get_window_id(window_cf_dictionary, true);
+            if let (Ok(window_id), Ok(layer)) = (window_id, layer) {
+                windows.push((window_id, layer));
+            }
+        }
+
+        get_lowest_window_at_or_above(&windows, CURSOR_WINDOW_LAYER)
+    }
+}
+
+fn get_windows(
+    list_option: CGWindowListOption,
+    include_untitled: bool,
+    max: usize,
+) -> XCapResult<Vec<ImplWindow>> {
+    unsafe {
+        let mut impl_windows = Vec::new();
+
+        // CGWindowListCopyWindowInfo 返回窗口顺序为从顶层到最底层
+        // 即在前面的窗口在数组前面
+        let cf_array = match CGWindowListCopyWindowInfo(
+            list_option | CGWindowListOption::ExcludeDesktopElements,
+            0,
+        ) {
+            Some(cf_array) => cf_array,
+            None => return Ok(impl_windows),
+        };
+
+        let windows_count = CFArrayGetCount(&cf_array);
+
+        for i in 0..windows_count {
+            if impl_windows.len() >= max {
+                break;
+            }
+
+            let window_cf_dictionary_ref =
+                CFArrayGetValueAtIndex(&cf_array, i) as *const CFDictionary;
+
+            if window_cf_dictionary_ref.is_null() {
+                continue;
+            }
+
+            let window_cf_dictionary = &*window_cf_dictionary_ref;
+
+            let window_id = match get_window_id(window_cf_dictionary, include_untitled) {
+                Ok(window_id) => window_id,
+                Err(_) => continue,
+            };
+
+            impl_windows.push(ImplWindow::new(window_id));
+        }
+
+        Ok(impl_windows)
+    }
+}
+
 impl ImplWindow {
     pub fn new(window_id: u32) -> ImplWindow {
         ImplWindow { window_id }
     }
 
-    pub fn all() -> XCapResult<Vec<ImplWindow>> {
-        unsafe {
-            let mut impl_window = Vec::new();
+    pub fn from_raw_handle(raw_handle: RawWindowHandle) -> XCapResult<ImplWindow> {
+        let RawWindowHandle::CoreGraphics { window_id } = raw_handle else {
+            return Err(XCapError::new("Not a CoreGraphics window id"));
+        };
 
-            // CGWindowListCopyWindowInfo 返回窗口顺序为从顶层到最底层
-            // 即在前面的窗口在数组前面
-            let cf_array = match CGWindowListCopyWindowInfo(
-                CGWindowListOption::OptionOnScreenOnly | CGWindowListOption::ExcludeDesktopElements,
-                0,
-            ) {
-                Some(cf_array) => cf_array,
-                None => return Ok(impl_window),
-            };
+        Ok(ImplWindow::new(window_id))
+    }
 
-            let windows_count = CFArrayGetCount(&cf_array);
+    pub fn from_automation_id(_automation_id: &str) -> XCapResult<ImplWindow> {
+        Err(XCapError::Unsupported(
+            "UI Automation is only supported on Windows".to_string(),
+        ))
+    }
 
-            for i in 0..windows_count {
-                let window_cf_dictionary_ref =
-                    CFArrayGetValueAtIndex(&cf_array, i) as *const CFDictionary;
+    pub fn raw_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::CoreGraphics {
+            window_id: self.window_id,
+        }
+    }
 
-                if window_cf_dictionary_ref.is_null() {
-                    continue;
-                }
+    pub fn is_valid(&self) -> bool {
+        get_window_cf_dictionary(self.window_id).is_ok()
+    }
 
-                let window_cf_dictionary = &*window_cf_dictionary_ref;
+    pub fn all() -> XCapResult<Vec<ImplWindow>> {
+        get_windows(CGWindowListOption::OptionOnScreenOnly, false, usize::MAX)
+    }
 
-                let window_id = match get_window_id(window_cf_dictionary) {
-                    Ok(window_id) => window_id,
-                    Err(_) => continue,
-                };
+    pub fn all_limited(max: usize) -> XCapResult<Vec<ImplWindow>> {
+        get_windows(CGWindowListOption::OptionOnScreenOnly, false, max)
+    }
 
-                impl_window.push(ImplWindow::new(window_id));
-            }
+    pub fn shell_surfaces() -> XCapResult<Vec<ImplWindow>> {
+        get_shell_windows()
+    }
 
-            Ok(impl_window)
-        }
+    pub fn all_spaces() -> XCapResult<Vec<ImplWindow>> {
+        get_windows(CGWindowListOption::OptionAll, false, usize::MAX)
+    }
+
+    pub fn all_including_untitled() -> XCapResult<Vec<ImplWindow>> {
+        get_windows(CGWindowListOption::OptionOnScreenOnly, true, usize::MAX)
+    }
+
+    pub fn foreground() -> XCapResult<Option<ImplWindow>> {
+        get_foreground_window()
     }
 }
 
@@ -220,10 +394,27 @@ impl ImplWindow {
         get_cf_string_value(window_cf_dictionary.as_ref(), "kCGWindowOwnerName")
     }
 
+    pub fn process_name(&self) -> XCapResult<String> {
+        let pid = self.pid()?;
+
+        unsafe {
+            let app = NSRunningApplication::runningApplicationWithProcessIdentifier(pid as i32)
+                .ok_or_else(|| XCapError::new(format!("Get running application {} failed", pid)))?;
+
+            let process_name = app
+                .executableURL()
+                .and_then(|executable_url| executable_url.lastPathComponent())
+                .ok_or_else(|| XCapError::new(format!("Get process {} name failed", pid)))?;
+
+            Ok(process_name.to_string())
+        }
+    }
+
     pub fn title(&self) -> XCapResult<String> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
 
-        get_cf_string_value(window_cf_dictionary.as_ref(), "kCGWindowName")
+        // 窗口没有标题或者无法读取标题时，kCGWindowName 不存在
+        Ok(get_cf_string_value(window_cf_dictionary.as_ref(), "kCGWindowName").unwrap_or_default())
     }
 
     pub fn current_monitor(&self) -> XCapResult<ImplMonitor> {
@@ -253,12 +444,38 @@ impl ImplWindow {
         Ok(impl_monitor.to_owned())
     }
 
+    pub fn monitor_coverage(&self) -> XCapResult<Vec<(ImplMonitor, f32)>> {
+        let window_rect = Rect::new(self.x()?, self.y()?, self.width()?, self.height()?);
+        let window_area = window_rect.area();
+
+        let mut monitor_coverage = Vec::new();
+
+        if window_area == 0 {
+            return Ok(monitor_coverage);
+        }
+
+        for impl_monitor in ImplMonitor::all()? {
+            let monitor_rect = Rect::new(
+                impl_monitor.x()?,
+                impl_monitor.y()?,
+                impl_monitor.width()?,
+                impl_monitor.height()?,
+            );
+
+            if let Some(overlap) = window_rect.intersection(&monitor_rect) {
+                monitor_coverage.push((impl_monitor, overlap.area() as f32 / window_area as f32));
+            }
+        }
+
+        Ok(monitor_coverage)
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
 
         let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
 
-        Ok(cg_rect.origin.x as i32)
+        Ok(global_point_to_i32(cg_rect.origin.x, cg_rect.origin.y).0)
     }
 
     pub fn y(&self) -> XCapResult<i32> {
@@ -266,7 +483,8 @@ impl ImplWindow {
 
         let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
 
-        Ok(cg_rect.origin.y as i32)
+        // kCGWindowBounds 和 CGDisplayBounds 一样原点在主显示器左上角，不需要翻转
+        Ok(global_point_to_i32(cg_rect.origin.x, cg_rect.origin.y).1)
     }
 
     pub fn z(&self) -> XCapResult<i32> {
@@ -295,7 +513,7 @@ impl ImplWindow {
 
                 let window_cf_dictionary = &*window_cf_dictionary_ref;
 
-                let window_id = match get_window_id(window_cf_dictionary) {
+                let window_id = match get_window_id(window_cf_dictionary, true) {
                     Ok(window_id) => window_id,
                     Err(_) => continue,
                 };
@@ -327,7 +545,9 @@ impl ImplWindow {
 
     pub fn is_minimized(&self) -> XCapResult<bool> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
-        let is_on_screen = get_cf_bool_value(window_cf_dictionary.as_ref(), "kCGWindowIsOnscreen")?;
+        // 不在屏幕上的窗口（如其他桌面空间中的窗口）没有 kCGWindowIsOnscreen
+        let is_on_screen = get_cf_bool_value(window_cf_dictionary.as_ref(), "kCGWindowIsOnscreen")
+            .unwrap_or(false);
         let is_maximized = self.is_maximized()?;
 
         Ok(!is_on_screen && !is_maximized)
@@ -365,15 +585,170 @@ impl ImplWindow {
         Ok(false)
     }
 
+    pub fn focus(&self) -> XCapResult<()> {
+        let pid = self.pid()?;
+
+        unsafe {
+            let app = NSRunningApplication::runningApplicationWithProcessIdentifier(pid as i32)
+                .ok_or_else(|| XCapError::new(format!("Get running application {} failed", pid)))?;
+
+            // 只能激活应用程序，应用程序的最前面的窗口会成为前台窗口
+            #[allow(deprecated)]
+            let is_activated =
+                app.activateWithOptions(NSApplicationActivationOptions::ActivateIgnoringOtherApps);
+
+            if !is_activated {
+                return Err(XCapError::new(format!(
+                    "Activate application {} failed",
+                    pid
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_capture_protected(&self) -> XCapResult<bool> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+
+        // kCGWindowSharingNone
+        let window_sharing_state =
+            get_cf_number_i32_value(window_cf_dictionary.as_ref(), "kCGWindowSharingState")?;
+
+        Ok(window_sharing_state == 0)
+    }
+
+    pub fn alpha_mode(&self) -> XCapResult<AlphaMode> {
+        // CGWindowListCreateImage 和 ScreenCaptureKit 返回的图像都是 kCGImageAlphaPremultipliedFirst
+        Ok(AlphaMode::Premultiplied)
+    }
+
+    pub fn opacity(&self) -> XCapResult<f32> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+
+        let alpha =
+            get_cf_number_f64_value(window_cf_dictionary.as_ref(), "kCGWindowAlpha").unwrap_or(1.0);
+
+        Ok(alpha as f32)
+    }
+
+    pub fn frame_offset(&self) -> XCapResult<(i32, i32)> {
+        // 截图的范围就是窗口的范围
+        Ok((0, 0))
+    }
+
+    pub fn child_rect_at(&self, _x: i32, _y: i32) -> XCapResult<Rect> {
+        // 窗口内的控件不是独立的 CGWindow，只能通过辅助功能 API 获取，目前没有接入
+        Err(XCapError::new(
+            "Capturing a child element is not supported on MacOS",
+        ))
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
 
         let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
 
-        capture(
-            cg_rect,
+        capture_window(self.window_id, cg_rect)
+    }
+
+    pub fn capture_with_picker() -> XCapResult<RgbaImage> {
+        Err(XCapError::Unsupported(
+            "The window picker is only available on Linux (Wayland)".to_string(),
+        ))
+    }
+
+    pub fn capture_image_snapped(&self) -> XCapResult<(RgbaImage, Rect)> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+        let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
+        let scale = self.current_monitor()?.pixel_scale()? as f64;
+
+        let pixel_rect = snap_to_pixel_grid(
+            cg_rect.origin.x,
+            cg_rect.origin.y,
+            cg_rect.size.width,
+            cg_rect.size.height,
+            scale,
+        );
+        // 对齐后的区域换算回点，截图的边界正好落在像素上。ScreenCaptureKit 截取窗口时不使用区域，
+        // 所以这里使用 CGWindowListCreateImage
+        let snapped_cg_rect = CGRect::new(
+            CGPoint::new(pixel_rect.x as f64 / scale, pixel_rect.y as f64 / scale),
+            CGSize::new(
+                pixel_rect.width as f64 / scale,
+                pixel_rect.height as f64 / scale,
+            ),
+        );
+
+        let image = capture(
+            snapped_cg_rect,
             CGWindowListOption::OptionIncludingWindow,
             self.window_id,
-        )
+        )?;
+        let pixel_rect = Rect::new(pixel_rect.x, pixel_rect.y, image.width(), image.height());
+
+        Ok((image, pixel_rect))
+    }
+
+    pub fn capture_scrollable(&self) -> XCapResult<RgbaImage> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+        let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
+
+        capture_scrollable(self.pid()? as i32, cg_rect, |viewport| {
+            let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+            let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
+            let image = capture_window(self.window_id, cg_rect)?;
+
+            // 辅助功能 API 的坐标单位是点，截图是像素
+            let scale = image.width() as f64 / cg_rect.size.width;
+            let viewport_rect = Rect::new(
+                ((viewport.origin.x - cg_rect.origin.x) * scale).round() as i32,
+                ((viewport.origin.y - cg_rect.origin.y) * scale).round() as i32,
+                (viewport.size.width * scale).round() as u32,
+                (viewport.size.height * scale).round() as u32,
+            );
+            let crop_rect = Rect::new(0, 0, image.width(), image.height())
+                .intersection(&viewport_rect)
+                .ok_or_else(|| XCapError::new("The scrollable area is outside the window"))?;
+
+            Ok(crop_imm(
+                &image,
+                crop_rect.x as u32,
+                crop_rect.y as u32,
+                crop_rect.width,
+                crop_rect.height,
+            )
+            .to_image())
+        })
     }
+    pub fn clear_cache() -> XCapResult<()> {
+        // 没有缓存的截图
+        Ok(())
+    }
+
+    pub fn capture_all(impl_windows: &[ImplWindow]) -> XCapResult<Vec<RgbaImage>> {
+        impl_windows
+            .iter()
+            .map(|impl_window| impl_window.capture_image())
+            .collect()
+    }
+}
+
+#[test]
+fn lowest_window_at_or_above_skips_to_the_last_overlay() {
+    let windows = [
+        (10, CURSOR_WINDOW_LAYER + 1),
+        (11, CURSOR_WINDOW_LAYER),
+        (12, 25),
+        (13, 0),
+    ];
+
+    assert_eq!(
+        get_lowest_window_at_or_above(&windows, CURSOR_WINDOW_LAYER),
+        Some(11)
+    );
+    assert_eq!(
+        get_lowest_window_at_or_above(&windows[2..], CURSOR_WINDOW_LAYER),
+        None
+    );
 }
@@ -1,5 +1,15 @@
+mod accessibility;
 mod capture;
+mod screen_capture_kit;
+mod vblank;
 
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+pub mod clock;
+pub mod cursor;
+pub mod impl_foreground_watcher;
+#[cfg(feature = "hotkey")]
+pub mod impl_hotkey_listener;
 pub mod impl_monitor;
 pub mod impl_video_recorder;
 pub mod impl_window;
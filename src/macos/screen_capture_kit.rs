@@ -0,0 +1,180 @@
+use std::{
+    ptr::NonNull,
+    sync::mpsc::{channel, Sender},
+    time::Duration,
+};
+
+use block2::RcBlock;
+use objc2::{rc::Retained, AllocAnyThread};
+use objc2_core_foundation::CGRect;
+use objc2_core_graphics::{kCGColorSpaceSRGB, CGDirectDisplayID, CGImage, CGWindowID};
+use objc2_foundation::{NSArray, NSError, NSOperatingSystemVersion, NSProcessInfo};
+use objc2_screen_capture_kit::{
+    SCContentFilter, SCScreenshotManager, SCShareableContent, SCStreamConfiguration,
+};
+
+use crate::{
+    capture_limit::check_capture_size,
+    error::{XCapError, XCapResult},
+    native_image::NativeImage,
+};
+
+use super::capture::cg_image_to_native_image;
+
+// ScreenCaptureKit 的回调在其他线程执行，等待超过该时间视为失败
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// SCScreenshotManager 需要 MacOS 14.0 及以上版本，SCShareableContent 需要 12.3
+pub fn is_available() -> bool {
+    NSProcessInfo::processInfo().isOperatingSystemAtLeastVersion(NSOperatingSystemVersion {
+        majorVersion: 14,
+        minorVersion: 0,
+        patchVersion: 0,
+    })
+}
+
+/// SCStreamConfiguration 的 showMouseClicks 需要 MacOS 15.0 及以上版本
+fn is_at_least_macos_15() -> bool {
+    NSProcessInfo::processInfo().isOperatingSystemAtLeastVersion(NSOperatingSystemVersion {
+        majorVersion: 15,
+        minorVersion: 0,
+        patchVersion: 0,
+    })
+}
+
+fn get_shareable_content() -> XCapResult<Retained<SCShareableContent>> {
+    let (tx, rx) = channel();
+
+    let completion_handler = RcBlock::new(
+        move |shareable_content: *mut SCShareableContent, error: *mut NSError| {
+            let result = match unsafe { Retained::retain(shareable_content) } {
+                Some(shareable_content) => Ok(shareable_content),
+                None => Err(error_message("SCShareableContent", error)),
+            };
+
+            let _ = tx.send(result);
+        },
+    );
+
+    unsafe {
+        SCShareableContent::getShareableContentWithCompletionHandler(&completion_handler);
+    }
+
+    rx.recv_timeout(TIMEOUT)
+        .map_err(|err| XCapError::with_source("Get SCShareableContent failed", err))?
+        .map_err(XCapError::new)
+}
+
+fn error_message(operation: &str, error: *mut NSError) -> String {
+    match unsafe { error.as_ref() } {
+        Some(error) => format!("{} failed: {}", operation, error.localizedDescription()),
+        None => format!("{} failed", operation),
+    }
+}
+
+fn send_image(
+    tx: &Sender<Result<NativeImage, String>>,
+    cg_image: *mut CGImage,
+    error: *mut NSError,
+) {
+    let result = match NonNull::new(cg_image) {
+        Some(cg_image) => unsafe { cg_image_to_native_image(Some(cg_image.as_ref())) }
+            .map_err(|err| err.to_string()),
+        None => Err(error_message("SCScreenshotManager", error)),
+    };
+
+    let _ = tx.send(result);
+}
+
+fn capture_image(
+    content_filter: &SCContentFilter,
+    source_rect: Option<CGRect>,
+    convert_to_srgb: bool,
+) -> XCapResult<NativeImage> {
+    unsafe {
+        let stream_configuration = SCStreamConfiguration::new();
+
+        // contentRect 的单位是点，需要乘以 pointPixelScale 得到像素
+        let content_rect = source_rect.unwrap_or_else(|| content_filter.contentRect());
+        let point_pixel_scale = content_filter.pointPixelScale() as f64;
+
+        if let Some(source_rect) = source_rect {
+            stream_configuration.setSourceRect(source_rect);
+        }
+        let width = (content_rect.size.width * point_pixel_scale) as usize;
+        let height = (content_rect.size.height * point_pixel_scale) as usize;
+        check_capture_size(width as u32, height as u32)?;
+
+        stream_configuration.setWidth(width);
+        stream_configuration.setHeight(height);
+        stream_configuration.setShowsCursor(false);
+        // 点击时的波纹效果也不截取
+        if is_at_least_macos_15() {
+            stream_configuration.setShowMouseClicks(false);
+        }
+
+        // 默认使用显示器的色彩空间，转换为 sRGB 后不同显示器的颜色一致
+        if convert_to_srgb {
+            stream_configuration.setColorSpaceName(kCGColorSpaceSRGB);
+        }
+
+        let (tx, rx) = channel();
+
+        let completion_handler =
+            RcBlock::new(move |cg_image: *mut CGImage, error: *mut NSError| {
+                send_image(&tx, cg_image, error);
+            });
+
+        SCScreenshotManager::captureImageWithFilter_configuration_completionHandler(
+            content_filter,
+            &stream_configuration,
+            Some(&completion_handler),
+        );
+
+        rx.recv_timeout(TIMEOUT)
+            .map_err(|err| XCapError::with_source("SCScreenshotManager capture failed", err))?
+            .map_err(XCapError::new)
+    }
+}
+
+/// source_rect 为相对于显示器左上角的区域，单位是点
+pub fn capture_display(
+    display_id: CGDirectDisplayID,
+    source_rect: Option<CGRect>,
+    convert_to_srgb: bool,
+) -> XCapResult<NativeImage> {
+    let shareable_content = get_shareable_content()?;
+
+    unsafe {
+        let display = shareable_content
+            .displays()
+            .iter()
+            .find(|display| display.displayID() == display_id)
+            .ok_or_else(|| XCapError::new("Not found SCDisplay"))?;
+
+        let content_filter = SCContentFilter::initWithDisplay_excludingWindows(
+            SCContentFilter::alloc(),
+            &display,
+            &NSArray::new(),
+        );
+
+        capture_image(&content_filter, source_rect, convert_to_srgb)
+    }
+}
+
+pub fn capture_window(window_id: CGWindowID) -> XCapResult<NativeImage> {
+    let shareable_content = get_shareable_content()?;
+
+    unsafe {
+        let window = shareable_content
+            .windows()
+            .iter()
+            .find(|window| window.windowID() == window_id)
+            .ok_or_else(|| XCapError::new("Not found SCWindow"))?;
+
+        let content_filter =
+            SCContentFilter::initWithDesktopIndependentWindow(SCContentFilter::alloc(), &window);
+
+        capture_image(&content_filter, None, false)
+    }
+}
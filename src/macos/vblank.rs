@@ -0,0 +1,90 @@
+use std::{
+    ffi::c_void,
+    ptr,
+    sync::mpsc::{sync_channel, SyncSender},
+    time::Duration,
+};
+
+use objc2_core_graphics::CGDirectDisplayID;
+use scopeguard::guard;
+
+use crate::error::{XCapError, XCapResult};
+
+type CVDisplayLinkRef = *mut c_void;
+type CVDisplayLinkOutputCallback = extern "C" fn(
+    display_link: CVDisplayLinkRef,
+    in_now: *const c_void,
+    in_output_time: *const c_void,
+    flags_in: u64,
+    flags_out: *mut u64,
+    context: *mut c_void,
+) -> i32;
+
+const KCV_RETURN_SUCCESS: i32 = 0;
+
+// 刷新率最低的显示器也会在这个时间内触发回调
+const VBLANK_TIMEOUT: Duration = Duration::from_millis(200);
+
+#[link(name = "CoreVideo", kind = "framework")]
+extern "C" {
+    fn CVDisplayLinkCreateWithCGDisplay(
+        display_id: CGDirectDisplayID,
+        display_link_out: *mut CVDisplayLinkRef,
+    ) -> i32;
+    fn CVDisplayLinkSetOutputCallback(
+        display_link: CVDisplayLinkRef,
+        callback: CVDisplayLinkOutputCallback,
+        context: *mut c_void,
+    ) -> i32;
+    fn CVDisplayLinkStart(display_link: CVDisplayLinkRef) -> i32;
+    fn CVDisplayLinkStop(display_link: CVDisplayLinkRef) -> i32;
+    fn CVDisplayLinkRelease(display_link: CVDisplayLinkRef);
+}
+
+extern "C" fn output_callback(
+    _display_link: CVDisplayLinkRef,
+    _in_now: *const c_void,
+    _in_output_time: *const c_void,
+    _flags_in: u64,
+    _flags_out: *mut u64,
+    context: *mut c_void,
+) -> i32 {
+    let sender = unsafe { &*(context as *const SyncSender<()>) };
+    // 只关心第一次回调，后续回调在通道满时直接丢弃
+    let _ = sender.try_send(());
+
+    KCV_RETURN_SUCCESS
+}
+
+/// 阻塞直到显示器的 CVDisplayLink 触发下一次回调，即下一帧开始扫描输出
+pub fn wait_for_vblank(display_id: CGDirectDisplayID) -> XCapResult<()> {
+    let (sender, receiver) = sync_channel::<()>(1);
+    let sender = Box::new(sender);
+
+    unsafe {
+        let mut display_link: CVDisplayLinkRef = ptr::null_mut();
+        if CVDisplayLinkCreateWithCGDisplay(display_id, &mut display_link) != KCV_RETURN_SUCCESS {
+            return Err(XCapError::new("CVDisplayLinkCreateWithCGDisplay failed"));
+        }
+
+        let display_link = guard(display_link, |display_link| {
+            CVDisplayLinkStop(display_link);
+            CVDisplayLinkRelease(display_link);
+        });
+
+        let context = &*sender as *const SyncSender<()> as *mut c_void;
+        if CVDisplayLinkSetOutputCallback(*display_link, output_callback, context)
+            != KCV_RETURN_SUCCESS
+        {
+            return Err(XCapError::new("CVDisplayLinkSetOutputCallback failed"));
+        }
+
+        if CVDisplayLinkStart(*display_link) != KCV_RETURN_SUCCESS {
+            return Err(XCapError::new("CVDisplayLinkStart failed"));
+        }
+
+        receiver
+            .recv_timeout(VBLANK_TIMEOUT)
+            .map_err(|err| XCapError::with_source("Wait for display link callback failed", err))
+    }
+}
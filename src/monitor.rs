@@ -1,12 +1,64 @@
-use std::sync::mpsc::Receiver;
+#[cfg(feature = "encoding")]
+use std::io::Write;
+use std::{
+    ops::Range,
+    path::Path,
+    sync::mpsc::Receiver,
+    thread,
+    time::{Duration, Instant},
+};
 
-use image::RgbaImage;
+use image::{
+    imageops::{resize, FilterType},
+    DynamicImage, GrayImage, Rgba32FImage, RgbaImage,
+};
+use scopeguard::guard;
 
 use crate::{
-    error::XCapResult, platform::impl_monitor::ImplMonitor, video_recorder::Frame, VideoRecorder,
+    cancellation_token::CancellationToken,
+    capture_config::{Backend, CaptureConfig},
+    capture_event::record_capture,
+    capture_quality::CaptureQuality,
+    capture_session::CaptureSession,
+    capture_warning::{is_black_image, is_uniform_image, CaptureWarning},
+    color::to_linear_f32,
+    coordinate::{image_to_screen, screen_to_image},
+    diagnostic::{report, Diagnostic, DiagnosticTarget},
+    diff::{borders_match, changed_row_ranges, diff_bounding_box},
+    display_mode::DisplayMode,
+    error::{XCapError, XCapResult},
+    frame_time::FrameTime,
+    gamma_ramp::GammaRamp,
+    known_monitor::KnownMonitor,
+    monitor_descriptor::MonitorDescriptor,
+    native_image::NativeImage,
+    platform::impl_monitor::ImplMonitor,
+    raw_handle::RawMonitorHandle,
+    rect::Rect,
+    rotation::Rotation,
+    save::save_image,
+    scale_factor::{set_scale_factor_override, ScaleFactorSource},
+    video_recorder::Frame,
+    VideoRecorder,
 };
 
-#[derive(Debug, Clone)]
+#[cfg(feature = "encoding")]
+use crate::encoding::{encode_bmp, encode_tiff, encode_to};
+#[cfg(feature = "futures")]
+use crate::frame_stream::monitor_frame_stream;
+#[cfg(feature = "clipboard")]
+use crate::platform::clipboard::set_clipboard_image;
+#[cfg(feature = "encoding")]
+use image::ImageFormat;
+
+// capture_image_stable 比较的边框宽度
+const STABLE_BORDER_BAND: u32 = 16;
+
+/// Monitors compare equal when they wrap the same platform display handle
+/// (`HMONITOR` on Windows, `CGDirectDisplayID` on MacOS, RandR output on Linux).
+/// The handle is stable while the display configuration doesn't change,
+/// on Windows it may be reassigned after monitors are reconnected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Monitor {
     pub(crate) impl_monitor: ImplMonitor,
 }
@@ -27,6 +79,89 @@ impl Monitor {
         Ok(monitors)
     }
 
+    /// List all monitors in a stable order, unlike [`Monitor::all`] which uses the order the
+    /// OS enumerates them in: the primary monitor first, then the others from left to right
+    /// by `x()`, then from top to bottom by `y()`. Monitors at the same position, e.g. mirrored
+    /// ones, are ordered by `id()`.
+    pub fn all_ordered() -> XCapResult<Vec<Monitor>> {
+        let mut keyed_monitors = Vec::new();
+
+        for monitor in Monitor::all()? {
+            // false 排在 true 前面，主显示器排在最前面
+            let key = (
+                !monitor.is_primary()?,
+                monitor.x()?,
+                monitor.y()?,
+                monitor.id()?,
+            );
+            keyed_monitors.push((key, monitor));
+        }
+
+        keyed_monitors.sort_by_key(|(key, _)| *key);
+
+        Ok(keyed_monitors
+            .into_iter()
+            .map(|(_, monitor)| monitor)
+            .collect())
+    }
+
+    /// All displays the system knows about, including inactive ones: displays that are
+    /// remembered but disconnected or turned off on Windows (`QueryDisplayConfig` with
+    /// `QDC_ALL_PATHS`), online but inactive (sleeping or mirrored) displays on MacOS and
+    /// connected but disabled outputs on Linux (X11).
+    pub fn all_including_inactive() -> XCapResult<Vec<KnownMonitor>> {
+        let known_monitors = ImplMonitor::all_including_inactive()?
+            .into_iter()
+            .map(|(name, is_active, impl_monitor)| KnownMonitor {
+                name,
+                is_active,
+                monitor: impl_monitor.map(Monitor::new),
+            })
+            .collect();
+
+        Ok(known_monitors)
+    }
+
+    /// Find the monitor described by `descriptor`. Monitors with the same name and connector
+    /// match, the one with the same position and size is preferred when several do. When none
+    /// matches, e.g. the connector changed, a monitor with the same name, position and size is
+    /// returned.
+    pub fn find(descriptor: &MonitorDescriptor) -> XCapResult<Monitor> {
+        let monitors = Monitor::all()?;
+        let mut candidates = Vec::new();
+        let mut fallback = None;
+
+        for monitor in monitors {
+            let monitor_descriptor = monitor.descriptor()?;
+            let is_same_bounds = monitor_descriptor.x == descriptor.x
+                && monitor_descriptor.y == descriptor.y
+                && monitor_descriptor.width == descriptor.width
+                && monitor_descriptor.height == descriptor.height;
+
+            if monitor_descriptor.name == descriptor.name
+                && monitor_descriptor.connector == descriptor.connector
+            {
+                candidates.push((is_same_bounds, monitor));
+            } else if monitor_descriptor.name == descriptor.name && is_same_bounds {
+                fallback.get_or_insert(monitor);
+            }
+        }
+
+        candidates.sort_by_key(|(is_same_bounds, _)| !is_same_bounds);
+
+        candidates
+            .into_iter()
+            .map(|(_, monitor)| monitor)
+            .next()
+            .or(fallback)
+            .ok_or_else(|| XCapError::new("Not found monitor"))
+    }
+
+    /// Create a monitor from a native handle, without enumerating the monitors.
+    pub fn from_raw_handle(raw_handle: RawMonitorHandle) -> XCapResult<Monitor> {
+        Ok(Monitor::new(ImplMonitor::from_raw_handle(raw_handle)?))
+    }
+
     pub fn from_point(x: i32, y: i32) -> XCapResult<Monitor> {
         let impl_monitor = ImplMonitor::from_point(x, y)?;
 
@@ -63,10 +198,45 @@ impl Monitor {
     pub fn rotation(&self) -> XCapResult<f32> {
         self.impl_monitor.rotation()
     }
+    /// The color depth of the current display mode, in bits per pixel: `dmBitsPerPel` on
+    /// Windows, the pixel encoding of the display mode on MacOS, the root window depth on
+    /// Linux (X11).
+    pub fn bits_per_pixel(&self) -> XCapResult<u32> {
+        self.impl_monitor.bits_per_pixel()
+    }
+    /// Whether the monitor is in HDR mode: the output color space is HDR10 (`IDXGIOutput6`)
+    /// on Windows, the screen currently shows extended dynamic range content on MacOS.
+    /// Always false on Linux.
+    pub fn is_hdr(&self) -> XCapResult<bool> {
+        self.impl_monitor.is_hdr()
+    }
+    /// The screen rotation as a [`Rotation`], easier to match on than `rotation()`.
+    pub fn orientation(&self) -> XCapResult<Rotation> {
+        Ok(Rotation::from_degrees(self.impl_monitor.rotation()?))
+    }
     /// Output device's pixel scale factor.
     pub fn scale_factor(&self) -> XCapResult<f32> {
         self.impl_monitor.scale_factor()
     }
+    /// How `scale_factor()` was determined, e.g. to tell when Windows fell back to
+    /// `GetDeviceCaps` because the process isn't DPI aware.
+    pub fn scale_factor_source(&self) -> XCapResult<ScaleFactorSource> {
+        self.impl_monitor.scale_factor_source()
+    }
+    /// Force the scale factor of the monitor, e.g. when a remote session reports it
+    /// incorrectly. `scale_factor()` and the captures that convert coordinates with it use
+    /// the override until it's cleared with `None`. The override is keyed by the monitor id.
+    pub fn set_scale_factor_override(&self, scale_factor: Option<f32>) -> XCapResult<()> {
+        if let Some(scale_factor) = scale_factor {
+            if !scale_factor.is_finite() || scale_factor <= 0.0 {
+                return Err(XCapError::new("Scale factor must be a positive number"));
+            }
+        }
+
+        set_scale_factor_override(self.id()?, scale_factor);
+
+        Ok(())
+    }
     /// The screen refresh rate.
     pub fn frequency(&self) -> XCapResult<f32> {
         self.impl_monitor.frequency()
@@ -80,17 +250,612 @@ impl Monitor {
     pub fn is_builtin(&self) -> XCapResult<bool> {
         self.impl_monitor.is_builtin()
     }
+
+    /// All display modes the monitor supports, read with `EnumDisplaySettingsExW` on Windows,
+    /// `CGDisplayCopyAllDisplayModes` on MacOS and the RandR output modes on Linux (X11).
+    pub fn available_modes(&self) -> XCapResult<Vec<DisplayMode>> {
+        self.impl_monitor.available_modes()
+    }
+
+    /// The current gamma ramp of the monitor, read with `GetDeviceGammaRamp` on Windows,
+    /// `CGGetDisplayTransferByTable` on MacOS and the RandR CRTC gamma on Linux (X11).
+    pub fn gamma_ramp(&self) -> XCapResult<GammaRamp> {
+        self.impl_monitor.gamma_ramp()
+    }
+
+    /// Set the gamma ramp of the monitor with `SetDeviceGammaRamp` on Windows,
+    /// `CGSetDisplayTransferByTable` on MacOS and the RandR CRTC gamma on Linux (X11).
+    /// The three channels must have as many entries as the ramp returned by `gamma_ramp()`:
+    /// 256 on Windows, where the driver may also reject ramps too far from the identity.
+    ///
+    /// The change stays until another ramp is set, keep the ramp read with `gamma_ramp()`
+    /// to restore it. On MacOS the system restores the ColorSync settings when the process
+    /// exits, on Windows and Linux the ramp outlives the process. No special permission is
+    /// needed, but on Linux the X server must allow RandR gamma changes.
+    pub fn set_gamma_ramp(&self, ramp: &GammaRamp) -> XCapResult<()> {
+        if ramp.red.is_empty()
+            || ramp.red.len() != ramp.green.len()
+            || ramp.red.len() != ramp.blue.len()
+        {
+            return Err(XCapError::new(
+                "Gamma ramp channels must have the same, non-zero length",
+            ));
+        }
+
+        self.impl_monitor.set_gamma_ramp(ramp)
+    }
+
+    /// Dim the monitor by setting a linear gamma ramp scaled by `level`, between 0.0 (black)
+    /// and 1.0 (full brightness). This changes the gamma ramp, not the backlight, see
+    /// [`Monitor::set_gamma_ramp`] for how long it lasts.
+    pub fn set_brightness(&self, level: f32) -> XCapResult<()> {
+        if !(0.0..=1.0).contains(&level) {
+            return Err(XCapError::new(
+                "Brightness level must be between 0.0 and 1.0",
+            ));
+        }
+
+        let size = self.impl_monitor.gamma_ramp()?.red.len();
+
+        self.impl_monitor
+            .set_gamma_ramp(&GammaRamp::linear(size, level))
+    }
+
+    /// The native handle of the monitor.
+    pub fn raw_handle(&self) -> RawMonitorHandle {
+        self.impl_monitor.raw_handle()
+    }
+
+    /// The connection the monitor is plugged into, see [`MonitorDescriptor::connector`].
+    pub fn connector(&self) -> XCapResult<String> {
+        self.impl_monitor.connector()
+    }
+
+    /// A description of the monitor that can be saved and matched later with
+    /// [`Monitor::find`] or [`Monitor::matches`].
+    pub fn descriptor(&self) -> XCapResult<MonitorDescriptor> {
+        Ok(MonitorDescriptor {
+            name: self.name()?,
+            x: self.x()?,
+            y: self.y()?,
+            width: self.width()?,
+            height: self.height()?,
+            connector: self.connector()?,
+        })
+    }
+
+    /// Whether the monitor has the name and connector of `descriptor`. The position and size
+    /// aren't compared, since they change with the display settings.
+    pub fn matches(&self, descriptor: &MonitorDescriptor) -> bool {
+        let is_same_name = self.name().is_ok_and(|name| name == descriptor.name);
+        let is_same_connector = self
+            .connector()
+            .is_ok_and(|connector| connector == descriptor.connector);
+
+        is_same_name && is_same_connector
+    }
+
+    /// An id of the monitor that stays the same across sessions, unlike [`Monitor::id`].
+    /// It's derived from the EDID manufacturer, product code and serial number, e.g.
+    /// `DEL-4093-ABC123`. If the EDID has no serial number it falls back to the connector
+    /// and position, e.g. `HDMI-1@1920,0`.
+    pub fn persistent_id(&self) -> XCapResult<String> {
+        // 读取 EDID 失败时同样使用接口和位置
+        if let Some(edid_id) = self.impl_monitor.edid_id().ok().flatten() {
+            return Ok(edid_id);
+        }
+
+        Ok(format!("{}@{},{}", self.connector()?, self.x()?, self.y()?))
+    }
+
+    /// Map a pixel of an image from `capture_image()` to screen coordinates, in the same units
+    /// as `x()`/`y()`. The image is captured in the monitor's current (rotated) orientation, so
+    /// only the origin and, on MacOS where the coordinates are points, the pixel scale apply.
+    pub fn image_to_screen(&self, px: u32, py: u32) -> XCapResult<(i32, i32)> {
+        Ok(image_to_screen(
+            self.bounds()?,
+            self.impl_monitor.pixel_scale()?,
+            px,
+            py,
+        ))
+    }
+
+    /// The inverse of [`Monitor::image_to_screen`], `None` when the point isn't on the monitor.
+    pub fn screen_to_image(&self, x: i32, y: i32) -> XCapResult<Option<(u32, u32)>> {
+        Ok(screen_to_image(
+            self.bounds()?,
+            self.impl_monitor.pixel_scale()?,
+            x,
+            y,
+        ))
+    }
+
+    /// The monitor bounds, the same as `x()`, `y()`, `width()` and `height()`.
+    pub fn bounds(&self) -> XCapResult<Rect> {
+        Ok(Rect::new(
+            self.x()?,
+            self.y()?,
+            self.width()?,
+            self.height()?,
+        ))
+    }
+
+    /// The usable area of the screen, excluding the taskbar
+    /// on Windows, the Dock and menu bar on MacOS, and the panels on Linux (X11).
+    pub fn work_area(&self) -> XCapResult<Rect> {
+        self.impl_monitor.work_area()
+    }
 }
 
 impl Monitor {
-    /// Capture image of the monitor
+    /// Capture image of the monitor, as sRGB encoded 8-bit RGBA.
+    /// The attempt is recorded in [`recent_events`](crate::recent_events).
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
+        record_capture(
+            DiagnosticTarget::Monitor(self.id().unwrap_or_default()),
+            "Monitor::capture_image",
+            || {
+                let (image, acceleration) = self.impl_monitor.capture_image_with_acceleration()?;
+                Ok((image, Some(acceleration)))
+            },
+        )
+    }
+
+    /// Block until the monitor's next vertical blanking interval begins. Uses
+    /// `IDXGIOutput::WaitForVBlank` on Windows, a `CVDisplayLink` on MacOS and
+    /// `DRM_IOCTL_WAIT_VBLANK` on `/dev/dri/card0` on Linux (X11).
+    pub fn wait_for_vblank(&self) -> XCapResult<()> {
+        self.impl_monitor.wait_for_vblank()
+    }
+
+    /// Wait for the next vertical blanking interval, then capture image of the monitor, so
+    /// that the capture starts right after a scanout completes. This narrows, but can not
+    /// remove, the window in which a VRR or interlaced display swaps frames mid-capture.
+    pub fn capture_image_on_vblank(&self) -> XCapResult<RgbaImage> {
+        self.impl_monitor.wait_for_vblank()?;
         self.impl_monitor.capture_image()
     }
 
+    /// Capture image of the monitor until two consecutive frames have the same pixels within
+    /// 16 pixels of the edges, or `max_attempts` captures were made, and return the last
+    /// frame. A torn frame from content changing mid-capture usually differs from the next one
+    /// along the edges, while the rest of a video or game frame is expected to change.
+    pub fn capture_image_stable(&self, max_attempts: u32) -> XCapResult<RgbaImage> {
+        let mut previous = self.impl_monitor.capture_image()?;
+
+        for _ in 1..max_attempts {
+            let current = self.impl_monitor.capture_image()?;
+
+            if borders_match(&previous, &current, STABLE_BORDER_BAND) {
+                return Ok(current);
+            }
+
+            previous = current;
+        }
+
+        Ok(previous)
+    }
+
+    /// Capture image of the monitor, returning `XCapError::Cancelled` once `token` is cancelled.
+    /// On Linux (Wayland) a pending screenshot portal request is closed, which also dismisses
+    /// its dialog. On Windows, MacOS and X11 the capture itself doesn't block, the token is
+    /// checked before it starts and before the image is returned.
+    pub fn capture_image_cancellable(&self, token: &CancellationToken) -> XCapResult<RgbaImage> {
+        self.impl_monitor.capture_image_cancellable(token)
+    }
+
+    /// Capture image of the monitor in the channel order the platform produces, skipping the
+    /// BGRA to RGBA conversion of `capture_image()`: BGRA on Windows and MacOS, RGBA on Linux
+    /// where the X11 pixels are converted anyway. Check [`NativeImage::format`].
+    pub fn capture_image_native(&self) -> XCapResult<NativeImage> {
+        self.impl_monitor.capture_image_native()
+    }
+
+    /// Capture image of the monitor with the backend that [`CaptureQuality`] selects on the
+    /// current platform, see the table in its documentation.
+    pub fn capture_image_with_quality(&self, quality: CaptureQuality) -> XCapResult<RgbaImage> {
+        self.impl_monitor.capture_image_with_quality(quality)
+    }
+
+    /// Capture image of the monitor with the first backend of `config.backend_order` that
+    /// succeeds, returning the image and the backend that captured it. The failures of the
+    /// backends before it are reported to the [diagnostic hook](crate::set_diagnostic_hook),
+    /// when all of them fail the last error is returned.
+    pub fn capture_image_with_config(
+        &self,
+        config: &CaptureConfig,
+    ) -> XCapResult<(RgbaImage, Backend)> {
+        let mut last_err = None;
+
+        for &backend in &config.backend_order {
+            // 之后尝试了其他方式的失败只作为诊断信息报告，最后一个失败作为错误返回
+            match self.impl_monitor.capture_image_with_backend(backend) {
+                Ok(image) => {
+                    if let Some(err) = last_err {
+                        self.report_backend_failure(err);
+                    }
+                    return Ok((image, backend));
+                }
+                Err(err) => {
+                    if let Some(err) = last_err.replace(err) {
+                        self.report_backend_failure(err);
+                    }
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| XCapError::new("No capture backend was configured")))
+    }
+
+    fn report_backend_failure(&self, err: XCapError) {
+        report(Diagnostic::new(
+            DiagnosticTarget::Monitor(self.id().unwrap_or_default()),
+            "capture_image_with_config",
+            err,
+        ));
+    }
+
+    /// Capture image of the monitor into `target`, which keeps its allocation, e.g. a frame
+    /// buffer reused in a capture loop. `target` must have the same dimensions as the image
+    /// returned by [`Monitor::capture_image`] (pixels, not `width()` x `height()` on scaled
+    /// monitors), otherwise an error is returned and `target` is left unchanged.
+    pub fn capture_reuse(&self, target: &mut RgbaImage) -> XCapResult<()> {
+        let image = self.capture_image()?;
+
+        if image.dimensions() != target.dimensions() {
+            return Err(XCapError::new(format!(
+                "Target image is {}x{}, but the capture is {}x{}",
+                target.width(),
+                target.height(),
+                image.width(),
+                image.height()
+            )));
+        }
+
+        target.copy_from_slice(&image);
+
+        Ok(())
+    }
+
+    /// Capture image of the monitor, with the time the capture started and whether it used the
+    /// GPU path, see [`FrameTime`]. The time is read before the capture, it's not the time the
+    /// frame was presented.
+    pub fn capture_image_timestamped(&self) -> XCapResult<(RgbaImage, FrameTime)> {
+        let mut frame_time = FrameTime::now();
+        let (image, acceleration) = self.impl_monitor.capture_image_with_acceleration()?;
+        frame_time.acceleration = acceleration;
+
+        Ok((image, frame_time))
+    }
+
+    /// Capture image of the monitor, as a `DynamicImage` holding the RGBA image.
+    pub fn capture_dynamic_image(&self) -> XCapResult<DynamicImage> {
+        Ok(DynamicImage::ImageRgba8(self.capture_image()?))
+    }
+
+    /// Capture image of the monitor and write it to `path`. The format is inferred from the
+    /// extension: .png and .bmp, or .jpg and .webp with the `encoding` feature.
+    /// Missing parent directories are created.
+    pub fn capture_to_file<P: AsRef<Path>>(&self, path: P) -> XCapResult<()> {
+        save_image(&self.capture_image()?, path.as_ref())
+    }
+
+    /// Capture image of the monitor encoded as uncompressed BMP, the alpha channel is dropped.
+    #[cfg(feature = "encoding")]
+    pub fn capture_bmp(&self) -> XCapResult<Vec<u8>> {
+        encode_bmp(&self.capture_image()?)
+    }
+
+    /// Capture image of the monitor and encode it straight into `writer` (a file or a socket),
+    /// without building the encoded bytes in memory. `format` is PNG, JPEG (quality 90) or BMP,
+    /// JPEG and BMP drop the alpha channel.
+    #[cfg(feature = "encoding")]
+    pub fn capture_encode_to<W: Write>(&self, writer: W, format: ImageFormat) -> XCapResult<()> {
+        encode_to(self.capture_image()?, writer, format)
+    }
+
+    /// Capture image of the monitor encoded as uncompressed 8-bit RGBA TIFF. With `embed_dpi`
+    /// the resolution is set to 96 DPI times the scale factor of the monitor.
+    #[cfg(feature = "encoding")]
+    pub fn capture_tiff(&self, embed_dpi: bool) -> XCapResult<Vec<u8>> {
+        let scale_factor = if embed_dpi {
+            Some(self.scale_factor()?)
+        } else {
+            None
+        };
+
+        encode_tiff(&self.capture_image()?, scale_factor)
+    }
+
+    /// Capture image of the monitor and put it on the system clipboard, as `CF_DIBV5` on
+    /// Windows and PNG on MacOS and Linux. On Linux `wl-copy` (Wayland) or `xclip` (X11)
+    /// must be installed, since the clipboard has to be served by a running process.
+    #[cfg(feature = "clipboard")]
+    pub fn capture_to_clipboard(&self) -> XCapResult<()> {
+        let image = self.capture_image()?;
+
+        set_clipboard_image(&image)
+    }
+
+    /// Capture image of the monitor, with a warning when the image is likely black
+    /// because a secure desktop is active or the content is protected.
+    pub fn capture_image_with_warning(&self) -> XCapResult<(RgbaImage, Option<CaptureWarning>)> {
+        let image = self.capture_image()?;
+
+        let warning = if self.impl_monitor.is_secure_desktop()? {
+            Some(CaptureWarning::SecureDesktop)
+        } else if is_black_image(&image) {
+            Some(CaptureWarning::ProtectedContent)
+        } else {
+            None
+        };
+
+        Ok((image, warning))
+    }
+
+    /// Capture image of the monitor and compare it with a previous capture. Returns the new
+    /// image and the bounding box of the changed pixels, in image pixels, or `None` when
+    /// nothing changed. When the image size changed the whole image is the bounding box.
+    pub fn capture_diff(&self, previous: &RgbaImage) -> XCapResult<(RgbaImage, Option<Rect>)> {
+        let image = self.capture_image()?;
+        let bounding_box = diff_bounding_box(previous, &image);
+
+        Ok((image, bounding_box))
+    }
+
+    /// Capture image of the monitor and compare it with a previous capture row by row. Returns
+    /// the new image and the ranges of changed rows, in image pixels, adjacent rows merged,
+    /// which suits line based remote protocols better than a bounding box. When the image
+    /// size changed all rows are returned.
+    pub fn capture_changed_rows(
+        &self,
+        previous: &RgbaImage,
+    ) -> XCapResult<(RgbaImage, Vec<Range<u32>>)> {
+        let image = self.capture_image()?;
+        let changed_rows = changed_row_ranges(previous, &image);
+
+        Ok((image, changed_rows))
+    }
+
+    /// Capture image of the monitor, retrying while the image is a single color (e.g. black
+    /// right after login or wake) until `timeout` elapses. The last captured image is
+    /// returned on timeout, since the screen may really show a single color.
+    pub fn capture_image_when_ready(&self, timeout: Duration) -> XCapResult<RgbaImage> {
+        let start = Instant::now();
+
+        loop {
+            let image = self.capture_image()?;
+
+            if !is_uniform_image(&image) || start.elapsed() >= timeout {
+                return Ok(image);
+            }
+
+            thread::sleep(Duration::from_millis(50).min(timeout.saturating_sub(start.elapsed())));
+        }
+    }
+
+    /// Whether the monitor can be captured without asking the user. On Linux (Wayland) it's
+    /// `true` when a ScreenCast portal restore token is saved for the monitor, which requires
+    /// the `pipewire` feature, and on MacOS when the app has the screen recording permission.
+    /// Always `true` on Windows and X11.
+    pub fn has_capture_permission(&self) -> bool {
+        self.impl_monitor.has_capture_permission()
+    }
+
+    /// Ask the user for permission to capture the monitor. On Linux (Wayland) this shows the
+    /// compositor's ScreenCast dialog and saves the restore token, see
+    /// `set_restore_token_path`, so later captures of the monitor reuse the permission instead of
+    /// prompting again. If the user picks another monitor in the dialog the token is saved for
+    /// that monitor and an error is returned. On MacOS it shows the screen recording prompt the
+    /// first time and returns [`XCapError::PermissionDenied`] until the permission is granted.
+    pub fn request_capture_permission(&self) -> XCapResult<()> {
+        self.impl_monitor.request_capture_permission()
+    }
+
+    /// Delete the restore token saved for the monitor on Linux (Wayland), the permission
+    /// granted to the compositor itself can only be revoked in the desktop settings. Does
+    /// nothing on Windows and MacOS.
+    pub fn forget_capture_permission(&self) -> XCapResult<()> {
+        self.impl_monitor.forget_capture_permission()
+    }
+
+    /// Capture image of the monitor while the secure desktop (lock screen, login screen or UAC
+    /// prompt) is shown, for privileged monitoring agents. Only supported on Windows, where the
+    /// capture runs on a thread switched to the input desktop (`OpenInputDesktop` and
+    /// `SetThreadDesktop`). That requires running as SYSTEM in the user's session (e.g. started
+    /// by a service with the session's token), a session 0 service can't see the user's screen.
+    /// Returns [`XCapError::PermissionDenied`] without the privileges and
+    /// [`XCapError::Unsupported`] on MacOS and Linux.
+    pub fn capture_secure_desktop(&self) -> XCapResult<RgbaImage> {
+        self.impl_monitor.capture_secure_desktop()
+    }
+
+    /// Capture image of the monitor, with the sRGB transfer function removed
+    /// so the color channels are linear. Alpha is unchanged.
+    pub fn capture_image_linear_f32(&self) -> XCapResult<Rgba32FImage> {
+        to_linear_f32(&self.capture_image()?)
+    }
+
+    /// Capture image of an area of the monitor.
+    /// `area` is relative to the monitor's top-left corner, in the same
+    /// units as `width()`/`height()`, also for monitors with a negative `x()`/`y()`.
+    /// The area is clamped to the monitor bounds.
+    pub fn capture_area(&self, area: Rect) -> XCapResult<RgbaImage> {
+        self.impl_monitor.capture_area(area)
+    }
+
+    /// Capture the monitor once and keep the frame, so several areas can be sliced from it with
+    /// [`CaptureSession::region`]. The areas are from the same instant and only one capture
+    /// is made.
+    pub fn capture_session(&self) -> XCapResult<CaptureSession> {
+        let image = self.capture_image()?;
+
+        Ok(CaptureSession::new(image, self.width()?, self.height()?))
+    }
+
+    /// Capture image of an area of the monitor given as fractions of the monitor size,
+    /// e.g. `(0.5, 0.0, 0.5, 0.5)` is the top-right quadrant, so the same area can be used
+    /// on monitors with different resolutions. All values are between 0.0 and 1.0.
+    pub fn capture_area_normalized(&self, x: f32, y: f32, w: f32, h: f32) -> XCapResult<RgbaImage> {
+        if [x, y, w, h]
+            .iter()
+            .any(|value| !(0.0..=1.0).contains(value))
+        {
+            return Err(XCapError::new(
+                "Normalized capture area must be between 0.0 and 1.0",
+            ));
+        }
+
+        let width = self.width()? as f32;
+        let height = self.height()? as f32;
+
+        // 先换算左上角和右下角再取差值，避免相邻的区域之间出现缝隙
+        let left = (x * width).round();
+        let top = (y * height).round();
+        let right = ((x + w) * width).round().min(width);
+        let bottom = ((y + h) * height).round().min(height);
+
+        self.capture_area(Rect::new(
+            left as i32,
+            top as i32,
+            (right - left).max(0.0) as u32,
+            (bottom - top).max(0.0) as u32,
+        ))
+    }
+
+    /// Capture image of an area of the monitor, with the alpha set to 0 where `mask` is 0,
+    /// for non-rectangular selections. The area must be inside the monitor and the mask must
+    /// be `area.width` x `area.height`, it is scaled (nearest neighbor) to the captured image
+    /// when the monitor has a scale factor.
+    pub fn capture_masked(&self, area: Rect, mask: &GrayImage) -> XCapResult<RgbaImage> {
+        if mask.dimensions() != (area.width, area.height) {
+            return Err(XCapError::new("Mask size doesn't match the capture area"));
+        }
+
+        let monitor_area = Rect::new(0, 0, self.width()?, self.height()?);
+        if monitor_area.intersection(&area) != Some(area) {
+            return Err(XCapError::new("Capture area is outside the monitor"));
+        }
+
+        let mut image = self.capture_area(area)?;
+
+        let mask = if mask.dimensions() == image.dimensions() {
+            mask.clone()
+        } else {
+            resize(mask, image.width(), image.height(), FilterType::Nearest)
+        };
+
+        for (pixel, mask_pixel) in image.pixels_mut().zip(mask.pixels()) {
+            if mask_pixel[0] == 0 {
+                pixel[3] = 0;
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Capture image of the monitor scaled to `width` x `height` pixels.
+    /// On Windows the scaling is done by GDI (`StretchBlt` in `HALFTONE` mode) while copying
+    /// from the screen, so no full resolution image is created. On MacOS and Linux the full
+    /// resolution image is captured and resized on the CPU with a triangle (bilinear) filter.
+    pub fn capture_scaled(&self, width: u32, height: u32) -> XCapResult<RgbaImage> {
+        if width == 0 || height == 0 {
+            return Err(XCapError::new("Capture size must not be zero"));
+        }
+
+        self.impl_monitor.capture_scaled(width, height)
+    }
+
+    /// Capture image of the `src` area of the monitor scaled to `dst_width` x `dst_height`
+    /// pixels, e.g. for magnifiers. `src` is relative to the monitor's top-left corner like
+    /// in [`Monitor::capture_area`] and is clamped to the monitor bounds. On Windows only
+    /// `src` is copied and scaled by GDI (`StretchBlt` in `HALFTONE` mode) in one step. On
+    /// MacOS and Linux `src` is captured at native resolution and resized on the CPU with a
+    /// triangle (bilinear) filter.
+    pub fn capture_region_scaled(
+        &self,
+        src: Rect,
+        dst_width: u32,
+        dst_height: u32,
+    ) -> XCapResult<RgbaImage> {
+        if dst_width == 0 || dst_height == 0 {
+            return Err(XCapError::new("Capture size must not be zero"));
+        }
+
+        self.impl_monitor
+            .capture_area_scaled(src, dst_width, dst_height)
+    }
+
+    /// A stream of images of the monitor for async code, e.g. `while let Some(frame) =
+    /// stream.next().await`. The frames are captured with [`Monitor::capture_image`] on a
+    /// background thread, which captures the next frame only after the previous one was
+    /// taken, so a slow consumer slows the capture down instead of buffering frames. Dropping
+    /// the stream stops the thread. Requires the `futures` feature.
+    #[cfg(feature = "futures")]
+    pub fn frame_stream(
+        &self,
+    ) -> impl futures::Stream<Item = XCapResult<RgbaImage>> + Send + Unpin {
+        monitor_frame_stream(self.raw_handle())
+    }
+
+    /// Capture `count` frames paced at `fps` frames per second, using the video recorder.
+    /// When the recorder delivers frames faster than `fps` the extra frames are dropped,
+    /// when it's slower the previous frame is repeated. Each image is returned with the
+    /// time it was received, relative to the start of the capture.
+    pub fn capture_frames(&self, count: usize, fps: u32) -> XCapResult<Vec<(RgbaImage, Duration)>> {
+        if fps == 0 {
+            return Err(XCapError::new("Capture fps must not be zero"));
+        }
+
+        let (video_recorder, receiver) = self.video_recorder()?;
+        let interval = Duration::from_secs_f64(1.0 / fps as f64);
+
+        video_recorder.start()?;
+
+        let _scope_guard_video_recorder = guard(video_recorder, |video_recorder| {
+            if let Err(err) = video_recorder.stop() {
+                log::error!("VideoRecorder stop failed: {}", err);
+            }
+        });
+
+        let start = Instant::now();
+        let mut last_frame: Option<(Frame, Duration)> = None;
+        let mut frames = Vec::with_capacity(count);
+
+        for i in 0..count {
+            let deadline = start + interval.mul_f64(i as f64);
+            thread::sleep(deadline.saturating_duration_since(Instant::now()));
+
+            // 只保留截止时间前收到的最新一帧
+            while let Ok(frame) = receiver.try_recv() {
+                last_frame = Some((frame, start.elapsed()));
+            }
+
+            // 第一帧还没有到达时，等待录制器产生第一帧
+            if last_frame.is_none() {
+                let frame = receiver
+                    .recv_timeout(Duration::from_secs(5))
+                    .map_err(|err| XCapError::with_source("Receive frame failed", err))?;
+                last_frame = Some((frame, start.elapsed()));
+            }
+
+            if let Some((frame, timestamp)) = &last_frame {
+                let image = RgbaImage::from_raw(frame.width, frame.height, frame.raw.clone())
+                    .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))?;
+
+                frames.push((image, *timestamp));
+            }
+        }
+
+        Ok(frames)
+    }
+
     pub fn video_recorder(&self) -> XCapResult<(VideoRecorder, Receiver<Frame>)> {
         let (impl_video_recorder, sx) = self.impl_monitor.video_recorder()?;
 
-        Ok((VideoRecorder::new(impl_video_recorder), sx))
+        Ok((
+            VideoRecorder::new(impl_video_recorder, self.raw_handle()),
+            sx,
+        ))
     }
 }
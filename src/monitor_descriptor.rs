@@ -0,0 +1,17 @@
+/// A description of a monitor that can be saved and matched against the monitors later,
+/// see [`Monitor::descriptor`](crate::Monitor::descriptor) and
+/// [`Monitor::find`](crate::Monitor::find). Unlike `Monitor::id()`, which wraps a platform
+/// handle that can change after reconnecting the monitor or rebooting, these values are stable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonitorDescriptor {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// The connection the monitor is plugged into: the device interface path of the monitor on
+    /// Windows, the vendor, model and serial number on MacOS and the RandR output name
+    /// (e.g. `HDMI-1`) on Linux.
+    pub connector: String,
+}
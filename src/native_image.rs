@@ -0,0 +1,56 @@
+use image::RgbaImage;
+
+use crate::error::{XCapError, XCapResult};
+
+/// The channel order of the pixels in a [`NativeImage`], 8 bits per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PixelFormat {
+    Rgba8,
+    Bgra8,
+}
+
+/// A captured image in the channel order the platform produced it, so it can be uploaded
+/// to the GPU (e.g. as `BGRA8Unorm`) without swapping the red and blue channels of every
+/// pixel first. Rows are tightly packed, `data` is `width * height * 4` bytes.
+#[derive(Debug, Clone)]
+pub struct NativeImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub data: Vec<u8>,
+}
+
+impl NativeImage {
+    pub fn new(width: u32, height: u32, format: PixelFormat, data: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            data,
+        }
+    }
+
+    /// Convert to an RGBA image, swapping the channels when the format is BGRA.
+    pub fn into_rgba_image(self) -> XCapResult<RgbaImage> {
+        let mut data = self.data;
+
+        if self.format == PixelFormat::Bgra8 {
+            for bgra in data.chunks_exact_mut(4) {
+                bgra.swap(0, 2);
+            }
+        }
+
+        RgbaImage::from_raw(self.width, self.height, data)
+            .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))
+    }
+}
+
+#[test]
+fn into_rgba_image_swaps_bgra() {
+    let native_image = NativeImage::new(1, 1, PixelFormat::Bgra8, vec![1, 2, 3, 4]);
+
+    assert_eq!(
+        native_image.into_rgba_image().unwrap().as_raw(),
+        &vec![3, 2, 1, 4]
+    );
+}
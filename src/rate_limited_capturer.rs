@@ -0,0 +1,95 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use image::RgbaImage;
+
+use crate::{error::XCapResult, Monitor};
+
+/// What [`RateLimitedCapturer::capture_image`] does when it's called again before the
+/// minimum interval elapsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RateLimitMode {
+    /// Sleep until the interval elapsed, then capture.
+    #[default]
+    Block,
+    /// Return the last captured image without capturing. Blocks when nothing was captured yet.
+    ReturnCached,
+}
+
+/// Captures a monitor at most once every `min_interval`, however often
+/// [`RateLimitedCapturer::capture_image`] is called, to cap the CPU usage of polling callers.
+#[derive(Debug)]
+pub struct RateLimitedCapturer {
+    monitor: Monitor,
+    min_interval: Duration,
+    mode: RateLimitMode,
+    // 失败的截图也计入频率限制
+    last_capture_at: Option<Instant>,
+    last_image: Option<RgbaImage>,
+}
+
+// 距离允许下一次截图还需要等待的时间
+fn remaining_interval(
+    last_capture_at: Option<Instant>,
+    now: Instant,
+    min_interval: Duration,
+) -> Duration {
+    match last_capture_at {
+        Some(last_capture_at) => (last_capture_at + min_interval).saturating_duration_since(now),
+        None => Duration::ZERO,
+    }
+}
+
+impl RateLimitedCapturer {
+    pub fn new(monitor: Monitor, min_interval: Duration, mode: RateLimitMode) -> Self {
+        RateLimitedCapturer {
+            monitor,
+            min_interval,
+            mode,
+            last_capture_at: None,
+            last_image: None,
+        }
+    }
+
+    /// The captured monitor
+    pub fn monitor(&self) -> &Monitor {
+        &self.monitor
+    }
+
+    /// Capture image of the monitor like [`Monitor::capture_image`], waiting or returning
+    /// the last image as configured by [`RateLimitMode`] when called too early.
+    pub fn capture_image(&mut self) -> XCapResult<RgbaImage> {
+        let remaining = remaining_interval(self.last_capture_at, Instant::now(), self.min_interval);
+
+        if !remaining.is_zero() {
+            match (self.mode, &self.last_image) {
+                (RateLimitMode::ReturnCached, Some(last_image)) => return Ok(last_image.clone()),
+                _ => thread::sleep(remaining),
+            }
+        }
+
+        self.last_capture_at = Some(Instant::now());
+        let image = self.monitor.capture_image()?;
+        self.last_image = Some(image.clone());
+
+        Ok(image)
+    }
+}
+
+#[test]
+fn remaining_interval_counts_down_from_last_capture() {
+    let now = Instant::now();
+    let min_interval = Duration::from_millis(500);
+
+    assert_eq!(remaining_interval(None, now, min_interval), Duration::ZERO);
+    assert_eq!(
+        remaining_interval(Some(now), now + Duration::from_millis(200), min_interval),
+        Duration::from_millis(300)
+    );
+    assert_eq!(
+        remaining_interval(Some(now), now + Duration::from_secs(1), min_interval),
+        Duration::ZERO
+    );
+}
@@ -0,0 +1,23 @@
+/// The native handle of a monitor. Only the variant of the current platform is accepted by
+/// [`Monitor::from_raw_handle`](crate::Monitor::from_raw_handle).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawMonitorHandle {
+    /// `HMONITOR` on Windows.
+    Win32 { h_monitor: isize },
+    /// `CGDirectDisplayID` on MacOS.
+    CoreGraphics { display_id: u32 },
+    /// RandR output on Linux.
+    RandR { output: u32 },
+}
+
+/// The native handle of a window. Only the variant of the current platform is accepted by
+/// [`Window::from_raw_handle`](crate::Window::from_raw_handle).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawWindowHandle {
+    /// `HWND` on Windows.
+    Win32 { hwnd: isize },
+    /// `CGWindowID` on MacOS.
+    CoreGraphics { window_id: u32 },
+    /// X11 window on Linux.
+    Xcb { window: u32 },
+}
@@ -0,0 +1,173 @@
+/// A rectangle, in the same units as `Monitor::x()`/`Monitor::width()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The x coordinate of the right edge, exclusive. Saturates at `i32::MAX`.
+    pub fn right(&self) -> i32 {
+        self.x.saturating_add_unsigned(self.width)
+    }
+
+    /// The y coordinate of the bottom edge, exclusive. Saturates at `i32::MAX`.
+    pub fn bottom(&self) -> i32 {
+        self.y.saturating_add_unsigned(self.height)
+    }
+
+    /// Whether the point is inside the rectangle. The right and bottom edges are exclusive.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// The overlapping part of the two rectangles, `None` when they don't overlap.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        Some(Rect::new(
+            left,
+            top,
+            (right as i64 - left as i64) as u32,
+            (bottom as i64 - top as i64) as u32,
+        ))
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &Rect) -> Rect {
+        let left = self.x.min(other.x);
+        let top = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        // 两个坐标相差可能超过 i32 的范围
+        Rect::new(
+            left,
+            top,
+            (right as i64 - left as i64) as u32,
+            (bottom as i64 - top as i64) as u32,
+        )
+    }
+
+    /// Clamp `area`, relative to the top-left corner of this rectangle, to this rectangle and
+    /// offset it to the coordinates of this rectangle, e.g. a monitor relative area to global
+    /// coordinates. `None` when `area` is outside.
+    pub(crate) fn clamp_relative(&self, area: &Rect) -> Option<Rect> {
+        let area = Rect::new(0, 0, self.width, self.height).intersection(area)?;
+
+        Some(Rect::new(
+            self.x.checked_add(area.x)?,
+            self.y.checked_add(area.y)?,
+            area.width,
+            area.height,
+        ))
+    }
+
+    pub fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+}
+
+#[test]
+fn rect_intersection_and_contains() {
+    let a = Rect::new(0, 0, 100, 50);
+    let b = Rect::new(80, -10, 40, 30);
+
+    assert_eq!(a.intersection(&b), Some(Rect::new(80, 0, 20, 20)));
+    assert_eq!(a.intersection(&Rect::new(100, 0, 10, 10)), None);
+    assert!(a.contains(99, 49));
+    assert!(!a.contains(100, 0));
+    assert_eq!(a.area(), 5000);
+}
+
+#[test]
+fn rect_with_negative_origin() {
+    // 主显示器左上方的显示器
+    let primary = Rect::new(0, 0, 1920, 1080);
+    let secondary = Rect::new(-1280, -200, 1280, 1024);
+
+    assert_eq!(
+        primary.union(&secondary),
+        Rect::new(-1280, -200, 3200, 1280)
+    );
+    assert_eq!(
+        secondary.intersection(&Rect::new(-100, -300, 300, 200)),
+        Some(Rect::new(-100, -200, 100, 100))
+    );
+    assert!(secondary.contains(-1280, -200));
+    assert!(!secondary.contains(0, 0));
+}
+
+#[test]
+fn rect_edges_saturate() {
+    let a = Rect::new(i32::MAX - 10, i32::MAX - 10, u32::MAX, 20);
+
+    assert_eq!(a.right(), i32::MAX);
+    assert_eq!(a.bottom(), i32::MAX);
+    assert!(a.contains(i32::MAX - 1, i32::MAX - 1));
+    assert_eq!(
+        a.intersection(&Rect::new(0, 0, i32::MAX as u32, i32::MAX as u32)),
+        Some(Rect::new(i32::MAX - 10, i32::MAX - 10, 10, 10))
+    );
+    assert_eq!(
+        Rect::new(i32::MIN, 0, 1, 1).union(&Rect::new(i32::MAX - 1, 0, 1, 1)),
+        Rect::new(i32::MIN, 0, u32::MAX, 1)
+    );
+}
+
+#[test]
+fn rect_huge_area_clamps_to_monitor() {
+    let monitor = Rect::new(0, 0, 1920, 1080);
+
+    assert_eq!(
+        monitor.intersection(&Rect::new(100, 100, u32::MAX, u32::MAX)),
+        Some(Rect::new(100, 100, 1820, 980))
+    );
+    assert_eq!(
+        monitor.intersection(&Rect::new(i32::MAX, 0, u32::MAX, 10)),
+        None
+    );
+}
+
+#[test]
+fn clamp_relative_on_negative_origin_monitor() {
+    // 主显示器左上方的显示器
+    let monitor = Rect::new(-1280, -200, 1280, 1024);
+
+    assert_eq!(
+        monitor.clamp_relative(&Rect::new(0, 0, 1280, 1024)),
+        Some(monitor)
+    );
+    assert_eq!(
+        monitor.clamp_relative(&Rect::new(100, 50, 200, 100)),
+        Some(Rect::new(-1180, -150, 200, 100))
+    );
+    assert_eq!(
+        monitor.clamp_relative(&Rect::new(-10, -10, 20, 20)),
+        Some(Rect::new(-1280, -200, 10, 10))
+    );
+    assert_eq!(
+        monitor.clamp_relative(&Rect::new(1200, 1000, 200, 200)),
+        Some(Rect::new(-80, 800, 80, 24))
+    );
+    assert_eq!(monitor.clamp_relative(&Rect::new(1280, 0, 10, 10)), None);
+    assert_eq!(monitor.clamp_relative(&Rect::new(-20, 0, 10, 10)), None);
+}
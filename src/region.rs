@@ -0,0 +1,97 @@
+use image::{
+    imageops::{overlay, resize, FilterType},
+    RgbaImage,
+};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    platform::impl_monitor::ImplMonitor,
+    Monitor, Rect,
+};
+
+/// The pixel density of an image captured across several monitors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PixelDensity {
+    /// The density of the monitor with the highest density, e.g. 2 pixels per unit
+    /// when a 200% scaled monitor is part of the region. No detail is lost.
+    Physical,
+    /// One pixel per unit of the monitor coordinates.
+    Logical,
+}
+
+/// The bounds of the virtual desktop, the smallest rectangle containing all monitors, in the
+/// same units as `Monitor::x()`/`Monitor::width()`. Use it to clamp the region passed to
+/// [`capture_region`]. On Linux (X11) it's the root window size.
+pub fn virtual_desktop_bounds() -> XCapResult<Rect> {
+    ImplMonitor::virtual_desktop_bounds()
+}
+
+/// Capture an area of the screen that may span several monitors.
+/// `region` is in the same units as `Monitor::x()`/`Monitor::width()`.
+/// Each monitor's part is resampled to the chosen `density` with a triangle (bilinear) filter,
+/// so monitors with different scale factors line up without a seam. Parts of the area that
+/// aren't on any monitor are transparent.
+pub fn capture_region(region: Rect, density: PixelDensity) -> XCapResult<RgbaImage> {
+    let mut parts = Vec::new();
+
+    for monitor in Monitor::all()? {
+        let bounds = monitor.bounds()?;
+
+        let Some(part) = bounds.intersection(&region) else {
+            continue;
+        };
+
+        let image = monitor.capture_area(Rect::new(
+            part.x - bounds.x,
+            part.y - bounds.y,
+            part.width,
+            part.height,
+        ))?;
+
+        parts.push((
+            part.x - region.x,
+            part.y - region.y,
+            part.width,
+            part.height,
+            image,
+        ));
+    }
+
+    if parts.is_empty() {
+        return Err(XCapError::new("Capture region is outside all monitors"));
+    }
+
+    // 每个显示器截图的像素与坐标单位的比例
+    let scale = match density {
+        PixelDensity::Physical => parts
+            .iter()
+            .map(|(_, _, part_width, _, image)| image.width() as f32 / *part_width as f32)
+            .fold(1.0, f32::max),
+        PixelDensity::Logical => 1.0,
+    };
+
+    let mut region_image = RgbaImage::new(
+        (region.width as f32 * scale).round() as u32,
+        (region.height as f32 * scale).round() as u32,
+    );
+
+    for (part_x, part_y, part_width, part_height, image) in parts {
+        let target_width = (part_width as f32 * scale).round() as u32;
+        let target_height = (part_height as f32 * scale).round() as u32;
+
+        let image = if image.dimensions() == (target_width, target_height) {
+            image
+        } else {
+            resize(&image, target_width, target_height, FilterType::Triangle)
+        };
+
+        overlay(
+            &mut region_image,
+            &image,
+            (part_x as f32 * scale).round() as i64,
+            (part_y as f32 * scale).round() as i64,
+        );
+    }
+
+    Ok(region_image)
+}
@@ -0,0 +1,46 @@
+/// The rotation of a monitor, clockwise, see [`Monitor::orientation`](crate::Monitor::orientation).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    #[default]
+    Normal,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl Rotation {
+    /// The rotation closest to `degrees`, any multiple of 360 is `Normal`.
+    pub fn from_degrees(degrees: f32) -> Rotation {
+        // macOS 的 CGDisplayRotation 返回 double，不一定是整数
+        match ((degrees / 90.0).round() as i64).rem_euclid(4) {
+            1 => Rotation::Rotate90,
+            2 => Rotation::Rotate180,
+            3 => Rotation::Rotate270,
+            _ => Rotation::Normal,
+        }
+    }
+
+    /// The rotation in clockwise degrees, 0, 90, 180 or 270.
+    pub fn degrees(&self) -> f32 {
+        match self {
+            Rotation::Normal => 0.0,
+            Rotation::Rotate90 => 90.0,
+            Rotation::Rotate180 => 180.0,
+            Rotation::Rotate270 => 270.0,
+        }
+    }
+
+    /// Whether the width and height of the monitor are swapped.
+    pub fn is_portrait(&self) -> bool {
+        matches!(self, Rotation::Rotate90 | Rotation::Rotate270)
+    }
+}
+
+#[test]
+fn rotation_from_degrees() {
+    assert_eq!(Rotation::from_degrees(0.0), Rotation::Normal);
+    assert_eq!(Rotation::from_degrees(89.6), Rotation::Rotate90);
+    assert_eq!(Rotation::from_degrees(-90.0), Rotation::Rotate270);
+    assert_eq!(Rotation::from_degrees(540.0), Rotation::Rotate180);
+    assert_eq!(Rotation::Rotate270.degrees(), 270.0);
+}
@@ -0,0 +1,55 @@
+use std::{fs, path::Path};
+
+use image::{ImageFormat, RgbaImage};
+
+#[cfg(feature = "encoding")]
+use crate::encoding::{encode_jpeg, encode_webp};
+use crate::error::{XCapError, XCapResult};
+
+#[cfg(feature = "encoding")]
+const JPEG_QUALITY: u8 = 90;
+#[cfg(feature = "encoding")]
+const WEBP_QUALITY: f32 = 90.0;
+
+/// Save the image to `path`, the format is inferred from the extension
+/// (.png, .jpg/.jpeg, .bmp or .webp). Missing parent directories are created.
+pub(crate) fn save_image(image: &RgbaImage, path: &Path) -> XCapResult<()> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase())
+        .ok_or_else(|| XCapError::new(format!("{} has no file extension", path.display())))?;
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(|err| {
+            XCapError::with_source(format!("Create directory {} failed", parent.display()), err)
+        })?;
+    }
+
+    match extension.as_str() {
+        "png" => image.save_with_format(path, ImageFormat::Png)?,
+        "bmp" => image.save_with_format(path, ImageFormat::Bmp)?,
+        #[cfg(feature = "encoding")]
+        "jpg" | "jpeg" => fs::write(path, encode_jpeg(image, JPEG_QUALITY)?)?,
+        #[cfg(feature = "encoding")]
+        "webp" => fs::write(path, encode_webp(image, WEBP_QUALITY)?)?,
+        #[cfg(not(feature = "encoding"))]
+        "jpg" | "jpeg" | "webp" => {
+            return Err(XCapError::Unsupported(format!(
+                "Saving .{} files requires the `encoding` feature",
+                extension
+            )))
+        }
+        _ => {
+            return Err(XCapError::Unsupported(format!(
+                "Unknown image format .{}",
+                extension
+            )))
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+};
+
+/// How the scale factor of a monitor was determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScaleFactorSource {
+    /// Set with `Monitor::set_scale_factor_override`.
+    Override,
+    /// Read from the per-monitor DPI: `GetDpiForMonitor` on Windows, the display mode on
+    /// MacOS and `Xft.dpi` on Linux.
+    Detected,
+    /// The per-monitor DPI wasn't available: `GetDeviceCaps` on Windows (e.g. the process
+    /// isn't DPI aware), or 1.0 on Linux when `Xft.dpi` isn't set.
+    Fallback,
+}
+
+// 以显示器 id 为键，id 在显示器配置不变时是稳定的
+static SCALE_FACTOR_OVERRIDES: LazyLock<Mutex<HashMap<u32, f32>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub(crate) fn get_scale_factor_override(monitor_id: u32) -> Option<f32> {
+    SCALE_FACTOR_OVERRIDES
+        .lock()
+        .ok()
+        .and_then(|overrides| overrides.get(&monitor_id).copied())
+}
+
+pub(crate) fn set_scale_factor_override(monitor_id: u32, scale_factor: Option<f32>) {
+    match SCALE_FACTOR_OVERRIDES.lock() {
+        Ok(mut overrides) => match scale_factor {
+            Some(scale_factor) => {
+                overrides.insert(monitor_id, scale_factor);
+            }
+            None => {
+                overrides.remove(&monitor_id);
+            }
+        },
+        Err(err) => log::error!("Set scale factor override failed: {}", err),
+    }
+}
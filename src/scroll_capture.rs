@@ -0,0 +1,116 @@
+use std::{thread, time::Duration};
+
+use image::{imageops::crop_imm, GenericImage, RgbaImage};
+
+use crate::{
+    diff::row_hashes,
+    error::{XCapError, XCapResult},
+};
+
+// 滚动后等待应用重新绘制的时间
+const SCROLL_REDRAW_DELAY: Duration = Duration::from_millis(150);
+// 无限滚动的页面不会到达底部，最多截取的页数
+const MAX_SCROLL_PAGES: usize = 50;
+// 两页重叠的行数少于这个值时不认为是重叠，避免空白行误匹配
+const MIN_OVERLAP_ROWS: u32 = 8;
+
+/// 窗口中可以滚动的区域，由各平台的辅助功能 API 实现
+pub(crate) trait ScrollContainer {
+    /// 截取可滚动区域当前显示的内容
+    fn capture_viewport(&mut self) -> XCapResult<RgbaImage>;
+    fn scroll_to_top(&mut self) -> XCapResult<()>;
+    /// 向下滚动大约一页，已经在底部时返回 false
+    fn scroll_down(&mut self) -> XCapResult<bool>;
+    /// 恢复截图之前的滚动位置
+    fn restore(&mut self) -> XCapResult<()>;
+}
+
+/// 下一页相对上一页向上移动的行数，找不到重叠时返回 None
+fn scroll_offset(previous: &[u64], next: &[u64]) -> Option<u32> {
+    let height = previous.len().min(next.len());
+
+    (1..=height.saturating_sub(MIN_OVERLAP_ROWS as usize))
+        .find(|&offset| previous[offset..height] == next[..height - offset])
+        .map(|offset| offset as u32)
+}
+
+/// 把依次向下滚动截取的页面拼接成一张图片，每页只追加上一页没有的内容
+pub(crate) fn stitch_pages(pages: &[RgbaImage]) -> XCapResult<RgbaImage> {
+    let first_page = pages
+        .first()
+        .ok_or_else(|| XCapError::new("No page was captured"))?;
+    let width = first_page.width();
+
+    let mut rows = Vec::with_capacity(pages.len());
+    rows.push((0, first_page.height()));
+    let mut previous_hashes = row_hashes(first_page);
+
+    for page in &pages[1..] {
+        if page.width() != width {
+            return Err(XCapError::new(
+                "The scroll area was resized while capturing",
+            ));
+        }
+
+        let hashes = row_hashes(page);
+        let new_rows = match scroll_offset(&previous_hashes, &hashes) {
+            Some(offset) => offset.min(page.height()),
+            None => page.height(),
+        };
+        rows.push((page.height() - new_rows, new_rows));
+        previous_hashes = hashes;
+    }
+
+    let height = rows.iter().map(|(_, new_rows)| new_rows).sum();
+    let mut image = RgbaImage::new(width, height);
+    let mut y = 0;
+    for (page, (top, new_rows)) in pages.iter().zip(rows) {
+        image.copy_from(&crop_imm(page, 0, top, width, new_rows).to_image(), 0, y)?;
+        y += new_rows;
+    }
+
+    Ok(image)
+}
+
+fn capture_pages(container: &mut dyn ScrollContainer) -> XCapResult<Vec<RgbaImage>> {
+    container.scroll_to_top()?;
+    thread::sleep(SCROLL_REDRAW_DELAY);
+
+    let mut pages = vec![container.capture_viewport()?];
+
+    while pages.len() < MAX_SCROLL_PAGES && container.scroll_down()? {
+        thread::sleep(SCROLL_REDRAW_DELAY);
+
+        let page = container.capture_viewport()?;
+        // 有些应用到达底部后仍然报告可以滚动
+        if pages.last() == Some(&page) {
+            break;
+        }
+        pages.push(page);
+    }
+
+    Ok(pages)
+}
+
+/// 滚动到顶部，逐页向下滚动截图并拼接，最后恢复原来的滚动位置
+pub(crate) fn capture_scrolled(container: &mut dyn ScrollContainer) -> XCapResult<RgbaImage> {
+    let pages = capture_pages(container);
+
+    if let Err(err) = container.restore() {
+        log::error!("Restore the scroll position failed: {}", err);
+    }
+
+    stitch_pages(&pages?)
+}
+
+#[test]
+fn stitch_pages_appends_only_new_rows() {
+    // 内容每行颜色不同，视口高 20 行，每次滚动 12 行
+    let content = RgbaImage::from_fn(4, 44, |_, y| image::Rgba([y as u8, 0, 0, 255]));
+    let pages: Vec<RgbaImage> = [0, 12, 24]
+        .iter()
+        .map(|&top| crop_imm(&content, 0, top, 4, 20).to_image())
+        .collect();
+
+    assert_eq!(stitch_pages(&pages).unwrap(), content);
+}
@@ -0,0 +1,18 @@
+use crate::{error::XCapResult, platform::impl_window::ImplWindow};
+
+/// Release the capture resources the crate keeps between calls, so a long-running process
+/// can reclaim them while it's idle. Capturing again after this is fine, the resources are
+/// recreated on demand.
+///
+/// Released: the last frames kept on Windows so minimized windows can still be captured, see
+/// [`set_minimized_frame_cache_capacity`](crate::set_minimized_frame_cache_capacity) (a
+/// minimized window captured after this returns an error until it's restored and captured
+/// again).
+///
+/// Not cached, nothing to release: `Shcore.dll` and GDI device contexts on Windows and the
+/// CoreGraphics resources on MacOS are created per call and freed when it returns, the
+/// DXGI/D3D11 objects are owned by the `VideoRecorder` and freed when it's dropped. The X11 connection on Linux is opened once and kept for the lifetime
+/// of the process, it's shared by every call and isn't released.
+pub fn shutdown() -> XCapResult<()> {
+    ImplWindow::clear_cache()
+}
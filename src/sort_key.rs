@@ -0,0 +1,14 @@
+/// The order of the windows returned by [`Window::all_sorted`](crate::Window::all_sorted).
+/// Windows with the same key stay in z order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    /// By [`Window::z`](crate::Window::z), topmost window first, the same as `Window::all()`.
+    #[default]
+    ZOrder,
+    /// By [`Window::title`](crate::Window::title), case-insensitive.
+    Title,
+    /// By [`Window::app_name`](crate::Window::app_name), case-insensitive.
+    AppName,
+    /// By the area of [`Window::bounds`](crate::Window::bounds), largest window first.
+    Area,
+}
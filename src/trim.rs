@@ -0,0 +1,60 @@
+use image::{imageops::crop_imm, RgbaImage};
+
+fn is_border_pixel(rgba: &[u8]) -> bool {
+    // 完全透明或者纯黑色的像素
+    rgba[3] == 0 || (rgba[0] == 0 && rgba[1] == 0 && rgba[2] == 0)
+}
+
+fn is_border_row(image: &RgbaImage, y: u32, left: u32, right: u32) -> bool {
+    (left..right).all(|x| is_border_pixel(&image.get_pixel(x, y).0))
+}
+
+fn is_border_column(image: &RgbaImage, x: u32, top: u32, bottom: u32) -> bool {
+    (top..bottom).all(|y| is_border_pixel(&image.get_pixel(x, y).0))
+}
+
+/// Crop the fully transparent or fully black edges of the image.
+/// Returns the cropped image and its (x, y) offset in the original image,
+/// an image without content is returned unchanged.
+pub(crate) fn trim_image_borders(image: RgbaImage) -> (RgbaImage, (u32, u32)) {
+    let (mut left, mut top) = (0, 0);
+    let (mut right, mut bottom) = (image.width(), image.height());
+
+    while top < bottom && is_border_row(&image, top, left, right) {
+        top += 1;
+    }
+
+    if top == bottom {
+        return (image, (0, 0));
+    }
+
+    while is_border_row(&image, bottom - 1, left, right) {
+        bottom -= 1;
+    }
+
+    while is_border_column(&image, left, top, bottom) {
+        left += 1;
+    }
+
+    while is_border_column(&image, right - 1, top, bottom) {
+        right -= 1;
+    }
+
+    let trimmed = crop_imm(&image, left, top, right - left, bottom - top).to_image();
+
+    (trimmed, (left, top))
+}
+
+#[test]
+fn trim_borders_crops_transparent_and_black_edges() {
+    let mut image = RgbaImage::new(5, 4);
+    image.put_pixel(0, 0, image::Rgba([0, 0, 0, 255]));
+    image.put_pixel(1, 1, image::Rgba([255, 0, 0, 255]));
+    image.put_pixel(2, 2, image::Rgba([0, 255, 0, 255]));
+
+    let (trimmed, offset) = trim_image_borders(image);
+
+    assert_eq!(offset, (1, 1));
+    assert_eq!(trimmed.dimensions(), (2, 2));
+    assert_eq!(trimmed.get_pixel(0, 0).0, [255, 0, 0, 255]);
+}
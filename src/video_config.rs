@@ -0,0 +1,28 @@
+/// The video codec used by [`VideoRecorder::start_to_file`](crate::VideoRecorder::start_to_file).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum VideoCodec {
+    #[default]
+    H264,
+    Vp9,
+}
+
+/// The encoding settings of [`VideoRecorder::start_to_file`](crate::VideoRecorder::start_to_file).
+/// The container is chosen from the file extension, e.g. `.mp4` or `.webm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VideoConfig {
+    pub codec: VideoCodec,
+    /// The target bitrate, in bits per second
+    pub bitrate: u32,
+    /// The frame rate of the video
+    pub fps: u32,
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        VideoConfig {
+            codec: VideoCodec::H264,
+            bitrate: 8_000_000,
+            fps: 30,
+        }
+    }
+}
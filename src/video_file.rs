@@ -0,0 +1,218 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::sync_channel,
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use image::{
+    imageops::{crop_imm, resize, FilterType},
+    RgbaImage,
+};
+
+use crate::{
+    diagnostic::{report, Diagnostic, DiagnosticTarget},
+    error::{XCapError, XCapResult},
+    raw_handle::RawMonitorHandle,
+    video_config::VideoConfig,
+    Monitor,
+};
+
+#[cfg(feature = "ffmpeg")]
+use crate::ffmpeg_encoder::FfmpegEncoder;
+#[cfg(target_os = "windows")]
+use crate::{platform::media_foundation::MediaFoundationEncoder, video_config::VideoCodec};
+
+/// 把帧编码写入视频文件
+pub(crate) trait VideoEncoder {
+    /// `index` 是帧在视频中的序号，截图比帧率慢时会跳过一些序号
+    fn write_frame(&mut self, image: &RgbaImage, index: u64) -> XCapResult<()>;
+    /// 写入缓存的帧和文件尾
+    fn finish(self: Box<Self>) -> XCapResult<()>;
+}
+
+fn new_video_encoder(
+    path: &Path,
+    width: u32,
+    height: u32,
+    config: &VideoConfig,
+) -> XCapResult<Box<dyn VideoEncoder>> {
+    // Windows 自带的 Media Foundation 只能编码 H.264
+    #[cfg(target_os = "windows")]
+    if config.codec == VideoCodec::H264 {
+        match MediaFoundationEncoder::new(path, width, height, config) {
+            Ok(encoder) => return Ok(Box::new(encoder)),
+            Err(err) if cfg!(feature = "ffmpeg") => report(Diagnostic::new(
+                DiagnosticTarget::None,
+                "MFCreateSinkWriterFromURL",
+                err,
+            )),
+            Err(err) => return Err(err),
+        }
+    }
+
+    new_ffmpeg_encoder(path, width, height, config)
+}
+
+#[cfg(feature = "ffmpeg")]
+fn new_ffmpeg_encoder(
+    path: &Path,
+    width: u32,
+    height: u32,
+    config: &VideoConfig,
+) -> XCapResult<Box<dyn VideoEncoder>> {
+    Ok(Box::new(FfmpegEncoder::new(path, width, height, config)?))
+}
+
+#[cfg(not(feature = "ffmpeg"))]
+fn new_ffmpeg_encoder(
+    _path: &Path,
+    _width: u32,
+    _height: u32,
+    config: &VideoConfig,
+) -> XCapResult<Box<dyn VideoEncoder>> {
+    Err(XCapError::Unsupported(format!(
+        "Encoding {:?} video on this platform requires the ffmpeg feature",
+        config.codec
+    )))
+}
+
+/// 显示器分辨率变化后，缩放到视频的尺寸
+fn fit_frame(image: RgbaImage, width: u32, height: u32) -> RgbaImage {
+    if image.dimensions() == (width, height) {
+        return image;
+    }
+
+    // 只多出奇数尺寸的一个像素时直接裁剪
+    let is_odd_size = |size: u32, target: u32| size.checked_sub(target).is_some_and(|d| d <= 1);
+    if is_odd_size(image.width(), width) && is_odd_size(image.height(), height) {
+        return crop_imm(&image, 0, 0, width, height).to_image();
+    }
+
+    resize(&image, width, height, FilterType::Triangle)
+}
+
+fn record_to_file<F>(
+    monitor: &Monitor,
+    path: &Path,
+    config: &VideoConfig,
+    is_stopped: &AtomicBool,
+    on_started: F,
+) -> XCapResult<()>
+where
+    F: FnOnce(XCapResult<()>),
+{
+    let encoder = monitor.capture_image().and_then(|image| {
+        // 编码器要求 YUV 4:2:0 的宽高都是偶数
+        let (width, height) = (image.width() & !1, image.height() & !1);
+        let encoder = new_video_encoder(path, width, height, config)?;
+
+        Ok((encoder, image, width, height))
+    });
+
+    let (mut encoder, image, width, height) = match encoder {
+        Ok(encoder) => {
+            on_started(Ok(()));
+            encoder
+        }
+        Err(err) => {
+            on_started(Err(err));
+            return Ok(());
+        }
+    };
+
+    let interval = Duration::from_secs_f64(1.0 / config.fps as f64);
+    let start = Instant::now();
+    let mut index = 0;
+    let mut frame = fit_frame(image, width, height);
+
+    loop {
+        encoder.write_frame(&frame, index)?;
+
+        if is_stopped.load(Ordering::Acquire) {
+            break;
+        }
+
+        index += 1;
+        thread::sleep((start + interval * index as u32).saturating_duration_since(Instant::now()));
+        // 截图比帧率慢时跳过已经过去的帧
+        index = index.max((start.elapsed().as_secs_f64() * config.fps as f64) as u64);
+
+        // 截图失败时重复上一帧
+        match monitor.capture_image() {
+            Ok(image) => frame = fit_frame(image, width, height),
+            Err(err) => report(Diagnostic::new(
+                DiagnosticTarget::Monitor(monitor.id().unwrap_or_default()),
+                "capture_image",
+                err,
+            )),
+        }
+    }
+
+    encoder.finish()
+}
+
+/// 在后台线程按照固定帧率截图并写入视频文件
+#[derive(Debug)]
+pub(crate) struct VideoFileRecording {
+    is_stopped: Arc<AtomicBool>,
+    handle: JoinHandle<XCapResult<()>>,
+}
+
+impl VideoFileRecording {
+    pub fn start(
+        raw_monitor_handle: RawMonitorHandle,
+        path: PathBuf,
+        config: VideoConfig,
+    ) -> XCapResult<VideoFileRecording> {
+        if config.fps == 0 {
+            return Err(XCapError::new("Video fps must not be zero"));
+        }
+
+        let is_stopped = Arc::new(AtomicBool::new(false));
+        let thread_is_stopped = is_stopped.clone();
+        let (tx, rx) = sync_channel(1);
+
+        // 编码器不能跨线程使用，在录制线程中创建，创建的结果通过 channel 返回
+        let handle = thread::spawn(move || {
+            let monitor = Monitor::from_raw_handle(raw_monitor_handle)?;
+
+            record_to_file(&monitor, &path, &config, &thread_is_stopped, |res| {
+                let _ = tx.send(res);
+            })
+        });
+
+        match rx.recv() {
+            Ok(Ok(())) => Ok(VideoFileRecording { is_stopped, handle }),
+            Ok(Err(err)) => Err(err),
+            // 线程在创建编码器之前就退出了
+            Err(_) => match handle.join() {
+                Ok(res) => Err(res
+                    .err()
+                    .unwrap_or_else(|| XCapError::new("Start recording failed"))),
+                Err(_) => Err(XCapError::new("The recording thread panicked")),
+            },
+        }
+    }
+
+    /// 停止录制并等待文件写入完成
+    pub fn stop(self) -> XCapResult<()> {
+        self.is_stopped.store(true, Ordering::Release);
+
+        self.handle
+            .join()
+            .map_err(|_| XCapError::new("The recording thread panicked"))?
+    }
+}
+
+#[test]
+fn fit_frame_crops_odd_sizes_and_scales_others() {
+    let image = RgbaImage::new(5, 3);
+
+    assert_eq!(fit_frame(image.clone(), 4, 2).dimensions(), (4, 2));
+    assert_eq!(fit_frame(image, 8, 6).dimensions(), (8, 6));
+}
@@ -1,6 +1,12 @@
-use std::sync::{Condvar, Mutex};
+use std::{
+    path::Path,
+    sync::{Arc, Condvar, Mutex},
+};
 
-use crate::{platform::impl_video_recorder::ImplVideoRecorder, XCapResult};
+use crate::{
+    platform::impl_video_recorder::ImplVideoRecorder, raw_handle::RawMonitorHandle,
+    video_config::VideoConfig, video_file::VideoFileRecording, XCapError, XCapResult,
+};
 
 #[derive(Debug, Clone)]
 pub struct Frame {
@@ -59,12 +65,19 @@ impl RecorderWaker {
 #[derive(Debug, Clone)]
 pub struct VideoRecorder {
     impl_video_recorder: ImplVideoRecorder,
+    raw_monitor_handle: RawMonitorHandle,
+    file_recording: Arc<Mutex<Option<VideoFileRecording>>>,
 }
 
 impl VideoRecorder {
-    pub(crate) fn new(impl_video_recorder: ImplVideoRecorder) -> VideoRecorder {
+    pub(crate) fn new(
+        impl_video_recorder: ImplVideoRecorder,
+        raw_monitor_handle: RawMonitorHandle,
+    ) -> VideoRecorder {
         VideoRecorder {
             impl_video_recorder,
+            raw_monitor_handle,
+            file_recording: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -73,7 +86,38 @@ impl VideoRecorder {
     pub fn start(&self) -> XCapResult<()> {
         self.impl_video_recorder.start()
     }
+
+    /// Record the monitor to a video file until [`VideoRecorder::stop`], which waits for the
+    /// file to be written. Frames are captured with `Monitor::capture_image` at `config.fps`,
+    /// independently of the frames sent to the receiver, and repeated when a capture is late.
+    /// If the monitor resolution changes the frames are scaled to the initial size.
+    ///
+    /// H.264 is encoded with Media Foundation on Windows. VP9, and every codec on MacOS and
+    /// Linux, require the `ffmpeg` feature, otherwise [`XCapError::Unsupported`] is returned.
+    pub fn start_to_file(&self, path: &Path, config: VideoConfig) -> XCapResult<()> {
+        let mut file_recording = self.file_recording.lock()?;
+        if file_recording.is_some() {
+            return Err(XCapError::new(
+                "The recorder is already recording to a file",
+            ));
+        }
+
+        *file_recording = Some(VideoFileRecording::start(
+            self.raw_monitor_handle,
+            path.to_path_buf(),
+            config,
+        )?);
+
+        Ok(())
+    }
     pub fn stop(&self) -> XCapResult<()> {
-        self.impl_video_recorder.stop()
+        self.impl_video_recorder.stop()?;
+
+        let file_recording = self.file_recording.lock()?.take();
+        if let Some(file_recording) = file_recording {
+            file_recording.stop()?;
+        }
+
+        Ok(())
     }
 }
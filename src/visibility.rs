@@ -0,0 +1,53 @@
+use crate::rect::Rect;
+
+/// The fraction (0.0–1.0) of `rect` that is inside one of `monitors` and not covered by
+/// any of `occluders`. The area is split on the edges of all rectangles into cells that are
+/// either fully visible or fully hidden, so the result is exact.
+pub(crate) fn visible_fraction(rect: Rect, monitors: &[Rect], occluders: &[Rect]) -> f32 {
+    if rect.area() == 0 {
+        return 0.0;
+    }
+
+    let clip = |other: &Rect| rect.intersection(other);
+    let monitors: Vec<Rect> = monitors.iter().filter_map(clip).collect();
+    let occluders: Vec<Rect> = occluders.iter().filter_map(clip).collect();
+
+    let mut xs = vec![rect.x, rect.right()];
+    let mut ys = vec![rect.y, rect.bottom()];
+    for item in monitors.iter().chain(occluders.iter()) {
+        xs.extend([item.x, item.right()]);
+        ys.extend([item.y, item.bottom()]);
+    }
+    xs.sort_unstable();
+    xs.dedup();
+    ys.sort_unstable();
+    ys.dedup();
+
+    let mut visible_area = 0u64;
+
+    for y in ys.windows(2) {
+        for x in xs.windows(2) {
+            let cell = Rect::new(x[0], y[0], (x[1] - x[0]) as u32, (y[1] - y[0]) as u32);
+            // 单元格要么完全在矩形内，要么完全在矩形外，检查左上角即可
+            let is_on_monitor = monitors.iter().any(|item| item.contains(cell.x, cell.y));
+            let is_occluded = occluders.iter().any(|item| item.contains(cell.x, cell.y));
+
+            if is_on_monitor && !is_occluded {
+                visible_area += cell.area();
+            }
+        }
+    }
+
+    visible_area as f32 / rect.area() as f32
+}
+
+#[test]
+fn visible_fraction_excludes_occluded_and_offscreen_area() {
+    let rect = Rect::new(0, 0, 100, 100);
+    let monitors = [Rect::new(0, 0, 100, 50)];
+    let occluders = [Rect::new(0, 0, 50, 100)];
+
+    assert_eq!(visible_fraction(rect, &monitors, &[]), 0.5);
+    assert_eq!(visible_fraction(rect, &monitors, &occluders), 0.25);
+    assert_eq!(visible_fraction(rect, &[], &[]), 0.0);
+}
@@ -1,12 +1,76 @@
-use image::RgbaImage;
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    hash::Hash,
+    path::Path,
+    thread,
+    time::{Duration, SystemTime},
+};
 
-use crate::{error::XCapResult, platform::impl_window::ImplWindow, Monitor};
+use image::{
+    imageops::{crop_imm, overlay, resize, FilterType},
+    DynamicImage, Rgba32FImage, RgbaImage,
+};
 
-#[derive(Debug, Clone)]
+use crate::{
+    alpha_mode::AlphaMode,
+    capture_event::record_capture,
+    capture_warning::{is_black_image, CaptureWarning},
+    color::to_linear_f32,
+    diagnostic::DiagnosticTarget,
+    error::{XCapError, XCapResult},
+    platform::impl_window::ImplWindow,
+    raw_handle::RawWindowHandle,
+    rect::Rect,
+    save::save_image,
+    sort_key::SortKey,
+    trim::trim_image_borders,
+    visibility::visible_fraction,
+    window_activity::get_last_active,
+    Monitor,
+};
+
+#[cfg(feature = "encoding")]
+use crate::encoding::{encode_bmp, encode_jpeg, encode_tiff, encode_webp};
+#[cfg(feature = "clipboard")]
+use crate::platform::clipboard::set_clipboard_image;
+
+// 窗口成为前台窗口后重新绘制需要的时间
+const FOCUS_REDRAW_DELAY: Duration = Duration::from_millis(100);
+
+// capture_tracking 在窗口移动或者缩放导致截图失败时的尝试次数
+const TRACKING_ATTEMPTS: u32 = 3;
+
+/// Windows compare equal when they wrap the same platform window handle
+/// (`HWND` on Windows, `CGWindowID` on MacOS, X11 window on Linux),
+/// which is stable for the lifetime of the window.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Window {
     pub(crate) impl_window: ImplWindow,
 }
 
+fn sort_windows_by<K, F>(windows: Vec<Window>, mut key: F) -> Vec<Window>
+where
+    K: Ord,
+    F: FnMut(&Window) -> XCapResult<K>,
+{
+    let mut keyed_windows: Vec<(Option<K>, Window)> = windows
+        .into_iter()
+        .map(|window| (key(&window).ok(), window))
+        .collect();
+
+    // 稳定排序，键相同的窗口保持 z 轴顺序，读取失败的窗口排在最后
+    keyed_windows.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    });
+
+    keyed_windows
+        .into_iter()
+        .map(|(_, window)| window)
+        .collect()
+}
+
 impl Window {
     pub(crate) fn new(impl_window: ImplWindow) -> Window {
         Window { impl_window }
@@ -23,9 +87,216 @@ impl Window {
 
         Ok(windows)
     }
+
+    /// List at most `max` windows, the same windows as the first `max` of [`Window::all`]:
+    /// sorted by z coordinate, topmost window first. On Windows and MacOS the enumeration
+    /// stops checking windows once `max` windows are found.
+    pub fn all_limited(max: usize) -> XCapResult<Vec<Window>> {
+        let windows = ImplWindow::all_limited(max)?
+            .into_iter()
+            .map(Window::new)
+            .collect();
+
+        Ok(windows)
+    }
+
+    /// Create a window from a native handle, without enumerating the windows.
+    pub fn from_raw_handle(raw_handle: RawWindowHandle) -> XCapResult<Window> {
+        Ok(Window::new(ImplWindow::from_raw_handle(raw_handle)?))
+    }
+
+    /// Find the window of the UI Automation element with the `AutomationId`, as used by test
+    /// automation frameworks. When the element is a control without its own `HWND` the nearest
+    /// ancestor window is returned, use [`Window::capture_child_at`] to capture just the control.
+    /// Returns [`XCapError::NotFound`] when no element matches and [`XCapError::Unsupported`]
+    /// on MacOS and Linux.
+    pub fn from_automation_id(automation_id: &str) -> XCapResult<Window> {
+        Ok(Window::new(ImplWindow::from_automation_id(automation_id)?))
+    }
+
+    /// List all windows, including the windows on inactive Spaces on MacOS.
+    /// Windows on an inactive Space can be captured, but the image is the last content
+    /// drawn while the Space was active, so it may be stale. On Windows and Linux this
+    /// is the same as [`Window::all`].
+    pub fn all_spaces() -> XCapResult<Vec<Window>> {
+        let windows = ImplWindow::all_spaces()?
+            .iter()
+            .map(|impl_window| Window::new(impl_window.clone()))
+            .collect();
+
+        Ok(windows)
+    }
+
+    /// List all windows, including the windows without a title, whose `title()` is empty.
+    /// On MacOS [`Window::all`] skips the windows whose `kCGWindowName` can't be read, which
+    /// without the screen recording permission are most windows. On Windows and Linux this
+    /// is the same as [`Window::all`].
+    pub fn all_including_untitled() -> XCapResult<Vec<Window>> {
+        let windows = ImplWindow::all_including_untitled()?
+            .iter()
+            .map(|impl_window| Window::new(impl_window.clone()))
+            .collect();
+
+        Ok(windows)
+    }
+
+    /// List all windows like [`Window::all`], keeping only the frontmost of the windows with
+    /// the same bounds, pid and title, e.g. the windows a browser creates for tab previews.
+    pub fn all_deduplicated() -> XCapResult<Vec<Window>> {
+        Window::all_deduplicated_by(|window| {
+            Ok((
+                window.x()?,
+                window.y()?,
+                window.width()?,
+                window.height()?,
+                window.pid()?,
+                window.title()?,
+            ))
+        })
+    }
+
+    /// List all windows like [`Window::all`], keeping only the frontmost of the windows with
+    /// the same key. Windows whose key can't be read are always kept.
+    pub fn all_deduplicated_by<K, F>(mut key: F) -> XCapResult<Vec<Window>>
+    where
+        K: Eq + Hash,
+        F: FnMut(&Window) -> XCapResult<K>,
+    {
+        let mut keys = HashSet::new();
+
+        // Window::all 按照 z 轴排序，先出现的窗口在前面
+        let windows = Window::all()?
+            .into_iter()
+            .filter(|window| match key(window) {
+                Ok(key) => keys.insert(key),
+                Err(_) => true,
+            })
+            .collect();
+
+        Ok(windows)
+    }
+
+    /// List all windows like [`Window::all`], sorted by `by`. Windows whose key can't be read,
+    /// e.g. because they were closed while sorting, are put last.
+    pub fn all_sorted(by: SortKey) -> XCapResult<Vec<Window>> {
+        let windows = Window::all()?;
+
+        let windows = match by {
+            SortKey::ZOrder => sort_windows_by(windows, |window| window.z().map(Reverse)),
+            SortKey::Title => sort_windows_by(windows, |window| {
+                window.title().map(|title| title.to_lowercase())
+            }),
+            SortKey::AppName => sort_windows_by(windows, |window| {
+                window.app_name().map(|app_name| app_name.to_lowercase())
+            }),
+            SortKey::Area => sort_windows_by(windows, |window| {
+                window.bounds().map(|bounds| Reverse(bounds.area()))
+            }),
+        };
+
+        Ok(windows)
+    }
+
+    /// List the windows of the OS shell, sorted by z coordinate: the taskbars (`Shell_TrayWnd`
+    /// and `Shell_SecondaryTrayWnd`) on Windows, the Dock, menu bar and status item windows on
+    /// MacOS and the `_NET_WM_WINDOW_TYPE_DOCK` panels on Linux (X11). Shells that don't draw
+    /// their panels as X11 windows, like GNOME Shell, have none.
+    pub fn shell_surfaces() -> XCapResult<Vec<Window>> {
+        let windows = ImplWindow::shell_surfaces()?
+            .into_iter()
+            .map(Window::new)
+            .collect();
+
+        Ok(windows)
+    }
+
+    /// Capture images of several windows, in the same order as `windows`.
+    /// On Windows the desktop is captured once and each window is cropped from it, so the
+    /// occluded parts of a window show whatever is on top of it, use [`Window::capture_image`]
+    /// for an isolated capture. Minimized windows and windows not fully on screen are still
+    /// captured individually. On MacOS and Linux every window is captured individually.
+    pub fn capture_all(windows: &[Window]) -> XCapResult<Vec<RgbaImage>> {
+        let impl_windows: Vec<ImplWindow> = windows
+            .iter()
+            .map(|window| window.impl_window.clone())
+            .collect();
+
+        ImplWindow::capture_all(&impl_windows)
+    }
+
+    /// Capture all visible windows of the process `pid` as one image, composited in z order on
+    /// a transparent background covering the union of the window bounds. Each window is
+    /// captured individually, so windows of other processes on top of them don't show.
+    pub fn capture_app(pid: u32) -> XCapResult<RgbaImage> {
+        let mut windows = Vec::new();
+
+        for window in Window::all()? {
+            if window.pid()? != pid || window.is_minimized()? {
+                continue;
+            }
+
+            let bounds = window.bounds()?;
+            if bounds.area() == 0 {
+                continue;
+            }
+
+            windows.push((window.z()?, bounds, window.capture_image()?));
+        }
+
+        let union_bounds = windows
+            .iter()
+            .map(|(_, bounds, _)| *bounds)
+            .reduce(|union_bounds, bounds| union_bounds.union(&bounds))
+            .ok_or_else(|| XCapError::new("Not found visible windows of the process"))?;
+
+        // 从最底层的窗口开始绘制
+        windows.sort_by_key(|(z, _, _)| *z);
+
+        // 截图可能是物理像素，使用最大的缩放比例避免丢失细节
+        let scale = windows
+            .iter()
+            .map(|(_, bounds, image)| image.width() as f32 / bounds.width as f32)
+            .fold(1.0, f32::max);
+
+        let mut app_image = RgbaImage::new(
+            (union_bounds.width as f32 * scale).round() as u32,
+            (union_bounds.height as f32 * scale).round() as u32,
+        );
+
+        for (_, bounds, image) in windows {
+            let target_width = (bounds.width as f32 * scale).round() as u32;
+            let target_height = (bounds.height as f32 * scale).round() as u32;
+
+            let image = if image.dimensions() == (target_width, target_height) {
+                image
+            } else {
+                resize(&image, target_width, target_height, FilterType::Triangle)
+            };
+
+            overlay(
+                &mut app_image,
+                &image,
+                ((bounds.x - union_bounds.x) as f32 * scale).round() as i64,
+                ((bounds.y - union_bounds.y) as f32 * scale).round() as i64,
+            );
+        }
+
+        Ok(app_image)
+    }
+
+    /// Capture the window the user picks in the compositor's ScreenCast dialog, on Linux
+    /// (Wayland) with the `pipewire` feature. The portal can't select a window by id, so this
+    /// is the only way to capture native Wayland windows. Unsupported on other platforms.
+    pub fn capture_with_picker() -> XCapResult<RgbaImage> {
+        ImplWindow::capture_with_picker()
+    }
 }
 
 impl Window {
+    /// The native handle of the window.
+    pub fn raw_handle(&self) -> RawWindowHandle {
+        self.impl_window.raw_handle()
+    }
     /// The window id
     pub fn id(&self) -> XCapResult<u32> {
         self.impl_window.id()
@@ -34,10 +305,14 @@ impl Window {
     pub fn pid(&self) -> XCapResult<u32> {
         self.impl_window.pid()
     }
-    /// The window app name
+    /// The window app name, a user-facing display name which may be localized
     pub fn app_name(&self) -> XCapResult<String> {
         self.impl_window.app_name()
     }
+    /// The executable file name of the window process, e.g. `firefox` or `Code.exe`
+    pub fn process_name(&self) -> XCapResult<String> {
+        self.impl_window.process_name()
+    }
     /// The window title
     pub fn title(&self) -> XCapResult<String> {
         self.impl_window.title()
@@ -46,6 +321,17 @@ impl Window {
     pub fn current_monitor(&self) -> XCapResult<Monitor> {
         Ok(Monitor::new(self.impl_window.current_monitor()?))
     }
+    /// The monitors the window is on, with the fraction of the window area on each of them.
+    pub fn monitor_coverage(&self) -> XCapResult<Vec<(Monitor, f32)>> {
+        let monitor_coverage = self
+            .impl_window
+            .monitor_coverage()?
+            .into_iter()
+            .map(|(impl_monitor, coverage)| (Monitor::new(impl_monitor), coverage))
+            .collect();
+
+        Ok(monitor_coverage)
+    }
     /// The window x coordinate.
     pub fn x(&self) -> XCapResult<i32> {
         self.impl_window.x()
@@ -54,6 +340,15 @@ impl Window {
     pub fn y(&self) -> XCapResult<i32> {
         self.impl_window.y()
     }
+    /// The window bounds, the same as `x()`, `y()`, `width()` and `height()`.
+    pub fn bounds(&self) -> XCapResult<Rect> {
+        Ok(Rect::new(
+            self.x()?,
+            self.y()?,
+            self.width()?,
+            self.height()?,
+        ))
+    }
     /// The window z coordinate.
     pub fn z(&self) -> XCapResult<i32> {
         self.impl_window.z()
@@ -66,6 +361,19 @@ impl Window {
     pub fn height(&self) -> XCapResult<u32> {
         self.impl_window.height()
     }
+    /// The overall opacity of the window from 0.0 to 1.0, set with `SetLayeredWindowAttributes`
+    /// on Windows, `kCGWindowAlpha` on MacOS and `_NET_WM_WINDOW_OPACITY` on Linux (X11).
+    /// 1.0 when the window has no opacity set.
+    pub fn opacity(&self) -> XCapResult<f32> {
+        self.impl_window.opacity()
+    }
+    /// The window excludes itself from screen capture (DRM video, password managers), so its
+    /// captures are black or empty: `SetWindowDisplayAffinity` with `WDA_MONITOR` or
+    /// `WDA_EXCLUDEFROMCAPTURE` on Windows, `kCGWindowSharingNone` on MacOS (such windows are
+    /// also left out of `Window::all()`). Always false on Linux.
+    pub fn is_capture_protected(&self) -> XCapResult<bool> {
+        self.impl_window.is_capture_protected()
+    }
     /// The window is minimized.
     pub fn is_minimized(&self) -> XCapResult<bool> {
         self.impl_window.is_minimized()
@@ -78,10 +386,284 @@ impl Window {
     pub fn is_focused(&self) -> XCapResult<bool> {
         self.impl_window.is_focused()
     }
+    /// Bring the window to the foreground: `SetForegroundWindow` on Windows, which only
+    /// succeeds when the calling process is allowed to change the foreground window, activating
+    /// the application (its frontmost window comes forward) on MacOS, and a
+    /// `_NET_ACTIVE_WINDOW` request to the window manager on Linux (X11).
+    pub fn focus(&self) -> XCapResult<()> {
+        self.impl_window.focus()
+    }
+    /// When the window last became the foreground window, for ordering windows by recent use.
+    /// Only known for activations after [`track_window_activity`](crate::track_window_activity)
+    /// was called, `None` otherwise. The focused window is active now.
+    pub fn last_active(&self) -> XCapResult<Option<SystemTime>> {
+        if self.is_focused()? {
+            return Ok(Some(SystemTime::now()));
+        }
+
+        Ok(get_last_active(self.id()?))
+    }
+    /// The fraction (0.0–1.0) of the window area that is currently visible: on a monitor and
+    /// not covered by a window above it. It's computed from the bounds, so it's much cheaper
+    /// than capturing, but transparent or non-rectangular windows count as fully covering.
+    /// A minimized window is 0.0.
+    pub fn visible_fraction(&self) -> XCapResult<f32> {
+        if self.is_minimized()? {
+            return Ok(0.0);
+        }
+
+        let monitors = Monitor::all()?
+            .iter()
+            .map(|monitor| monitor.bounds())
+            .collect::<XCapResult<Vec<Rect>>>()?;
+
+        // Window::all 按照 z 轴顺序排列，在当前窗口前面的窗口都在它上面，
+        // 当前窗口不在列表中时（例如被过滤掉的工具窗口）比较 z 值
+        let windows = Window::all()?;
+        let windows_above = match windows.iter().position(|window| window == self) {
+            Some(position) => windows[..position].to_vec(),
+            None => {
+                let z = self.z()?;
+                let mut windows_above = Vec::new();
+                for window in windows {
+                    if window.z()? > z {
+                        windows_above.push(window);
+                    }
+                }
+                windows_above
+            }
+        };
+
+        let mut occluders = Vec::new();
+        for window in windows_above {
+            if !window.is_minimized()? {
+                occluders.push(window.bounds()?);
+            }
+        }
+
+        Ok(visible_fraction(self.bounds()?, &monitors, &occluders))
+    }
 }
 
 impl Window {
+    /// Capture image of the window, as sRGB encoded 8-bit RGBA. Translucent pixels may have
+    /// premultiplied alpha, see [`Window::capture_image_with_alpha_mode`].
+    /// On Windows, a minimized window can't be captured, so the last frame captured while it
+    /// was visible is returned instead, which may be stale. Only the most recently captured
+    /// windows are kept, see
+    /// [`set_minimized_frame_cache_capacity`](crate::set_minimized_frame_cache_capacity). If
+    /// there is no previous frame, an error is returned.
+    /// On Linux (Wayland) only XWayland windows can be captured, see
+    /// [`Window::capture_with_picker`] for native Wayland windows.
+    /// The attempt is recorded in [`recent_events`](crate::recent_events).
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
-        self.impl_window.capture_image()
+        record_capture(
+            DiagnosticTarget::Window(self.id().unwrap_or_default()),
+            "Window::capture_image",
+            || Ok((self.impl_window.capture_image()?, None)),
+        )
+    }
+
+    /// Capture image of the window with the capture area snapped outwards to the pixel grid.
+    /// On MacOS the window bounds are in points and can be fractional on Retina displays,
+    /// capturing them as-is blurs the edges into 1px seams. Returns the image and the captured
+    /// area in physical pixels, on MacOS that is the global coordinates times the backing scale.
+    pub fn capture_image_snapped(&self) -> XCapResult<(RgbaImage, Rect)> {
+        self.impl_window.capture_image_snapped()
+    }
+
+    /// Capture the full content of the largest vertically scrollable area in the window (a
+    /// document, a web page), by scrolling it from the top to the bottom and stitching the
+    /// pages into one tall image. The scroll position is restored afterwards. Uses UI
+    /// Automation on Windows and the accessibility API on MacOS, which needs the
+    /// accessibility permission. Returns [`XCapError::Unsupported`] when no scrollable area is
+    /// found, and on Linux.
+    pub fn capture_scrollable(&self) -> XCapResult<RgbaImage> {
+        self.impl_window.capture_scrollable()
+    }
+
+    /// Capture image of the window at its current position and size, for recorders that follow
+    /// a moving window. The geometry is read again right before the capture, and a capture that
+    /// failed because the window moved or was resized meanwhile is retried. Returns
+    /// [`XCapError::NotFound`] once the window is closed.
+    pub fn capture_tracking(&self) -> XCapResult<RgbaImage> {
+        let mut last_err = None;
+
+        for _ in 0..TRACKING_ATTEMPTS {
+            match self.impl_window.capture_image() {
+                Ok(image) => return Ok(image),
+                // 截图失败时区分窗口已经关闭和几何信息变化
+                Err(_) if !self.impl_window.is_valid() => {
+                    return Err(XCapError::NotFound("The window was closed".to_string()));
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| XCapError::new("Capture window failed")))
+    }
+
+    /// Bring the window to the foreground and capture it, for windows that only render while
+    /// they are in front. This changes the foreground window the user sees and takes the
+    /// keyboard focus, so it should only be used when the user expects it. With
+    /// `restore_foreground` the previous foreground window is brought back after the capture.
+    /// See [`Window::focus`] for the platform restrictions.
+    pub fn capture_image_focused(&self, restore_foreground: bool) -> XCapResult<RgbaImage> {
+        let previous_foreground = if restore_foreground {
+            ImplWindow::foreground()?
+        } else {
+            None
+        };
+
+        self.focus()?;
+        // 等待窗口重新绘制
+        thread::sleep(FOCUS_REDRAW_DELAY);
+
+        let image = self.capture_image();
+
+        if let Some(previous_foreground) = previous_foreground {
+            if previous_foreground != self.impl_window {
+                if let Err(err) = previous_foreground.focus() {
+                    log::error!("Restore the foreground window failed: {}", err);
+                }
+            }
+        }
+
+        image
+    }
+
+    /// Capture image of the window, as a `DynamicImage` holding the RGBA image.
+    pub fn capture_dynamic_image(&self) -> XCapResult<DynamicImage> {
+        Ok(DynamicImage::ImageRgba8(self.capture_image()?))
+    }
+
+    /// Capture image of the window and write it to `path`. The format is inferred from the
+    /// extension: .png and .bmp, or .jpg and .webp with the `encoding` feature.
+    /// Missing parent directories are created.
+    pub fn capture_to_file<P: AsRef<Path>>(&self, path: P) -> XCapResult<()> {
+        save_image(&self.capture_image()?, path.as_ref())
+    }
+
+    /// Capture image of the window, with a warning when the image is fully black because
+    /// the window is likely drawn on a hardware overlay, see
+    /// [`CaptureWarning::LikelyHardwareOverlay`].
+    pub fn capture_image_with_warning(&self) -> XCapResult<(RgbaImage, Option<CaptureWarning>)> {
+        let image = self.capture_image()?;
+
+        let warning = is_black_image(&image).then_some(CaptureWarning::LikelyHardwareOverlay);
+
+        Ok((image, warning))
+    }
+
+    /// Capture image of the window and report how its alpha channel is stored. Layered windows
+    /// on Windows and all windows on MacOS are captured with [`AlphaMode::Premultiplied`]
+    /// alpha, call [`unpremultiply`](crate::unpremultiply) before compositing them with
+    /// the `image` crate. Captures on Linux are opaque.
+    pub fn capture_image_with_alpha_mode(&self) -> XCapResult<(RgbaImage, AlphaMode)> {
+        let alpha_mode = self.impl_window.alpha_mode()?;
+        let image = self.capture_image()?;
+
+        Ok((image, alpha_mode))
+    }
+
+    /// Capture image of the window and put it on the system clipboard, as `CF_DIBV5` on
+    /// Windows and PNG on MacOS and Linux. On Linux `wl-copy` (Wayland) or `xclip` (X11)
+    /// must be installed, since the clipboard has to be served by a running process.
+    #[cfg(feature = "clipboard")]
+    pub fn capture_to_clipboard(&self) -> XCapResult<()> {
+        let image = self.capture_image()?;
+
+        set_clipboard_image(&image)
+    }
+
+    /// Capture image of the window encoded as JPEG, `quality` is between 1 and 100.
+    /// The alpha channel is dropped.
+    #[cfg(feature = "encoding")]
+    pub fn capture_jpeg(&self, quality: u8) -> XCapResult<Vec<u8>> {
+        encode_jpeg(&self.capture_image()?, quality)
+    }
+
+    /// Capture image of the window encoded as lossy WebP, `quality` is between 0 and 100,
+    /// around 70 works well for thumbnails.
+    #[cfg(feature = "encoding")]
+    pub fn capture_webp(&self, quality: f32) -> XCapResult<Vec<u8>> {
+        encode_webp(&self.capture_image()?, quality)
+    }
+
+    /// Capture image of the window encoded as uncompressed BMP, the alpha channel is dropped.
+    #[cfg(feature = "encoding")]
+    pub fn capture_bmp(&self) -> XCapResult<Vec<u8>> {
+        encode_bmp(&self.capture_image()?)
+    }
+
+    /// Capture image of the window encoded as uncompressed 8-bit RGBA TIFF. With `embed_dpi`
+    /// the resolution is set to 96 DPI times the scale factor of the monitor the window is on.
+    #[cfg(feature = "encoding")]
+    pub fn capture_tiff(&self, embed_dpi: bool) -> XCapResult<Vec<u8>> {
+        let scale_factor = if embed_dpi {
+            Some(self.current_monitor()?.scale_factor()?)
+        } else {
+            None
+        };
+
+        encode_tiff(&self.capture_image()?, scale_factor)
+    }
+
+    /// Capture image of the window, with the sRGB transfer function removed
+    /// so the color channels are linear. Alpha is unchanged.
+    pub fn capture_image_linear_f32(&self) -> XCapResult<Rgba32FImage> {
+        to_linear_f32(&self.capture_image()?)
+    }
+
+    /// Capture image of the window, with the (x, y) offset of the image origin from the
+    /// window's visible frame origin, adding it to an image pixel gives the pixel position in
+    /// the frame. On Windows the image is the client area while the frame is
+    /// `DWMWA_EXTENDED_FRAME_BOUNDS`, which excludes the DWM drop shadow.
+    /// On MacOS and Linux the offset is always (0, 0).
+    pub fn capture_image_with_offset(&self) -> XCapResult<(RgbaImage, (i32, i32))> {
+        let image = self.capture_image()?;
+
+        Ok((image, self.impl_window.frame_offset()?))
+    }
+
+    /// Capture image of the innermost child window (a button, a text field, ...) at (x, y),
+    /// relative to the top-left corner of the window image. Returns the image and the child
+    /// bounds relative to the window. Child windows are `HWND`s on Windows and X11 windows
+    /// on Linux, MacOS is not supported.
+    pub fn capture_child_at(&self, x: i32, y: i32) -> XCapResult<(RgbaImage, Rect)> {
+        let child_rect = self.impl_window.child_rect_at(x, y)?;
+        let window_rect = Rect::new(0, 0, self.width()?, self.height()?);
+
+        let crop_rect = window_rect
+            .intersection(&child_rect)
+            .ok_or_else(|| XCapError::new("Child window is outside the window"))?;
+
+        let image = self.capture_image()?;
+
+        // 截图可能是物理像素，需要按比例换算
+        let scale = image.width() as f32 / window_rect.width as f32;
+        let image = crop_imm(
+            &image,
+            (crop_rect.x as f32 * scale).round() as u32,
+            (crop_rect.y as f32 * scale).round() as u32,
+            (crop_rect.width as f32 * scale).round() as u32,
+            (crop_rect.height as f32 * scale).round() as u32,
+        )
+        .to_image();
+
+        Ok((image, child_rect))
+    }
+
+    /// Capture image of the window, when `trim_borders` is true the uniform fully transparent
+    /// or fully black edges (e.g. DWM shadows) are cropped. Returns the image and its (x, y)
+    /// offset in the untrimmed capture, which is (0, 0) when `trim_borders` is false.
+    pub fn capture_image_trimmed(&self, trim_borders: bool) -> XCapResult<(RgbaImage, (u32, u32))> {
+        let image = self.capture_image()?;
+
+        if !trim_borders {
+            return Ok((image, (0, 0)));
+        }
+
+        Ok(trim_image_borders(image))
     }
 }
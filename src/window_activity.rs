@@ -0,0 +1,54 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::SystemTime,
+};
+
+use crate::{
+    error::XCapResult,
+    foreground_watcher::{watch_foreground_window, ForegroundWatcher},
+    Window,
+};
+
+// 以窗口 id 为键，记录窗口最近一次成为前台窗口的时间
+static LAST_ACTIVE: LazyLock<Mutex<HashMap<u32, SystemTime>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn set_last_active(window: &Window, time: SystemTime) {
+    let window_id = match window.id() {
+        Ok(window_id) => window_id,
+        Err(err) => {
+            log::error!("Get window id failed: {}", err);
+            return;
+        }
+    };
+
+    match LAST_ACTIVE.lock() {
+        Ok(mut last_active) => {
+            last_active.insert(window_id, time);
+        }
+        Err(err) => log::error!("Set window last active time failed: {}", err),
+    }
+}
+
+pub(crate) fn get_last_active(window_id: u32) -> Option<SystemTime> {
+    LAST_ACTIVE
+        .lock()
+        .ok()
+        .and_then(|last_active| last_active.get(&window_id).copied())
+}
+
+/// Start recording when each window becomes the foreground window, read with
+/// [`Window::last_active`]. None of the platforms keep this history, so only the activations
+/// after the tracking started are known. The tracking stops when
+/// [`ForegroundWatcher::stop`] is called on the returned watcher.
+pub fn track_window_activity() -> XCapResult<ForegroundWatcher> {
+    let now = SystemTime::now();
+    for window in Window::all()? {
+        if window.is_focused().unwrap_or(false) {
+            set_last_active(&window, now);
+        }
+    }
+
+    watch_foreground_window(|window| set_last_active(&window, SystemTime::now()))
+}
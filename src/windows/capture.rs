@@ -1,4 +1,4 @@
-use std::{ffi::c_void, mem};
+use std::{ffi::c_void, fmt::Debug, mem};
 
 use image::{DynamicImage, RgbaImage};
 use scopeguard::guard;
@@ -8,24 +8,35 @@ use windows::Win32::{
         Dwm::DwmIsCompositionEnabled,
         Gdi::{
             BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject,
-            GetCurrentObject, GetDIBits, GetObjectW, GetWindowDC, ReleaseDC, SelectObject, BITMAP,
-            BITMAPINFO, BITMAPINFOHEADER, DIB_RGB_COLORS, HBITMAP, HDC, OBJ_BITMAP, SRCCOPY,
+            GetCurrentObject, GetDC, GetDIBits, GetObjectW, GetWindowDC, ReleaseDC, SelectObject,
+            SetStretchBltMode, StretchBlt, BITMAP, BITMAPINFO, BITMAPINFOHEADER, DIB_RGB_COLORS,
+            HALFTONE, HBITMAP, HDC, OBJ_BITMAP, SRCCOPY,
         },
     },
     Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS},
-    UI::WindowsAndMessaging::GetDesktopWindow,
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    capture_limit::check_capture_size,
+    diagnostic::{report, Diagnostic, DiagnosticTarget},
+    error::{XCapError, XCapResult},
+};
 
-use super::utils::{bgra_to_rgba_image, get_os_major_version, get_window_info};
+use super::utils::{bgra_to_rgba_image, fix_bgra_alpha, get_os_major_version, get_window_info};
 
-fn to_rgba_image(
+pub(super) fn to_rgba_image(
     hdc_mem: HDC,
     h_bitmap: HBITMAP,
     width: i32,
     height: i32,
 ) -> XCapResult<RgbaImage> {
+    let buffer = to_bgra_buffer(hdc_mem, h_bitmap, width, height)?;
+
+    bgra_to_rgba_image(width as u32, height as u32, buffer)
+}
+
+/// 读取位图的像素，GDI 的像素顺序为 BGRA
+fn to_bgra_buffer(hdc_mem: HDC, h_bitmap: HBITMAP, width: i32, height: i32) -> XCapResult<Vec<u8>> {
     let buffer_size = width * height * 4;
     let mut bitmap_info = BITMAPINFO {
         bmiHeader: BITMAPINFOHEADER {
@@ -60,26 +71,68 @@ fn to_rgba_image(
         }
     };
 
-    bgra_to_rgba_image(width as u32, height as u32, buffer)
+    Ok(buffer)
+}
+
+fn report_gdi_failure<T: Debug>(operation: &'static str, val: T) {
+    report(
+        Diagnostic::new(DiagnosticTarget::None, operation, format!("{:?}", val))
+            .with_os_error_code(unsafe { GetLastError().0 as i32 }),
+    );
 }
 
-fn delete_bitmap_object(val: HBITMAP) {
+pub(super) fn delete_bitmap_object(val: HBITMAP) {
     unsafe {
         let succeed = DeleteObject(val.into()).as_bool();
 
         if !succeed {
-            log::error!("DeleteObject({:?}) failed: {:?}", val, GetLastError());
+            report_gdi_failure("DeleteObject", val);
         }
     }
 }
 
 #[allow(unused)]
 pub fn capture_monitor(x: i32, y: i32, width: i32, height: i32) -> XCapResult<RgbaImage> {
+    capture_monitor_scaled(x, y, width, height, width, height)
+}
+
+pub fn capture_monitor_scaled(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    dst_width: i32,
+    dst_height: i32,
+) -> XCapResult<RgbaImage> {
+    let buffer = capture_monitor_bgra_scaled(x, y, width, height, dst_width, dst_height)?;
+
+    bgra_to_rgba_image(dst_width as u32, dst_height as u32, buffer)
+}
+
+/// Capture the monitor without converting the pixels, in the BGRA order of GDI.
+pub fn capture_monitor_bgra(x: i32, y: i32, width: i32, height: i32) -> XCapResult<Vec<u8>> {
+    let mut buffer = capture_monitor_bgra_scaled(x, y, width, height, width, height)?;
+    fix_bgra_alpha(&mut buffer);
+
+    Ok(buffer)
+}
+
+fn capture_monitor_bgra_scaled(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    dst_width: i32,
+    dst_height: i32,
+) -> XCapResult<Vec<u8>> {
+    check_capture_size(dst_width.max(0) as u32, dst_height.max(0) as u32)?;
+
     unsafe {
-        let hwnd = GetDesktopWindow();
-        let scope_guard_hdc_desktop_window = guard(GetWindowDC(Some(hwnd)), |val| {
-            if ReleaseDC(Some(hwnd), val) != 1 {
-                log::error!("ReleaseDC({:?}) failed: {:?}", val, GetLastError());
+        // 桌面窗口的 DC 会被裁剪到主显示器的范围，位于主显示器左侧或上方（坐标为负）的显示器截图为空白，
+        // 屏幕 DC 覆盖整个虚拟桌面，坐标与屏幕坐标相同
+        let scope_guard_hdc_desktop_window = guard(GetDC(None), |val| {
+            if ReleaseDC(None, val) != 1 {
+                report_gdi_failure("ReleaseDC", val);
             }
         });
 
@@ -89,13 +142,13 @@ pub fn capture_monitor(x: i32, y: i32, width: i32, height: i32) -> XCapResult<Rg
             CreateCompatibleDC(Some(*scope_guard_hdc_desktop_window)),
             |val| {
                 if !DeleteDC(val).as_bool() {
-                    log::error!("DeleteDC({:?}) failed: {:?}", val, GetLastError());
+                    report_gdi_failure("DeleteDC", val);
                 }
             },
         );
 
         let scope_guard_h_bitmap = guard(
-            CreateCompatibleBitmap(*scope_guard_hdc_desktop_window, width, height),
+            CreateCompatibleBitmap(*scope_guard_hdc_desktop_window, dst_width, dst_height),
             delete_bitmap_object,
         );
 
@@ -103,21 +156,43 @@ pub fn capture_monitor(x: i32, y: i32, width: i32, height: i32) -> XCapResult<Rg
         SelectObject(*scope_guard_mem, (*scope_guard_h_bitmap).into());
 
         // 拷贝原始图像到内存
-        // 这里不需要缩放图片，所以直接使用BitBlt
-        // 如需要缩放，则使用 StretchBlt
-        BitBlt(
+        // 不需要缩放图片时直接使用BitBlt，需要缩放时使用 StretchBlt
+        if width == dst_width && height == dst_height {
+            BitBlt(
+                *scope_guard_mem,
+                0,
+                0,
+                width,
+                height,
+                Some(*scope_guard_hdc_desktop_window),
+                x,
+                y,
+                SRCCOPY,
+            )?;
+        } else {
+            SetStretchBltMode(*scope_guard_mem, HALFTONE);
+            StretchBlt(
+                *scope_guard_mem,
+                0,
+                0,
+                dst_width,
+                dst_height,
+                Some(*scope_guard_hdc_desktop_window),
+                x,
+                y,
+                width,
+                height,
+                SRCCOPY,
+            )
+            .ok()?;
+        }
+
+        to_bgra_buffer(
             *scope_guard_mem,
-            0,
-            0,
-            width,
-            height,
-            Some(*scope_guard_hdc_desktop_window),
-            x,
-            y,
-            SRCCOPY,
-        )?;
-
-        to_rgba_image(*scope_guard_mem, *scope_guard_h_bitmap, width, height)
+            *scope_guard_h_bitmap,
+            dst_width,
+            dst_height,
+        )
     }
 }
 
@@ -132,7 +207,7 @@ pub fn capture_window(hwnd: HWND, scale_factor: f32) -> XCapResult<RgbaImage> {
 
         let scope_guard_hdc_window = guard(GetWindowDC(Some(hwnd)), |val| {
             if ReleaseDC(Some(hwnd), val) != 1 {
-                log::error!("ReleaseDC({:?}) failed: {:?}", val, GetLastError());
+                report_gdi_failure("ReleaseDC", val);
             }
         });
 
@@ -155,11 +230,13 @@ pub fn capture_window(hwnd: HWND, scale_factor: f32) -> XCapResult<RgbaImage> {
         width = (width as f32 * scale_factor).ceil() as i32;
         height = (height as f32 * scale_factor).ceil() as i32;
 
+        check_capture_size(width.max(0) as u32, height.max(0) as u32)?;
+
         // 内存中的HDC，使用 DeleteDC 函数释放
         // https://learn.microsoft.com/zh-cn/windows/win32/api/wingdi/nf-wingdi-createcompatibledc
         let scope_guard_hdc_mem = guard(CreateCompatibleDC(Some(*scope_guard_hdc_window)), |val| {
             if !DeleteDC(val).as_bool() {
-                log::error!("DeleteDC({:?}) failed: {:?}", val, GetLastError());
+                report_gdi_failure("DeleteDC", val);
             }
         });
         let scope_guard_h_bitmap = guard(
@@ -0,0 +1,93 @@
+use std::{mem, ptr, slice};
+
+use image::RgbaImage;
+use scopeguard::guard;
+use windows::Win32::{
+    Foundation::{GetLastError, GlobalFree, HANDLE},
+    Graphics::Gdi::{BITMAPV5HEADER, BI_BITFIELDS},
+    System::{
+        DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
+        Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+        Ole::CF_DIBV5,
+    },
+};
+
+use crate::error::{XCapError, XCapResult};
+
+// LCS_sRGB
+const LCS_SRGB: u32 = 0x7352_4742;
+
+fn to_dib_v5(image: &RgbaImage) -> Vec<u8> {
+    let width = image.width();
+    let height = image.height();
+    let header_size = mem::size_of::<BITMAPV5HEADER>();
+
+    let header = BITMAPV5HEADER {
+        bV5Size: header_size as u32,
+        bV5Width: width as i32,
+        // 部分应用不支持自上而下的位图，所以使用自下而上的行顺序
+        bV5Height: height as i32,
+        bV5Planes: 1,
+        bV5BitCount: 32,
+        bV5Compression: BI_BITFIELDS,
+        bV5SizeImage: width * height * 4,
+        bV5RedMask: 0x00FF_0000,
+        bV5GreenMask: 0x0000_FF00,
+        bV5BlueMask: 0x0000_00FF,
+        bV5AlphaMask: 0xFF00_0000,
+        bV5CSType: LCS_SRGB,
+        ..Default::default()
+    };
+
+    let mut buffer = Vec::with_capacity(header_size + (width * height * 4) as usize);
+    buffer.extend_from_slice(unsafe {
+        slice::from_raw_parts(&header as *const BITMAPV5HEADER as *const u8, header_size)
+    });
+
+    for row in image.rows().rev() {
+        for rgba in row {
+            buffer.extend_from_slice(&[rgba[2], rgba[1], rgba[0], rgba[3]]);
+        }
+    }
+
+    buffer
+}
+
+pub(crate) fn set_clipboard_image(image: &RgbaImage) -> XCapResult<()> {
+    let dib_v5 = to_dib_v5(image);
+
+    unsafe {
+        OpenClipboard(None)?;
+
+        let _scope_guard_clipboard = guard((), |_| {
+            if let Err(err) = CloseClipboard() {
+                log::error!("CloseClipboard failed: {:?}", err);
+            }
+        });
+
+        EmptyClipboard()?;
+
+        let h_global = GlobalAlloc(GMEM_MOVEABLE, dib_v5.len())?;
+
+        let data_ptr = GlobalLock(h_global);
+        if data_ptr.is_null() {
+            let _ = GlobalFree(Some(h_global));
+            return Err(XCapError::new(format!(
+                "GlobalLock failed: {:?}",
+                GetLastError()
+            )));
+        }
+
+        ptr::copy_nonoverlapping(dib_v5.as_ptr(), data_ptr.cast(), dib_v5.len());
+        // 锁计数减为 0 时 GlobalUnlock 也会返回错误，所以忽略返回值
+        let _ = GlobalUnlock(h_global);
+
+        // 设置成功后内存由系统管理，失败时需要手动释放
+        if let Err(err) = SetClipboardData(CF_DIBV5.0 as u32, Some(HANDLE(h_global.0))) {
+            let _ = GlobalFree(Some(h_global));
+            return Err(err.into());
+        }
+    }
+
+    Ok(())
+}
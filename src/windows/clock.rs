@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
+
+pub fn get_clock_time() -> (u64, Duration) {
+    let mut counter = 0;
+    let mut frequency = 0;
+
+    unsafe {
+        // 在 Windows XP 之后的系统上这两个函数总是成功
+        let _ = QueryPerformanceCounter(&mut counter);
+        let _ = QueryPerformanceFrequency(&mut frequency);
+    }
+
+    let counter = counter as u64;
+    let frequency = (frequency as u64).max(1);
+
+    let nanos = counter as u128 * 1_000_000_000 / frequency as u128;
+
+    (counter, Duration::from_nanos(nanos as u64))
+}
@@ -0,0 +1,134 @@
+use std::{ffi::c_void, mem};
+
+use image::RgbaImage;
+use scopeguard::guard;
+use windows::Win32::{
+    Foundation::{GetLastError, POINT},
+    Graphics::Gdi::{
+        CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, GetDC, GetObjectW, PatBlt, ReleaseDC,
+        SelectObject, BITMAP, BLACKNESS, HBITMAP, ROP_CODE, WHITENESS,
+    },
+    UI::WindowsAndMessaging::{
+        DrawIconEx, GetCursorInfo, GetCursorPos, GetIconInfo, CURSORINFO, CURSOR_SHOWING,
+        DI_NORMAL, HICON, ICONINFO,
+    },
+};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::capture::{delete_bitmap_object, to_rgba_image};
+
+pub fn get_cursor_position() -> XCapResult<(i32, i32)> {
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point)? };
+
+    Ok((point.x, point.y))
+}
+
+fn get_bitmap_size(h_bitmap: HBITMAP) -> Option<(i32, i32)> {
+    let mut bitmap = BITMAP::default();
+
+    let result = unsafe {
+        GetObjectW(
+            h_bitmap.into(),
+            mem::size_of::<BITMAP>() as i32,
+            Some(&mut bitmap as *mut BITMAP as *mut c_void),
+        )
+    };
+
+    if result == 0 {
+        return None;
+    }
+
+    Some((bitmap.bmWidth, bitmap.bmHeight))
+}
+
+pub fn get_cursor_image() -> XCapResult<(RgbaImage, i32, i32)> {
+    unsafe {
+        let mut cursor_info = CURSORINFO {
+            cbSize: mem::size_of::<CURSORINFO>() as u32,
+            ..CURSORINFO::default()
+        };
+        GetCursorInfo(&mut cursor_info)?;
+
+        if cursor_info.flags != CURSOR_SHOWING {
+            return Err(XCapError::new("Cursor is hidden"));
+        }
+
+        let h_icon = HICON(cursor_info.hCursor.0);
+        let mut icon_info = ICONINFO::default();
+        GetIconInfo(h_icon, &mut icon_info)?;
+
+        // GetIconInfo 创建的位图需要调用方释放
+        let scope_guard_hbm_color = guard(icon_info.hbmColor, |val| {
+            if !val.is_invalid() {
+                delete_bitmap_object(val);
+            }
+        });
+        let scope_guard_hbm_mask = guard(icon_info.hbmMask, |val| {
+            if !val.is_invalid() {
+                delete_bitmap_object(val);
+            }
+        });
+
+        // 黑白光标的 mask 位图包含 AND 和 XOR 两部分，高度是光标的两倍
+        let (width, height) = match get_bitmap_size(*scope_guard_hbm_color) {
+            Some(size) => size,
+            None => get_bitmap_size(*scope_guard_hbm_mask)
+                .map(|(width, height)| (width, height / 2))
+                .ok_or_else(|| XCapError::new("Get cursor size failed"))?,
+        };
+
+        let scope_guard_hdc_screen = guard(GetDC(None), |val| {
+            if ReleaseDC(None, val) != 1 {
+                log::error!("ReleaseDC({:?}) failed: {:?}", val, GetLastError());
+            }
+        });
+        let scope_guard_hdc_mem = guard(CreateCompatibleDC(Some(*scope_guard_hdc_screen)), |val| {
+            if !DeleteDC(val).as_bool() {
+                log::error!("DeleteDC({:?}) failed: {:?}", val, GetLastError());
+            }
+        });
+        let scope_guard_h_bitmap = guard(
+            CreateCompatibleBitmap(*scope_guard_hdc_screen, width, height),
+            delete_bitmap_object,
+        );
+        SelectObject(*scope_guard_hdc_mem, (*scope_guard_h_bitmap).into());
+
+        // 分别在黑色和白色背景上绘制光标，通过两者的差值计算透明度，
+        // 这样彩色光标、黑白光标以及反色光标都能得到正确的结果
+        let draw_on = |background: ROP_CODE| -> XCapResult<RgbaImage> {
+            PatBlt(*scope_guard_hdc_mem, 0, 0, width, height, background).ok()?;
+            DrawIconEx(*scope_guard_hdc_mem, 0, 0, h_icon, 0, 0, 0, None, DI_NORMAL)?;
+
+            to_rgba_image(*scope_guard_hdc_mem, *scope_guard_h_bitmap, width, height)
+        };
+
+        let on_black = draw_on(BLACKNESS)?;
+        let on_white = draw_on(WHITENESS)?;
+
+        let mut image = RgbaImage::new(width as u32, height as u32);
+        for ((pixel, black), white) in image
+            .pixels_mut()
+            .zip(on_black.pixels())
+            .zip(on_white.pixels())
+        {
+            let difference = (0..3)
+                .map(|i| white[i] as u32 - (black[i] as u32).min(white[i] as u32))
+                .sum::<u32>()
+                / 3;
+            let alpha = 255 - difference.min(255);
+
+            if alpha == 0 {
+                continue;
+            }
+
+            for i in 0..3 {
+                pixel[i] = (black[i] as u32 * 255 / alpha).min(255) as u8;
+            }
+            pixel[3] = alpha as u8;
+        }
+
+        Ok((image, icon_info.xHotspot as i32, icon_info.yHotspot as i32))
+    }
+}
@@ -0,0 +1,91 @@
+use std::{marker::PhantomData, mem};
+
+use windows::{
+    core::{s, w},
+    Win32::System::LibraryLoader::GetProcAddress,
+};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::utils::load_library;
+
+// https://learn.microsoft.com/zh-cn/windows/win32/hidpi/dpi-awareness-context
+const DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2: isize = -4;
+
+// 定义 SetThreadDpiAwarenessContext 函数的类型，Windows 10 1607 之前没有这个函数
+type SetThreadDpiAwarenessContext = unsafe extern "system" fn(dpi_context: isize) -> isize;
+
+/// 返回线程之前的 DPI 感知上下文
+fn set_thread_dpi_awareness_context(dpi_context: isize) -> XCapResult<isize> {
+    unsafe {
+        let scope_guard_hmodule = load_library(w!("User32.dll"))?;
+
+        let set_thread_dpi_awareness_context_proc_address =
+            GetProcAddress(*scope_guard_hmodule, s!("SetThreadDpiAwarenessContext")).ok_or(
+                XCapError::new("GetProcAddress SetThreadDpiAwarenessContext failed"),
+            )?;
+
+        let set_thread_dpi_awareness_context: SetThreadDpiAwarenessContext =
+            mem::transmute(set_thread_dpi_awareness_context_proc_address);
+
+        // https://learn.microsoft.com/zh-cn/windows/win32/api/winuser/nf-winuser-setthreaddpiawarenesscontext
+        let previous_dpi_context = set_thread_dpi_awareness_context(dpi_context);
+        if previous_dpi_context == 0 {
+            return Err(XCapError::with_source(
+                "SetThreadDpiAwarenessContext failed",
+                windows::core::Error::from_win32(),
+            ));
+        }
+
+        Ok(previous_dpi_context)
+    }
+}
+
+/// Makes the current thread per-monitor DPI aware while the guard is alive, so monitor and
+/// window coordinates are real pixels even when the process isn't DPI aware. The previous
+/// awareness is restored on drop. Does nothing before Windows 10 1703.
+#[derive(Debug)]
+pub struct DpiAwarenessGuard {
+    previous_dpi_context: Option<isize>,
+    // DPI 感知是线程级别的，必须在创建的线程中恢复
+    _not_send: PhantomData<*const ()>,
+}
+
+impl DpiAwarenessGuard {
+    pub fn new() -> DpiAwarenessGuard {
+        let previous_dpi_context =
+            match set_thread_dpi_awareness_context(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) {
+                Ok(previous_dpi_context) => Some(previous_dpi_context),
+                Err(err) => {
+                    log::info!("set_thread_dpi_awareness_context failed: {}", err);
+                    None
+                }
+            };
+
+        DpiAwarenessGuard {
+            previous_dpi_context,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// 线程是否已设置为 per-monitor DPI 感知
+    pub(crate) fn is_active(&self) -> bool {
+        self.previous_dpi_context.is_some()
+    }
+}
+
+impl Default for DpiAwarenessGuard {
+    fn default() -> Self {
+        DpiAwarenessGuard::new()
+    }
+}
+
+impl Drop for DpiAwarenessGuard {
+    fn drop(&mut self) {
+        if let Some(previous_dpi_context) = self.previous_dpi_context {
+            if let Err(err) = set_thread_dpi_awareness_context(previous_dpi_context) {
+                log::error!("Restore thread DPI awareness context failed: {}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::slice;
+
+use image::RgbaImage;
+use windows::{
+    core::Interface,
+    Win32::{
+        Foundation::HMODULE,
+        Graphics::{
+            Direct3D::D3D_DRIVER_TYPE_HARDWARE,
+            Direct3D11::{
+                D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext, ID3D11Resource,
+                ID3D11Texture2D, D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+                D3D11_CREATE_DEVICE_SINGLETHREADED, D3D11_MAPPED_SUBRESOURCE, D3D11_MAP_READ,
+                D3D11_SDK_VERSION, D3D11_TEXTURE2D_DESC, D3D11_USAGE_STAGING,
+            },
+            Dxgi::{
+                IDXGIDevice, IDXGIOutput1, IDXGIOutputDuplication, IDXGIResource,
+                DXGI_ERROR_WAIT_TIMEOUT, DXGI_OUTDUPL_FRAME_INFO,
+            },
+            Gdi::HMONITOR,
+        },
+    },
+};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::utils::bgra_to_rgba_image;
+
+// 单次截图时等待新帧的次数和每次的超时时间（毫秒），桌面静止时不会产生新帧
+const ACQUIRE_FRAME_ATTEMPTS: u32 = 5;
+const ACQUIRE_FRAME_TIMEOUT: u32 = 100;
+
+/// 创建 D3D11 设备并复制显示器对应的 DXGI 输出
+pub(super) fn create_output_duplication(
+    h_monitor: HMONITOR,
+) -> XCapResult<(ID3D11Device, ID3D11DeviceContext, IDXGIOutputDuplication)> {
+    unsafe {
+        let mut d3d_device = None;
+        D3D11CreateDevice(
+            None,
+            D3D_DRIVER_TYPE_HARDWARE,
+            HMODULE::default(),
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT | D3D11_CREATE_DEVICE_SINGLETHREADED,
+            None,
+            D3D11_SDK_VERSION,
+            Some(&mut d3d_device),
+            None,
+            None,
+        )?;
+
+        let d3d_device = d3d_device.ok_or(XCapError::new("Call D3D11CreateDevice failed"))?;
+        let dxgi_device = d3d_device.cast::<IDXGIDevice>()?;
+        let d3d_context = d3d_device.GetImmediateContext()?;
+
+        let adapter = dxgi_device.GetAdapter()?;
+
+        // 显示器不在该适配器上时，EnumOutputs 返回 DXGI_ERROR_NOT_FOUND
+        let mut output_index = 0;
+        loop {
+            let output = adapter.EnumOutputs(output_index)?;
+            output_index += 1;
+
+            if output.GetDesc()?.Monitor != h_monitor {
+                continue;
+            }
+
+            let duplication = output
+                .cast::<IDXGIOutput1>()?
+                .DuplicateOutput(&dxgi_device)?;
+
+            return Ok((d3d_device, d3d_context, duplication));
+        }
+    }
+}
+
+/// 复制到 CPU 可读的纹理，按行读取，每行的字节数 RowPitch 可能大于宽度
+fn read_texture(
+    d3d_device: &ID3D11Device,
+    d3d_context: &ID3D11DeviceContext,
+    source_texture: &ID3D11Texture2D,
+) -> XCapResult<RgbaImage> {
+    unsafe {
+        let mut desc = D3D11_TEXTURE2D_DESC::default();
+        source_texture.GetDesc(&mut desc);
+        desc.BindFlags = 0;
+        desc.MiscFlags = 0;
+        desc.Usage = D3D11_USAGE_STAGING;
+        desc.CPUAccessFlags = D3D11_CPU_ACCESS_READ.0 as u32;
+
+        let mut staging_texture = None;
+        d3d_device.CreateTexture2D(&desc, None, Some(&mut staging_texture))?;
+        let staging_texture = staging_texture.ok_or(XCapError::new("CreateTexture2D failed"))?;
+
+        let resource: ID3D11Resource = staging_texture.cast()?;
+        d3d_context.CopyResource(Some(&resource), Some(&source_texture.cast()?));
+
+        let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+        d3d_context.Map(Some(&resource), 0, D3D11_MAP_READ, 0, Some(&mut mapped))?;
+
+        let data = slice::from_raw_parts(
+            mapped.pData.cast::<u8>(),
+            (desc.Height * mapped.RowPitch) as usize,
+        );
+        let mut buffer = Vec::with_capacity((desc.Width * desc.Height * 4) as usize);
+        for row in data.chunks_exact(mapped.RowPitch as usize) {
+            buffer.extend_from_slice(&row[..(desc.Width * 4) as usize]);
+        }
+
+        d3d_context.Unmap(Some(&resource), 0);
+
+        bgra_to_rgba_image(desc.Width, desc.Height, buffer)
+    }
+}
+
+/// 通过 DXGI Desktop Duplication 截取显示器，可以截取 GDI 截图为黑色的硬件加速画面
+pub(super) fn capture_monitor_dxgi(h_monitor: HMONITOR) -> XCapResult<RgbaImage> {
+    let (d3d_device, d3d_context, duplication) = create_output_duplication(h_monitor)?;
+
+    for _ in 0..ACQUIRE_FRAME_ATTEMPTS {
+        let mut frame_info = DXGI_OUTDUPL_FRAME_INFO::default();
+        let mut resource: Option<IDXGIResource> = None;
+
+        unsafe {
+            match duplication.AcquireNextFrame(
+                ACQUIRE_FRAME_TIMEOUT,
+                &mut frame_info,
+                &mut resource,
+            ) {
+                Ok(()) => {}
+                Err(err) if err.code() == DXGI_ERROR_WAIT_TIMEOUT => continue,
+                Err(err) => return Err(err.into()),
+            }
+
+            // 第一帧可能还没有桌面图像，LastPresentTime 为 0
+            let image = match resource {
+                Some(resource) if frame_info.LastPresentTime != 0 => {
+                    read_texture(&d3d_device, &d3d_context, &resource.cast()?)
+                }
+                _ => Err(XCapError::new("AcquireNextFrame returned no desktop image")),
+            };
+
+            duplication.ReleaseFrame()?;
+
+            if let Ok(image) = image {
+                return Ok(image);
+            }
+        }
+    }
+
+    Err(XCapError::new("DXGI returned no frame"))
+}
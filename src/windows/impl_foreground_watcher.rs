@@ -0,0 +1,100 @@
+use std::{cell::RefCell, sync::mpsc, thread};
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    System::Threading::GetCurrentThreadId,
+    UI::{
+        Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
+        WindowsAndMessaging::{
+            DispatchMessageW, GetMessageW, PostThreadMessageW, TranslateMessage,
+            EVENT_SYSTEM_FOREGROUND, MSG, OBJID_WINDOW, WINEVENT_OUTOFCONTEXT,
+            WINEVENT_SKIPOWNPROCESS, WM_QUIT,
+        },
+    },
+};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::impl_window::ImplWindow;
+
+type Callback = Box<dyn FnMut(ImplWindow) + Send>;
+
+thread_local! {
+    // WinEventProc 没有用户数据参数，回调保存在运行消息循环的线程中
+    static CALLBACK: RefCell<Option<Callback>> = RefCell::new(None);
+}
+
+unsafe extern "system" fn win_event_proc(
+    _: HWINEVENTHOOK,
+    event: u32,
+    hwnd: HWND,
+    id_object: i32,
+    _: i32,
+    _: u32,
+    _: u32,
+) {
+    if event != EVENT_SYSTEM_FOREGROUND || id_object != OBJID_WINDOW.0 {
+        return;
+    }
+
+    CALLBACK.with(|callback| {
+        if let Some(callback) = callback.borrow_mut().as_mut() {
+            callback(ImplWindow::new(hwnd));
+        }
+    });
+}
+
+#[derive(Debug)]
+pub struct ImplForegroundWatcher {
+    thread_id: u32,
+}
+
+impl ImplForegroundWatcher {
+    pub fn new(callback: Callback) -> XCapResult<Self> {
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || unsafe {
+            CALLBACK.with(|val| *val.borrow_mut() = Some(callback));
+
+            // WINEVENT_OUTOFCONTEXT 的回调通过当前线程的消息循环派发
+            let hook = SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                None,
+                Some(win_event_proc),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+            );
+
+            if hook.is_invalid() {
+                let _ = tx.send(Err(XCapError::new("SetWinEventHook failed")));
+                return;
+            }
+
+            let _ = tx.send(Ok(GetCurrentThreadId()));
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+
+            if !UnhookWinEvent(hook).as_bool() {
+                log::error!("UnhookWinEvent({:?}) failed", hook);
+            }
+        });
+
+        let thread_id = rx
+            .recv()
+            .map_err(|err| XCapError::with_source("Start foreground watcher failed", err))??;
+
+        Ok(ImplForegroundWatcher { thread_id })
+    }
+
+    pub fn stop(&self) -> XCapResult<()> {
+        unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0))? };
+
+        Ok(())
+    }
+}
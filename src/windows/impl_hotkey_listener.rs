@@ -0,0 +1,113 @@
+use std::{sync::mpsc, thread};
+
+use windows::Win32::{
+    Foundation::{LPARAM, WPARAM},
+    System::Threading::GetCurrentThreadId,
+    UI::{
+        Input::KeyboardAndMouse::{
+            RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL,
+            MOD_NOREPEAT, MOD_SHIFT, MOD_WIN, VIRTUAL_KEY, VK_F1, VK_F10, VK_F11, VK_F12, VK_F2,
+            VK_F3, VK_F4, VK_F5, VK_F6, VK_F7, VK_F8, VK_F9, VK_SNAPSHOT,
+        },
+        WindowsAndMessaging::{GetMessageW, PostThreadMessageW, MSG, WM_HOTKEY, WM_QUIT},
+    },
+};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    hotkey::{HotKey, HotKeyCode},
+};
+
+type Callback = Box<dyn FnMut() + Send>;
+
+const HOTKEY_ID: i32 = 1;
+
+fn get_virtual_key(code: HotKeyCode) -> VIRTUAL_KEY {
+    match code {
+        HotKeyCode::PrintScreen => VK_SNAPSHOT,
+        HotKeyCode::F1 => VK_F1,
+        HotKeyCode::F2 => VK_F2,
+        HotKeyCode::F3 => VK_F3,
+        HotKeyCode::F4 => VK_F4,
+        HotKeyCode::F5 => VK_F5,
+        HotKeyCode::F6 => VK_F6,
+        HotKeyCode::F7 => VK_F7,
+        HotKeyCode::F8 => VK_F8,
+        HotKeyCode::F9 => VK_F9,
+        HotKeyCode::F10 => VK_F10,
+        HotKeyCode::F11 => VK_F11,
+        HotKeyCode::F12 => VK_F12,
+    }
+}
+
+fn get_hotkey_modifiers(hotkey: HotKey) -> HOT_KEY_MODIFIERS {
+    // MOD_NOREPEAT 避免按住按键时重复触发
+    let mut modifiers = MOD_NOREPEAT;
+
+    if hotkey.modifiers.control {
+        modifiers |= MOD_CONTROL;
+    }
+    if hotkey.modifiers.alt {
+        modifiers |= MOD_ALT;
+    }
+    if hotkey.modifiers.shift {
+        modifiers |= MOD_SHIFT;
+    }
+    if hotkey.modifiers.meta {
+        modifiers |= MOD_WIN;
+    }
+
+    modifiers
+}
+
+#[derive(Debug)]
+pub struct ImplHotKeyListener {
+    thread_id: u32,
+}
+
+impl ImplHotKeyListener {
+    pub fn new(hotkey: HotKey, mut callback: Callback) -> XCapResult<Self> {
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || unsafe {
+            // 没有窗口时，WM_HOTKEY 消息发送到注册热键的线程的消息队列
+            if let Err(err) = RegisterHotKey(
+                None,
+                HOTKEY_ID,
+                get_hotkey_modifiers(hotkey),
+                get_virtual_key(hotkey.code).0 as u32,
+            ) {
+                let _ = tx.send(Err(XCapError::with_source(
+                    "RegisterHotKey failed, the hotkey may be used by another application",
+                    err,
+                )));
+                return;
+            }
+
+            let _ = tx.send(Ok(GetCurrentThreadId()));
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                if msg.message == WM_HOTKEY && msg.wParam.0 == HOTKEY_ID as usize {
+                    callback();
+                }
+            }
+
+            if let Err(err) = UnregisterHotKey(None, HOTKEY_ID) {
+                log::error!("UnregisterHotKey failed: {}", err);
+            }
+        });
+
+        let thread_id = rx
+            .recv()
+            .map_err(|err| XCapError::with_source("Start hotkey listener failed", err))??;
+
+        Ok(ImplHotKeyListener { thread_id })
+    }
+
+    pub fn stop(&self) -> XCapResult<()> {
+        unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0))? };
+
+        Ok(())
+    }
+}
@@ -1,43 +1,87 @@
-use std::{mem, ptr, sync::mpsc::Receiver};
+use std::{
+    ffi::c_void,
+    hash::{Hash, Hasher},
+    mem, ptr,
+    sync::mpsc::Receiver,
+};
 
 use image::RgbaImage;
 use scopeguard::guard;
 use widestring::U16CString;
 use windows::{
-    core::{s, w, HRESULT, PCWSTR},
+    core::{s, w, Interface, HRESULT, PCWSTR},
     Win32::{
-        Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL,
+        Devices::Display::{
+            DisplayConfigGetDeviceInfo, GetDisplayConfigBufferSizes, QueryDisplayConfig,
+            DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME, DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+            DISPLAYCONFIG_DEVICE_INFO_HEADER, DISPLAYCONFIG_MODE_INFO,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL, DISPLAYCONFIG_PATH_INFO,
+            DISPLAYCONFIG_SOURCE_DEVICE_NAME, DISPLAYCONFIG_TARGET_DEVICE_NAME, QDC_ALL_PATHS,
+        },
         Foundation::{GetLastError, BOOL, LPARAM, POINT, RECT, TRUE},
+        Graphics::Dxgi::{
+            Common::DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020, CreateDXGIFactory1, IDXGIFactory1,
+            IDXGIOutput, IDXGIOutput6,
+        },
         Graphics::Gdi::{
-            CreateDCW, DeleteDC, EnumDisplayMonitors, EnumDisplaySettingsW, GetDeviceCaps,
-            GetMonitorInfoW, MonitorFromPoint, DESKTOPHORZRES, DEVMODEW, DMDO_180, DMDO_270,
-            DMDO_90, DMDO_DEFAULT, ENUM_CURRENT_SETTINGS, HDC, HMONITOR, HORZRES, MONITORINFO,
-            MONITORINFOEXW, MONITOR_DEFAULTTONULL,
+            CreateDCW, DeleteDC, EnumDisplayMonitors, EnumDisplaySettingsExW, EnumDisplaySettingsW,
+            GetDeviceCaps, GetMonitorInfoW, MonitorFromPoint, DESKTOPHORZRES, DEVMODEW, DMDO_180,
+            DMDO_270, DMDO_90, DMDO_DEFAULT, ENUM_CURRENT_SETTINGS, ENUM_DISPLAY_SETTINGS_FLAGS,
+            ENUM_DISPLAY_SETTINGS_MODE, HDC, HMONITOR, HORZRES, MONITORINFO, MONITORINFOEXW,
+            MONITOR_DEFAULTTONULL,
         },
         System::{LibraryLoader::GetProcAddress, Threading::GetCurrentProcess},
-        UI::WindowsAndMessaging::MONITORINFOF_PRIMARY,
+        UI::{
+            ColorSystem::{GetDeviceGammaRamp, SetDeviceGammaRamp},
+            WindowsAndMessaging::{
+                GetSystemMetrics, MONITORINFOF_PRIMARY, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
+                SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+            },
+        },
     },
 };
 
 use crate::{
+    cancellation_token::CancellationToken,
+    capture_config::Backend,
+    capture_quality::CaptureQuality,
+    display_mode::DisplayMode,
+    edid::parse_edid_id,
     error::{XCapError, XCapResult},
+    frame_time::Acceleration,
+    gamma_ramp::GammaRamp,
+    native_image::{NativeImage, PixelFormat},
+    raw_handle::RawMonitorHandle,
+    rect::Rect,
+    scale_factor::{get_scale_factor_override, ScaleFactorSource},
     video_recorder::Frame,
 };
 
 use super::{
-    capture::capture_monitor,
+    capture::{capture_monitor, capture_monitor_bgra, capture_monitor_scaled},
+    dpi_awareness::DpiAwarenessGuard,
+    dxgi_capture::capture_monitor_dxgi,
     impl_video_recorder::ImplVideoRecorder,
-    utils::{get_monitor_config, get_process_is_dpi_awareness, load_library},
+    utils::{
+        get_monitor_config, get_monitor_edid, get_process_is_dpi_awareness, is_secure_desktop,
+        load_library, run_on_input_desktop,
+    },
 };
 
 // A 函数与 W 函数区别
 // https://learn.microsoft.com/zh-cn/windows/win32/learnwin32/working-with-strings
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct ImplMonitor {
     pub h_monitor: HMONITOR,
 }
 
+impl Hash for ImplMonitor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.h_monitor.0 as isize).hash(state);
+    }
+}
+
 extern "system" fn monitor_enum_proc(
     h_monitor: HMONITOR,
     _: HDC,
@@ -45,7 +89,8 @@ extern "system" fn monitor_enum_proc(
     state: LPARAM,
 ) -> BOOL {
     unsafe {
-        let state = Box::leak(Box::from_raw(state.0 as *mut Vec<HMONITOR>));
+        // state 指向调用方栈上的 Vec，这里只借用，不获取所有权
+        let state = &mut *(state.0 as *mut Vec<HMONITOR>);
         state.push(h_monitor);
 
         TRUE
@@ -78,6 +123,9 @@ fn get_dev_mode_w(h_monitor: HMONITOR) -> XCapResult<DEVMODEW> {
     Ok(dev_mode_w)
 }
 
+// windows 0.59 没有导出 DISPLAYCONFIG_PATH_ACTIVE
+const DISPLAYCONFIG_PATH_ACTIVE: u32 = 0x1;
+
 // 定义 GetDpiForMonitor 函数的类型
 type GetDpiForMonitor = unsafe extern "system" fn(
     h_monitor: HMONITOR,
@@ -115,9 +163,9 @@ fn get_hi_dpi_scale_factor(h_monitor: HMONITOR) -> XCapResult<f32> {
     }
 }
 
-fn get_scale_factor(h_monitor: HMONITOR) -> XCapResult<f32> {
+fn get_scale_factor(h_monitor: HMONITOR) -> XCapResult<(f32, ScaleFactorSource)> {
     let scale_factor = match get_hi_dpi_scale_factor(h_monitor) {
-        Ok(val) => val,
+        Ok(val) => (val, ScaleFactorSource::Detected),
         Err(err) => {
             log::info!("get_hi_dpi_scale_factor failed: {}", err);
             let monitor_info_ex_w = get_monitor_info_ex_w(h_monitor)?;
@@ -140,7 +188,10 @@ fn get_scale_factor(h_monitor: HMONITOR) -> XCapResult<f32> {
                 let physical_width = GetDeviceCaps(Some(*scope_guard_hdc), DESKTOPHORZRES);
                 let logical_width = GetDeviceCaps(Some(*scope_guard_hdc), HORZRES);
 
-                physical_width as f32 / logical_width as f32
+                (
+                    physical_width as f32 / logical_width as f32,
+                    ScaleFactorSource::Fallback,
+                )
             }
         }
     };
@@ -148,23 +199,91 @@ fn get_scale_factor(h_monitor: HMONITOR) -> XCapResult<f32> {
     Ok(scale_factor)
 }
 
+fn find_impl_monitor_by_path(
+    impl_monitors: &[ImplMonitor],
+    path: &DISPLAYCONFIG_PATH_INFO,
+) -> Option<ImplMonitor> {
+    let mut source = DISPLAYCONFIG_SOURCE_DEVICE_NAME {
+        header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
+            r#type: DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+            size: mem::size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32,
+            adapterId: path.sourceInfo.adapterId,
+            id: path.sourceInfo.id,
+        },
+        ..DISPLAYCONFIG_SOURCE_DEVICE_NAME::default()
+    };
+
+    if unsafe { DisplayConfigGetDeviceInfo(&mut source.header) } != 0 {
+        return None;
+    }
+
+    impl_monitors
+        .iter()
+        .find(|impl_monitor| {
+            get_monitor_info_ex_w(impl_monitor.h_monitor)
+                .map(|monitor_info_ex_w| monitor_info_ex_w.szDevice == source.viewGdiDeviceName)
+                .unwrap_or(false)
+        })
+        .cloned()
+}
+
+fn get_dxgi_output(h_monitor: HMONITOR) -> XCapResult<IDXGIOutput> {
+    unsafe {
+        let factory = CreateDXGIFactory1::<IDXGIFactory1>()?;
+
+        // 枚举结束时返回 DXGI_ERROR_NOT_FOUND
+        let mut adapter_index = 0;
+        while let Ok(adapter) = factory.EnumAdapters1(adapter_index) {
+            let mut output_index = 0;
+            while let Ok(output) = adapter.EnumOutputs(output_index) {
+                if output.GetDesc()?.Monitor == h_monitor {
+                    return Ok(output);
+                }
+                output_index += 1;
+            }
+            adapter_index += 1;
+        }
+    }
+
+    Err(XCapError::new("Not found DXGI output of the monitor"))
+}
+
 impl ImplMonitor {
     pub fn new(h_monitor: HMONITOR) -> ImplMonitor {
         ImplMonitor { h_monitor }
     }
 
+    pub fn from_raw_handle(raw_handle: RawMonitorHandle) -> XCapResult<ImplMonitor> {
+        let RawMonitorHandle::Win32 { h_monitor } = raw_handle else {
+            return Err(XCapError::new("Not a Win32 monitor handle"));
+        };
+
+        let h_monitor = HMONITOR(h_monitor as *mut c_void);
+        // 校验句柄是否有效
+        get_monitor_info_ex_w(h_monitor)?;
+
+        Ok(ImplMonitor::new(h_monitor))
+    }
+
+    pub fn raw_handle(&self) -> RawMonitorHandle {
+        RawMonitorHandle::Win32 {
+            h_monitor: self.h_monitor.0 as isize,
+        }
+    }
+
     pub fn all() -> XCapResult<Vec<ImplMonitor>> {
-        let hmonitors_mut_ptr: *mut Vec<HMONITOR> = Box::into_raw(Box::default());
+        // h_monitors 由当前函数持有，即使 EnumDisplayMonitors 失败提前返回也会被正常释放
+        // HMONITOR 不需要手动释放
+        let mut h_monitors: Vec<HMONITOR> = Vec::new();
 
-        let h_monitors = unsafe {
+        unsafe {
             EnumDisplayMonitors(
                 None,
                 None,
                 Some(monitor_enum_proc),
-                LPARAM(hmonitors_mut_ptr as isize),
+                LPARAM(&mut h_monitors as *mut Vec<HMONITOR> as isize),
             )
             .ok()?;
-            Box::from_raw(hmonitors_mut_ptr)
         };
 
         let mut impl_monitors = Vec::with_capacity(h_monitors.len());
@@ -176,6 +295,102 @@ impl ImplMonitor {
         Ok(impl_monitors)
     }
 
+    pub fn all_including_inactive() -> XCapResult<Vec<(String, bool, Option<ImplMonitor>)>> {
+        let mut number_of_paths = 0;
+        let mut number_of_modes = 0;
+
+        unsafe {
+            GetDisplayConfigBufferSizes(QDC_ALL_PATHS, &mut number_of_paths, &mut number_of_modes)
+                .ok()?;
+        }
+
+        let mut paths = vec![DISPLAYCONFIG_PATH_INFO::default(); number_of_paths as usize];
+        let mut modes = vec![DISPLAYCONFIG_MODE_INFO::default(); number_of_modes as usize];
+
+        unsafe {
+            QueryDisplayConfig(
+                QDC_ALL_PATHS,
+                &mut number_of_paths,
+                paths.as_mut_ptr(),
+                &mut number_of_modes,
+                modes.as_mut_ptr(),
+                None,
+            )
+            .ok()?;
+        }
+
+        paths.truncate(number_of_paths as usize);
+
+        let active_impl_monitors = ImplMonitor::all()?;
+        let mut targets = Vec::new();
+        let mut known_monitors = Vec::new();
+
+        // QDC_ALL_PATHS 会返回所有 source 与 target 的组合，活动的 path 排在前面，
+        // 每个 target 只保留第一个 path
+        for path in paths {
+            let target_key = (
+                path.targetInfo.adapterId.LowPart,
+                path.targetInfo.adapterId.HighPart,
+                path.targetInfo.id,
+            );
+            if targets.contains(&target_key) {
+                continue;
+            }
+            targets.push(target_key);
+
+            let mut target = DISPLAYCONFIG_TARGET_DEVICE_NAME {
+                header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
+                    r#type: DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+                    size: mem::size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as u32,
+                    adapterId: path.targetInfo.adapterId,
+                    id: path.targetInfo.id,
+                },
+                ..DISPLAYCONFIG_TARGET_DEVICE_NAME::default()
+            };
+
+            if unsafe { DisplayConfigGetDeviceInfo(&mut target.header) } != 0 {
+                continue;
+            }
+
+            let name =
+                U16CString::from_vec_truncate(target.monitorFriendlyDeviceName).to_string()?;
+
+            // 没有名称的 target 是显卡上从未连接过显示器的接口
+            if name.is_empty() {
+                continue;
+            }
+
+            let is_active = path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0;
+            let impl_monitor = if is_active {
+                find_impl_monitor_by_path(&active_impl_monitors, &path)
+            } else {
+                None
+            };
+
+            known_monitors.push((name, is_active, impl_monitor));
+        }
+
+        Ok(known_monitors)
+    }
+
+    pub fn virtual_desktop_bounds() -> XCapResult<Rect> {
+        let (x, y, width, height) = unsafe {
+            (
+                GetSystemMetrics(SM_XVIRTUALSCREEN),
+                GetSystemMetrics(SM_YVIRTUALSCREEN),
+                GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            )
+        };
+
+        // 获取失败时返回 0
+        if width == 0 || height == 0 {
+            return Err(XCapError::new("GetSystemMetrics SM_CXVIRTUALSCREEN failed"));
+        }
+
+        Ok(Rect::new(x, y, width as u32, height as u32))
+    }
+
     pub fn from_point(x: i32, y: i32) -> XCapResult<ImplMonitor> {
         let point = POINT { x, y };
         let h_monitor = unsafe { MonitorFromPoint(point, MONITOR_DEFAULTTONULL) };
@@ -196,21 +411,41 @@ impl ImplMonitor {
     pub fn name(&self) -> XCapResult<String> {
         let monitor_info_ex_w = get_monitor_info_ex_w(self.h_monitor)?;
 
-        let config_default_name = format!("Unknown Monitor {}", self.h_monitor.0 as u32);
+        // 优先使用 DISPLAYCONFIG_TARGET_DEVICE_NAME 中的显示器型号，获取失败时回退到设备路径，例如 \\.\DISPLAY1
+        let device_name = U16CString::from_vec_truncate(monitor_info_ex_w.szDevice).to_string()?;
         let config = match get_monitor_config(monitor_info_ex_w) {
             Ok(config) => config,
-            Err(_) => return Ok(config_default_name),
+            Err(err) => {
+                log::info!("get_monitor_config failed: {}", err);
+                return Ok(device_name);
+            }
         };
 
         let name = U16CString::from_vec_truncate(config.monitorFriendlyDeviceName).to_string()?;
 
         if name.is_empty() {
-            return Ok(config_default_name);
+            return Ok(device_name);
         }
 
         Ok(name)
     }
 
+    pub fn connector(&self) -> XCapResult<String> {
+        let monitor_info_ex_w = get_monitor_info_ex_w(self.h_monitor)?;
+        let config = get_monitor_config(monitor_info_ex_w)?;
+
+        // monitorDevicePath 包含显示器的硬件 id 和所在接口，重启后保持不变
+        let connector = U16CString::from_vec_truncate(config.monitorDevicePath).to_string()?;
+
+        Ok(connector)
+    }
+
+    pub fn edid_id(&self) -> XCapResult<Option<String>> {
+        let edid = get_monitor_edid(&self.connector()?)?;
+
+        Ok(parse_edid_id(&edid))
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         let dev_mode_w = get_dev_mode_w(self.h_monitor)?;
         let dm_position = unsafe { dev_mode_w.Anonymous1.Anonymous2.dmPosition };
@@ -249,8 +484,44 @@ impl ImplMonitor {
         Ok(rotation)
     }
 
+    pub fn bits_per_pixel(&self) -> XCapResult<u32> {
+        let dev_mode_w = get_dev_mode_w(self.h_monitor)?;
+
+        Ok(dev_mode_w.dmBitsPerPel)
+    }
+
+    pub fn is_hdr(&self) -> XCapResult<bool> {
+        // IDXGIOutput6 需要 Windows 10 1703 及以上版本
+        let output6 = get_dxgi_output(self.h_monitor)?.cast::<IDXGIOutput6>()?;
+        let output_desc1 = unsafe { output6.GetDesc1()? };
+
+        // 开启 HDR 后输出使用 HDR10 色彩空间（ST.2084 + BT.2020）
+        Ok(output_desc1.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020)
+    }
+
     pub fn scale_factor(&self) -> XCapResult<f32> {
-        get_scale_factor(self.h_monitor)
+        if let Some(scale_factor) = get_scale_factor_override(self.id()?) {
+            return Ok(scale_factor);
+        }
+
+        let (scale_factor, _) = get_scale_factor(self.h_monitor)?;
+
+        Ok(scale_factor)
+    }
+
+    pub fn pixel_scale(&self) -> XCapResult<f32> {
+        // 坐标就是物理像素
+        Ok(1.0)
+    }
+
+    pub fn scale_factor_source(&self) -> XCapResult<ScaleFactorSource> {
+        if get_scale_factor_override(self.id()?).is_some() {
+            return Ok(ScaleFactorSource::Override);
+        }
+
+        let (_, scale_factor_source) = get_scale_factor(self.h_monitor)?;
+
+        Ok(scale_factor_source)
     }
 
     pub fn frequency(&self) -> XCapResult<f32> {
@@ -270,7 +541,154 @@ impl ImplMonitor {
         Ok(config.outputTechnology == DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL)
     }
 
+    pub fn available_modes(&self) -> XCapResult<Vec<DisplayMode>> {
+        let monitor_info_exw = get_monitor_info_ex_w(self.h_monitor)?;
+        let sz_device = monitor_info_exw.szDevice.as_ptr();
+
+        let mut modes = Vec::new();
+        let mut mode_num = 0;
+
+        loop {
+            let mut dev_mode_w = DEVMODEW {
+                dmSize: mem::size_of::<DEVMODEW>() as u16,
+                ..DEVMODEW::default()
+            };
+
+            // iModeNum 从 0 开始递增，超出范围时返回 FALSE
+            let has_mode = unsafe {
+                EnumDisplaySettingsExW(
+                    PCWSTR(sz_device),
+                    ENUM_DISPLAY_SETTINGS_MODE(mode_num),
+                    &mut dev_mode_w,
+                    ENUM_DISPLAY_SETTINGS_FLAGS(0),
+                )
+                .as_bool()
+            };
+
+            if !has_mode {
+                break;
+            }
+
+            let mode = DisplayMode {
+                width: dev_mode_w.dmPelsWidth,
+                height: dev_mode_w.dmPelsHeight,
+                refresh_rate: dev_mode_w.dmDisplayFrequency as f32,
+                bit_depth: dev_mode_w.dmBitsPerPel,
+            };
+
+            // 同一分辨率会因缩放方式等字段不同而重复出现
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+
+            mode_num += 1;
+        }
+
+        Ok(modes)
+    }
+
+    pub fn gamma_ramp(&self) -> XCapResult<GammaRamp> {
+        let monitor_info_ex_w = get_monitor_info_ex_w(self.h_monitor)?;
+
+        unsafe {
+            let scope_guard_hdc = guard(
+                CreateDCW(
+                    PCWSTR(monitor_info_ex_w.szDevice.as_ptr()),
+                    PCWSTR(monitor_info_ex_w.szDevice.as_ptr()),
+                    PCWSTR(ptr::null()),
+                    None,
+                ),
+                |val| {
+                    if !DeleteDC(val).as_bool() {
+                        log::error!("DeleteDC({:?}) failed: {:?}", val, GetLastError());
+                    }
+                },
+            );
+
+            // 依次为红、绿、蓝三个通道，每个通道 256 项
+            let mut ramp = [[0u16; 256]; 3];
+            GetDeviceGammaRamp(*scope_guard_hdc, ramp.as_mut_ptr().cast()).ok()?;
+
+            Ok(GammaRamp {
+                red: ramp[0].to_vec(),
+                green: ramp[1].to_vec(),
+                blue: ramp[2].to_vec(),
+            })
+        }
+    }
+
+    pub fn set_gamma_ramp(&self, gamma_ramp: &GammaRamp) -> XCapResult<()> {
+        if gamma_ramp.red.len() != 256 {
+            return Err(XCapError::new(
+                "SetDeviceGammaRamp needs 256 entries per channel",
+            ));
+        }
+
+        let monitor_info_ex_w = get_monitor_info_ex_w(self.h_monitor)?;
+
+        let mut ramp = [[0u16; 256]; 3];
+        ramp[0].copy_from_slice(&gamma_ramp.red);
+        ramp[1].copy_from_slice(&gamma_ramp.green);
+        ramp[2].copy_from_slice(&gamma_ramp.blue);
+
+        unsafe {
+            let scope_guard_hdc = guard(
+                CreateDCW(
+                    PCWSTR(monitor_info_ex_w.szDevice.as_ptr()),
+                    PCWSTR(monitor_info_ex_w.szDevice.as_ptr()),
+                    PCWSTR(ptr::null()),
+                    None,
+                ),
+                |val| {
+                    if !DeleteDC(val).as_bool() {
+                        log::error!("DeleteDC({:?}) failed: {:?}", val, GetLastError());
+                    }
+                },
+            );
+
+            // 驱动会拒绝偏离线性过大的 ramp
+            if !SetDeviceGammaRamp(*scope_guard_hdc, ramp.as_ptr().cast()).as_bool() {
+                return Err(XCapError::new(
+                    "SetDeviceGammaRamp failed, the ramp may be too far from the identity",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn work_area(&self) -> XCapResult<Rect> {
+        let monitor_info_ex_w = get_monitor_info_ex_w(self.h_monitor)?;
+        let rc_work = monitor_info_ex_w.monitorInfo.rcWork;
+
+        Ok(Rect::new(
+            rc_work.left,
+            rc_work.top,
+            (rc_work.right - rc_work.left) as u32,
+            (rc_work.bottom - rc_work.top) as u32,
+        ))
+    }
+
+    pub fn is_secure_desktop(&self) -> XCapResult<bool> {
+        Ok(is_secure_desktop())
+    }
+
+    pub fn has_capture_permission(&self) -> bool {
+        true
+    }
+
+    pub fn request_capture_permission(&self) -> XCapResult<()> {
+        Ok(())
+    }
+
+    pub fn forget_capture_permission(&self) -> XCapResult<()> {
+        Ok(())
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
+        // 进程不感知 DPI 时获取到的是虚拟化的坐标，截图的大小和位置会出错
+        let _dpi_awareness_guard = DpiAwarenessGuard::new();
+
         let x = self.x()?;
         let y = self.y()?;
         let width = self.width()?;
@@ -279,7 +697,158 @@ impl ImplMonitor {
         capture_monitor(x, y, width as i32, height as i32)
     }
 
+    pub fn capture_image_with_acceleration(&self) -> XCapResult<(RgbaImage, Acceleration)> {
+        // 显示器截图总是使用 GDI
+        Ok((self.capture_image()?, Acceleration::Software))
+    }
+
+    pub fn wait_for_vblank(&self) -> XCapResult<()> {
+        let output = get_dxgi_output(self.h_monitor)?;
+
+        unsafe { output.WaitForVBlank()? };
+
+        Ok(())
+    }
+
+    pub fn capture_image_cancellable(&self, token: &CancellationToken) -> XCapResult<RgbaImage> {
+        // GDI 截图不会阻塞，只在开始前和返回前检查
+        token.check()?;
+        let image = self.capture_image()?;
+        token.check()?;
+
+        Ok(image)
+    }
+
+    pub fn capture_backends(&self) -> Vec<(&'static str, CaptureQuality)> {
+        vec![
+            ("GDI BitBlt", CaptureQuality::Balanced),
+            ("GDI StretchBlt", CaptureQuality::Fast),
+        ]
+    }
+
+    pub fn default_backend_order() -> Vec<Backend> {
+        vec![Backend::GdiBitBlt, Backend::Dxgi]
+    }
+
+    pub fn capture_image_with_backend(&self, backend: Backend) -> XCapResult<RgbaImage> {
+        match backend {
+            Backend::GdiBitBlt => self.capture_image(),
+            Backend::Dxgi => capture_monitor_dxgi(self.h_monitor),
+            _ => Err(XCapError::Unsupported(format!(
+                "{:?} is not available on Windows",
+                backend
+            ))),
+        }
+    }
+
+    pub fn capture_image_with_quality(&self, quality: CaptureQuality) -> XCapResult<RgbaImage> {
+        match quality {
+            CaptureQuality::Fast => {
+                // 缩放到逻辑分辨率，StretchBlt 复制时缩放，不会生成全分辨率的图像
+                let scale_factor = self.scale_factor()?;
+                if scale_factor <= 1.0 {
+                    return self.capture_image();
+                }
+
+                let width = self.width()?;
+                let height = self.height()?;
+
+                self.capture_scaled(
+                    ((width as f32 / scale_factor).round() as u32).max(1),
+                    ((height as f32 / scale_factor).round() as u32).max(1),
+                )
+            }
+            // GDI 复制的是合成后的 sRGB 桌面，没有更高保真的方式
+            CaptureQuality::Balanced | CaptureQuality::HighFidelity => self.capture_image(),
+        }
+    }
+
+    pub fn capture_image_native(&self) -> XCapResult<NativeImage> {
+        let _dpi_awareness_guard = DpiAwarenessGuard::new();
+
+        let width = self.width()?;
+        let height = self.height()?;
+        let buffer = capture_monitor_bgra(self.x()?, self.y()?, width as i32, height as i32)?;
+
+        Ok(NativeImage::new(width, height, PixelFormat::Bgra8, buffer))
+    }
+
+    pub fn capture_secure_desktop(&self) -> XCapResult<RgbaImage> {
+        let _dpi_awareness_guard = DpiAwarenessGuard::new();
+
+        let x = self.x()?;
+        let y = self.y()?;
+        let width = self.width()?;
+        let height = self.height()?;
+
+        run_on_input_desktop(|| capture_monitor(x, y, width as i32, height as i32))
+    }
+
+    pub fn capture_area(&self, area: Rect) -> XCapResult<RgbaImage> {
+        let _dpi_awareness_guard = DpiAwarenessGuard::new();
+
+        // right/bottom 是饱和计算的，超出 i32 范围的区域也会被裁剪到显示器内
+        let monitor = Rect::new(self.x()?, self.y()?, self.width()?, self.height()?);
+        let area = monitor
+            .clamp_relative(&area)
+            .ok_or_else(|| XCapError::new("Capture area is outside the monitor"))?;
+
+        capture_monitor(area.x, area.y, area.width as i32, area.height as i32)
+    }
+
+    pub fn capture_area_scaled(
+        &self,
+        area: Rect,
+        width: u32,
+        height: u32,
+    ) -> XCapResult<RgbaImage> {
+        let _dpi_awareness_guard = DpiAwarenessGuard::new();
+
+        let monitor = Rect::new(self.x()?, self.y()?, self.width()?, self.height()?);
+        let area = monitor
+            .clamp_relative(&area)
+            .ok_or_else(|| XCapError::new("Capture area is outside the monitor"))?;
+
+        capture_monitor_scaled(
+            area.x,
+            area.y,
+            area.width as i32,
+            area.height as i32,
+            width as i32,
+            height as i32,
+        )
+    }
+
+    pub fn capture_scaled(&self, width: u32, height: u32) -> XCapResult<RgbaImage> {
+        let _dpi_awareness_guard = DpiAwarenessGuard::new();
+
+        capture_monitor_scaled(
+            self.x()?,
+            self.y()?,
+            self.width()? as i32,
+            self.height()? as i32,
+            width as i32,
+            height as i32,
+        )
+    }
+
     pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
         ImplVideoRecorder::new(self.h_monitor)
     }
 }
+
+#[test]
+fn monitor_enum_proc_borrows_caller_state() {
+    let mut h_monitors: Vec<HMONITOR> = Vec::new();
+    let state = LPARAM(&mut h_monitors as *mut Vec<HMONITOR> as isize);
+
+    for i in 1..=3 {
+        let h_monitor = HMONITOR(i as *mut std::ffi::c_void);
+        let result = monitor_enum_proc(h_monitor, HDC::default(), std::ptr::null_mut(), state);
+        assert_eq!(result, TRUE);
+    }
+
+    // 回调只借用 Vec，调用结束后仍由调用方持有并释放
+    assert_eq!(h_monitors.len(), 3);
+    assert_eq!(h_monitors[2], HMONITOR(3 as *mut std::ffi::c_void));
+}
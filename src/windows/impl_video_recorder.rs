@@ -9,22 +9,16 @@ use std::{
 
 use windows::{
     core::Interface,
-    Win32::{
-        Foundation::HMODULE,
-        Graphics::{
-            Direct3D::D3D_DRIVER_TYPE_HARDWARE,
-            Direct3D11::{
-                D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext, ID3D11Resource,
-                ID3D11Texture2D, D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT,
-                D3D11_CREATE_DEVICE_SINGLETHREADED, D3D11_MAPPED_SUBRESOURCE, D3D11_MAP_READ,
-                D3D11_SDK_VERSION, D3D11_TEXTURE2D_DESC, D3D11_USAGE_STAGING,
-            },
-            Dxgi::{
-                IDXGIDevice, IDXGIOutput1, IDXGIOutputDuplication, IDXGIResource,
-                DXGI_ERROR_WAIT_TIMEOUT, DXGI_OUTDUPL_FRAME_INFO,
-            },
-            Gdi::HMONITOR,
+    Win32::Graphics::{
+        Direct3D11::{
+            ID3D11Device, ID3D11DeviceContext, ID3D11Resource, ID3D11Texture2D,
+            D3D11_CPU_ACCESS_READ, D3D11_MAPPED_SUBRESOURCE, D3D11_MAP_READ, D3D11_TEXTURE2D_DESC,
+            D3D11_USAGE_STAGING,
         },
+        Dxgi::{
+            IDXGIOutputDuplication, IDXGIResource, DXGI_ERROR_WAIT_TIMEOUT, DXGI_OUTDUPL_FRAME_INFO,
+        },
+        Gdi::HMONITOR,
     },
 };
 
@@ -33,7 +27,7 @@ use crate::{
     XCapError, XCapResult,
 };
 
-use super::utils::bgra_to_rgba;
+use super::{dxgi_capture::create_output_duplication, utils::bgra_to_rgba};
 
 pub fn texture_to_frame(
     d3d_device: &ID3D11Device,
@@ -93,49 +87,19 @@ pub struct ImplVideoRecorder {
 
 impl ImplVideoRecorder {
     pub fn new(h_monitor: HMONITOR) -> XCapResult<(Self, Receiver<Frame>)> {
-        unsafe {
-            let mut d3d_device = None;
-            D3D11CreateDevice(
-                None,
-                D3D_DRIVER_TYPE_HARDWARE,
-                HMODULE::default(),
-                D3D11_CREATE_DEVICE_BGRA_SUPPORT | D3D11_CREATE_DEVICE_SINGLETHREADED,
-                None,
-                D3D11_SDK_VERSION,
-                Some(&mut d3d_device),
-                None,
-                None,
-            )?;
-
-            let d3d_device = d3d_device.ok_or(XCapError::new("Call D3D11CreateDevice failed"))?;
-            let dxgi_device = d3d_device.cast::<IDXGIDevice>()?;
-            let d3d_context = d3d_device.GetImmediateContext()?;
-
-            let adapter = dxgi_device.GetAdapter()?;
-
-            let mut output_index = 0;
-            loop {
-                let output = adapter.EnumOutputs(output_index)?;
-                output_index += 1;
-                let output_desc = output.GetDesc()?;
-
-                let output1 = output.cast::<IDXGIOutput1>()?;
-                let duplication = output1.DuplicateOutput(&dxgi_device)?;
-
-                if output_desc.Monitor == h_monitor {
-                    let (tx, sx) = sync_channel(0);
-                    let s = Self {
-                        d3d_device,
-                        d3d_context,
-                        duplication,
-                        recorder_waker: Arc::new(RecorderWaker::new()),
-                        tx,
-                    };
-                    s.on_frame()?;
-                    return Ok((s, sx));
-                }
-            }
-        }
+        let (d3d_device, d3d_context, duplication) = create_output_duplication(h_monitor)?;
+
+        let (tx, sx) = sync_channel(0);
+        let s = Self {
+            d3d_device,
+            d3d_context,
+            duplication,
+            recorder_waker: Arc::new(RecorderWaker::new()),
+            tx,
+        };
+        s.on_frame()?;
+
+        Ok((s, sx))
     }
 
     pub fn on_frame(&self) -> XCapResult<()> {
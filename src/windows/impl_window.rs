@@ -1,44 +1,147 @@
 use core::slice;
-use std::{ffi::c_void, mem, ptr};
+use std::{
+    collections::VecDeque,
+    ffi::c_void,
+    hash::{Hash, Hasher},
+    mem,
+    path::Path,
+    ptr,
+    sync::Mutex,
+};
 
-use image::RgbaImage;
+use image::{imageops::crop_imm, RgbaImage};
 use widestring::U16CString;
 use windows::{
-    core::{HSTRING, PCWSTR},
+    core::{HSTRING, PCWSTR, PWSTR},
     Win32::{
-        Foundation::{GetLastError, BOOL, HANDLE, HWND, LPARAM, MAX_PATH, RECT, TRUE},
+        Foundation::{GetLastError, BOOL, HANDLE, HWND, LPARAM, MAX_PATH, POINT, RECT, TRUE},
         Graphics::{
             Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS},
-            Gdi::{IsRectEmpty, MonitorFromWindow, MONITOR_DEFAULTTONEAREST},
+            Gdi::{IsRectEmpty, MonitorFromWindow, ScreenToClient, MONITOR_DEFAULTTONEAREST},
         },
         Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW},
         System::{
             ProcessStatus::{GetModuleBaseNameW, GetModuleFileNameExW},
             Threading::{
-                GetCurrentProcess, GetCurrentProcessId, PROCESS_QUERY_LIMITED_INFORMATION,
+                GetCurrentProcess, GetCurrentProcessId, QueryFullProcessImageNameW,
+                PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
             },
         },
         UI::WindowsAndMessaging::{
-            EnumWindows, GetClassNameW, GetForegroundWindow, GetWindowLongPtrW,
-            GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow,
-            IsWindowVisible, IsZoomed, GWL_EXSTYLE, WINDOW_EX_STYLE, WS_EX_TOOLWINDOW,
+            ChildWindowFromPointEx, EnumWindows, GetClassNameW, GetForegroundWindow,
+            GetLayeredWindowAttributes, GetSystemMetrics, GetWindowDisplayAffinity,
+            GetWindowLongPtrW, GetWindowRect, GetWindowTextLengthW, GetWindowTextW,
+            GetWindowThreadProcessId, IsIconic, IsWindow, IsWindowVisible, IsZoomed,
+            SetForegroundWindow, ShowWindow, CWP_SKIPINVISIBLE, CWP_SKIPTRANSPARENT, GWL_EXSTYLE,
+            LAYERED_WINDOW_ATTRIBUTES_FLAGS, LWA_ALPHA, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
+            SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, SW_RESTORE, WDA_NONE, WINDOW_EX_STYLE,
+            WS_EX_LAYERED, WS_EX_TOOLWINDOW,
         },
     },
 };
 
-use crate::error::XCapResult;
+use crate::{
+    alpha_mode::AlphaMode,
+    diagnostic::{report, Diagnostic, DiagnosticTarget},
+    error::{XCapError, XCapResult},
+    raw_handle::RawWindowHandle,
+    rect::Rect,
+};
 
 use super::{
-    capture::capture_window,
+    capture::{capture_monitor, capture_window},
+    dpi_awareness::DpiAwarenessGuard,
     impl_monitor::ImplMonitor,
+    ui_automation::{capture_scrollable, find_hwnd_by_automation_id},
     utils::{get_process_is_dpi_awareness, get_window_info, open_process},
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct ImplWindow {
     pub hwnd: HWND,
 }
 
+impl Hash for ImplWindow {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.hwnd.0 as isize).hash(state);
+    }
+}
+
+// A minimized window has no pixels to copy, and the DWM thumbnail can only be
+// composed onto another window by DWM, it can't be read back. So keep the last
+// frame captured while the window was visible and return it while minimized.
+// 最近使用的窗口在后面，容量为 0 时不缓存
+const DEFAULT_LAST_FRAMES_CAPACITY: usize = 8;
+
+struct LastFrames {
+    capacity: usize,
+    frames: VecDeque<(isize, RgbaImage)>,
+}
+
+static LAST_FRAMES: Mutex<LastFrames> = Mutex::new(LastFrames {
+    capacity: DEFAULT_LAST_FRAMES_CAPACITY,
+    frames: VecDeque::new(),
+});
+
+fn evict_last_frames(frames: &mut VecDeque<(isize, RgbaImage)>, capacity: usize) {
+    while frames.len() > capacity {
+        frames.pop_front();
+    }
+}
+
+/// Keep the last frame of up to `capacity` windows, so capturing a minimized window on
+/// Windows returns the frame captured while it was visible. Defaults to 8 windows, 0 disables
+/// the cache, since every capture then costs an extra copy of the image. The least recently
+/// captured windows are evicted first and frames of destroyed windows are dropped.
+pub fn set_minimized_frame_cache_capacity(capacity: usize) {
+    match LAST_FRAMES.lock() {
+        Ok(mut last_frames) => {
+            last_frames.capacity = capacity;
+            evict_last_frames(&mut last_frames.frames, capacity);
+        }
+        Err(err) => log::error!("Set minimized frame cache capacity failed: {}", err),
+    }
+}
+
+fn save_last_frame(hwnd: HWND, image: &RgbaImage) -> XCapResult<()> {
+    let mut last_frames = LAST_FRAMES.lock()?;
+
+    if last_frames.capacity == 0 {
+        return Ok(());
+    }
+
+    let capacity = last_frames.capacity;
+    let frames = &mut last_frames.frames;
+
+    // drop frames of windows that have been destroyed, and the previous frame of this window
+    frames.retain(|&(key, _)| {
+        key != hwnd.0 as isize && unsafe { IsWindow(Some(HWND(key as *mut c_void))).as_bool() }
+    });
+    frames.push_back((hwnd.0 as isize, image.clone()));
+    evict_last_frames(frames, capacity);
+
+    Ok(())
+}
+
+fn get_last_frame(hwnd: HWND) -> XCapResult<RgbaImage> {
+    let mut last_frames = LAST_FRAMES.lock()?;
+    let frames = &mut last_frames.frames;
+
+    let index = frames
+        .iter()
+        .position(|(key, _)| *key == hwnd.0 as isize)
+        .ok_or_else(|| XCapError::new("Window is minimized and has no previous frame"))?;
+
+    // 读取后移到最后，作为最近使用的窗口
+    let frame = frames
+        .remove(index)
+        .ok_or_else(|| XCapError::new("Window is minimized and has no previous frame"))?;
+    let image = frame.1.clone();
+    frames.push_back(frame);
+
+    Ok(image)
+}
+
 fn is_window_cloaked(hwnd: HWND) -> bool {
     unsafe {
         let mut cloaked = 0u32;
@@ -59,7 +162,38 @@ fn is_window_cloaked(hwnd: HWND) -> bool {
     }
 }
 
+// 窗口可见边框的范围，不包含 DWM 绘制的阴影
+fn get_window_frame_bounds(hwnd: HWND) -> XCapResult<RECT> {
+    let mut rect = RECT::default();
+
+    unsafe {
+        DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_EXTENDED_FRAME_BOUNDS,
+            &mut rect as *mut RECT as *mut c_void,
+            mem::size_of::<RECT>() as u32,
+        )?;
+    }
+
+    Ok(rect)
+}
+
 // https://webrtc.googlesource.com/src.git/+/refs/heads/main/modules/desktop_capture/win/window_capture_utils.cc#52
+// 主显示器与其他显示器上的任务栏
+const SHELL_CLASS_NAMES: [&str; 2] = ["Shell_TrayWnd", "Shell_SecondaryTrayWnd"];
+
+fn get_window_class_name(hwnd: HWND) -> String {
+    let mut lp_class_name = [0u16; MAX_PATH as usize];
+    let lp_class_name_length = unsafe { GetClassNameW(hwnd, &mut lp_class_name) } as usize;
+    if lp_class_name_length < 1 {
+        return String::new();
+    }
+
+    U16CString::from_vec_truncate(&lp_class_name[0..lp_class_name_length])
+        .to_string()
+        .unwrap_or_default()
+}
+
 fn is_valid_window(hwnd: HWND) -> bool {
     unsafe {
         // ignore invisible windows
@@ -74,15 +208,7 @@ fn is_valid_window(hwnd: HWND) -> bool {
         //   return TRUE;
         // }
 
-        let mut lp_class_name = [0u16; MAX_PATH as usize];
-        let lp_class_name_length = GetClassNameW(hwnd, &mut lp_class_name) as usize;
-        if lp_class_name_length < 1 {
-            return false;
-        }
-
-        let class_name = U16CString::from_vec_truncate(&lp_class_name[0..lp_class_name_length])
-            .to_string()
-            .unwrap_or_default();
+        let class_name = get_window_class_name(hwnd);
         if class_name.is_empty() {
             return false;
         }
@@ -129,19 +255,10 @@ fn is_valid_window(hwnd: HWND) -> bool {
             return false;
         }
 
-        let mut rect = RECT::default();
-
-        let get_rect_is_err = DwmGetWindowAttribute(
-            hwnd,
-            DWMWA_EXTENDED_FRAME_BOUNDS,
-            &mut rect as *mut RECT as *mut c_void,
-            mem::size_of::<RECT>() as u32,
-        )
-        .is_err();
-
-        if get_rect_is_err {
-            return false;
-        }
+        let rect = match get_window_frame_bounds(hwnd) {
+            Ok(rect) => rect,
+            Err(_) => return false,
+        };
 
         if IsRectEmpty(&rect).as_bool() {
             return false;
@@ -152,7 +269,7 @@ fn is_valid_window(hwnd: HWND) -> bool {
 }
 
 unsafe extern "system" fn enum_valid_windows(hwnd: HWND, state: LPARAM) -> BOOL {
-    let state = Box::leak(Box::from_raw(state.0 as *mut Vec<HWND>));
+    let state = &mut *(state.0 as *mut Vec<HWND>);
 
     if is_valid_window(hwnd) {
         state.push(hwnd);
@@ -161,8 +278,24 @@ unsafe extern "system" fn enum_valid_windows(hwnd: HWND, state: LPARAM) -> BOOL
     TRUE
 }
 
+struct LimitedWindows {
+    hwnds: Vec<HWND>,
+    max: usize,
+}
+
+unsafe extern "system" fn enum_valid_windows_limited(hwnd: HWND, state: LPARAM) -> BOOL {
+    let state = &mut *(state.0 as *mut LimitedWindows);
+
+    // 回调返回 FALSE 时 EnumWindows 也会返回错误，所以数量足够后只跳过检查，继续枚举
+    if state.hwnds.len() < state.max && is_valid_window(hwnd) {
+        state.hwnds.push(hwnd);
+    }
+
+    TRUE
+}
+
 unsafe extern "system" fn enum_all_windows(hwnd: HWND, state: LPARAM) -> BOOL {
-    let state = Box::leak(Box::from_raw(state.0 as *mut Vec<HWND>));
+    let state = &mut *(state.0 as *mut Vec<HWND>);
 
     state.push(hwnd);
 
@@ -193,10 +326,13 @@ fn get_module_basename(handle: HANDLE) -> XCapResult<String> {
         let result = GetModuleBaseNameW(handle, None, &mut module_base_name_w);
 
         if result == 0 {
-            log::error!(
-                "GetModuleBaseNameW({:?}) failed: {:?}",
-                handle,
-                GetLastError()
+            report(
+                Diagnostic::new(
+                    DiagnosticTarget::None,
+                    "GetModuleBaseNameW",
+                    format!("{:?}", handle),
+                )
+                .with_os_error_code(GetLastError().0 as i32),
             );
 
             GetModuleFileNameExW(Some(handle), None, &mut module_base_name_w);
@@ -208,6 +344,32 @@ fn get_module_basename(handle: HANDLE) -> XCapResult<String> {
     }
 }
 
+fn get_process_name(pid: u32) -> XCapResult<String> {
+    let scope_guard_handle = open_process(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)?;
+
+    unsafe {
+        let mut filename = [0u16; MAX_PATH as usize];
+        let mut filename_length = filename.len() as u32;
+
+        QueryFullProcessImageNameW(
+            *scope_guard_handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(filename.as_mut_ptr()),
+            &mut filename_length,
+        )?;
+
+        let path =
+            U16CString::from_vec_truncate(&filename[..filename_length as usize]).to_string()?;
+
+        let process_name = Path::new(&path)
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or(path);
+
+        Ok(process_name)
+    }
+}
+
 fn get_window_pid(hwnd: HWND) -> u32 {
     unsafe {
         let mut lp_dw_process_id = 0;
@@ -221,7 +383,11 @@ fn get_app_name(pid: u32) -> XCapResult<String> {
         let scope_guard_handle = match open_process(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
             Ok(box_handle) => box_handle,
             Err(err) => {
-                log::error!("open_process failed: {}", err);
+                report(Diagnostic::new(
+                    DiagnosticTarget::Process(pid),
+                    "open_process",
+                    err,
+                ));
                 return Ok(String::new());
             }
         };
@@ -233,10 +399,13 @@ fn get_app_name(pid: u32) -> XCapResult<String> {
 
         let file_version_info_size_w = GetFileVersionInfoSizeW(pcw_filename, None);
         if file_version_info_size_w == 0 {
-            log::error!(
-                "GetFileVersionInfoSizeW({:?}) failed: {:?}",
-                pcw_filename,
-                GetLastError()
+            report(
+                Diagnostic::new(
+                    DiagnosticTarget::Process(pid),
+                    "GetFileVersionInfoSizeW",
+                    pcw_filename.to_string().unwrap_or_default(),
+                )
+                .with_os_error_code(GetLastError().0 as i32),
             );
 
             return get_module_basename(*scope_guard_handle);
@@ -312,16 +481,47 @@ fn get_app_name(pid: u32) -> XCapResult<String> {
 }
 
 impl ImplWindow {
-    fn new(hwnd: HWND) -> ImplWindow {
+    pub fn new(hwnd: HWND) -> ImplWindow {
         ImplWindow { hwnd }
     }
 
+    pub fn from_raw_handle(raw_handle: RawWindowHandle) -> XCapResult<ImplWindow> {
+        let RawWindowHandle::Win32 { hwnd } = raw_handle else {
+            return Err(XCapError::new("Not a Win32 window handle"));
+        };
+
+        let hwnd = HWND(hwnd as *mut c_void);
+        if unsafe { !IsWindow(Some(hwnd)).as_bool() } {
+            return Err(XCapError::new("Invalid window handle"));
+        }
+
+        Ok(ImplWindow::new(hwnd))
+    }
+
+    pub fn from_automation_id(automation_id: &str) -> XCapResult<ImplWindow> {
+        let hwnd = find_hwnd_by_automation_id(automation_id)?;
+
+        Ok(ImplWindow::new(hwnd))
+    }
+
+    pub fn raw_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Win32 {
+            hwnd: self.hwnd.0 as isize,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        unsafe { IsWindow(Some(self.hwnd)).as_bool() }
+    }
+
     pub fn all() -> XCapResult<Vec<ImplWindow>> {
-        let hwnds_mut_ptr: *mut Vec<HWND> = Box::into_raw(Box::default());
+        let mut hwnds: Vec<HWND> = Vec::new();
 
-        let hwnds = unsafe {
-            EnumWindows(Some(enum_valid_windows), LPARAM(hwnds_mut_ptr as isize))?;
-            Box::from_raw(hwnds_mut_ptr)
+        unsafe {
+            EnumWindows(
+                Some(enum_valid_windows),
+                LPARAM(&mut hwnds as *mut Vec<HWND> as isize),
+            )?;
         };
 
         let mut impl_windows = Vec::new();
@@ -332,6 +532,62 @@ impl ImplWindow {
 
         Ok(impl_windows)
     }
+
+    pub fn all_limited(max: usize) -> XCapResult<Vec<ImplWindow>> {
+        let mut state = LimitedWindows {
+            hwnds: Vec::new(),
+            max,
+        };
+
+        unsafe {
+            EnumWindows(
+                Some(enum_valid_windows_limited),
+                LPARAM(&mut state as *mut LimitedWindows as isize),
+            )?;
+        };
+
+        Ok(state.hwnds.into_iter().map(ImplWindow::new).collect())
+    }
+
+    pub fn shell_surfaces() -> XCapResult<Vec<ImplWindow>> {
+        let mut hwnds: Vec<HWND> = Vec::new();
+
+        unsafe {
+            EnumWindows(
+                Some(enum_all_windows),
+                LPARAM(&mut hwnds as *mut Vec<HWND> as isize),
+            )?;
+        };
+
+        let impl_windows = hwnds
+            .into_iter()
+            .filter(|&hwnd| {
+                let is_visible = unsafe { IsWindowVisible(hwnd).as_bool() };
+                is_visible && SHELL_CLASS_NAMES.contains(&get_window_class_name(hwnd).as_str())
+            })
+            .map(ImplWindow::new)
+            .collect();
+
+        Ok(impl_windows)
+    }
+
+    pub fn all_spaces() -> XCapResult<Vec<ImplWindow>> {
+        ImplWindow::all()
+    }
+
+    pub fn all_including_untitled() -> XCapResult<Vec<ImplWindow>> {
+        ImplWindow::all()
+    }
+
+    pub fn foreground() -> XCapResult<Option<ImplWindow>> {
+        let hwnd = unsafe { GetForegroundWindow() };
+
+        if hwnd.is_invalid() {
+            return Ok(None);
+        }
+
+        Ok(Some(ImplWindow::new(hwnd)))
+    }
 }
 
 impl ImplWindow {
@@ -348,6 +604,10 @@ impl ImplWindow {
         get_app_name(self.pid()?)
     }
 
+    pub fn process_name(&self) -> XCapResult<String> {
+        get_process_name(self.pid()?)
+    }
+
     pub fn title(&self) -> XCapResult<String> {
         get_window_title(self.hwnd)
     }
@@ -358,6 +618,32 @@ impl ImplWindow {
         Ok(ImplMonitor::new(h_monitor))
     }
 
+    pub fn monitor_coverage(&self) -> XCapResult<Vec<(ImplMonitor, f32)>> {
+        let window_rect = Rect::new(self.x()?, self.y()?, self.width()?, self.height()?);
+        let window_area = window_rect.area();
+
+        let mut monitor_coverage = Vec::new();
+
+        if window_area == 0 {
+            return Ok(monitor_coverage);
+        }
+
+        for impl_monitor in ImplMonitor::all()? {
+            let monitor_rect = Rect::new(
+                impl_monitor.x()?,
+                impl_monitor.y()?,
+                impl_monitor.width()?,
+                impl_monitor.height()?,
+            );
+
+            if let Some(overlap) = window_rect.intersection(&monitor_rect) {
+                monitor_coverage.push((impl_monitor, overlap.area() as f32 / window_area as f32));
+            }
+        }
+
+        Ok(monitor_coverage)
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         let window_info = get_window_info(self.hwnd)?;
         Ok(window_info.rcClient.left)
@@ -369,12 +655,14 @@ impl ImplWindow {
     }
 
     pub fn z(&self) -> XCapResult<i32> {
-        let hwnds_mut_ptr: *mut Vec<HWND> = Box::into_raw(Box::default());
+        let mut hwnds: Vec<HWND> = Vec::new();
 
-        let hwnds = unsafe {
+        unsafe {
             // EnumWindows 函数按照 Z 顺序遍历顶层窗口，从最顶层的窗口开始，依次向下遍历。
-            EnumWindows(Some(enum_all_windows), LPARAM(hwnds_mut_ptr as isize))?;
-            Box::from_raw(hwnds_mut_ptr)
+            EnumWindows(
+                Some(enum_all_windows),
+                LPARAM(&mut hwnds as *mut Vec<HWND> as isize),
+            )?;
         };
 
         let mut z = hwnds.len() as i32;
@@ -410,14 +698,138 @@ impl ImplWindow {
         unsafe { Ok(GetForegroundWindow() == self.hwnd) }
     }
 
+    pub fn focus(&self) -> XCapResult<()> {
+        unsafe {
+            // 最小化的窗口需要先还原
+            if IsIconic(self.hwnd).as_bool() {
+                let _ = ShowWindow(self.hwnd, SW_RESTORE);
+            }
+
+            // 只有前台进程（或者刚接收过输入的进程）才能改变前台窗口
+            if !SetForegroundWindow(self.hwnd).as_bool() {
+                return Err(XCapError::new(
+                    "SetForegroundWindow failed, only the foreground process can change the foreground window",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_capture_protected(&self) -> XCapResult<bool> {
+        let mut affinity = 0u32;
+        unsafe { GetWindowDisplayAffinity(self.hwnd, &mut affinity)? };
+
+        // WDA_MONITOR 截图为黑色，WDA_EXCLUDEFROMCAPTURE 截图中不显示窗口
+        Ok(affinity != WDA_NONE.0)
+    }
+
+    pub fn opacity(&self) -> XCapResult<f32> {
+        unsafe {
+            let gwl_ex_style = WINDOW_EX_STYLE(GetWindowLongPtrW(self.hwnd, GWL_EXSTYLE) as u32);
+
+            // 只有分层窗口才能设置整体透明度
+            if !gwl_ex_style.contains(WS_EX_LAYERED) {
+                return Ok(1.0);
+            }
+
+            let mut alpha = 255u8;
+            let mut flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS::default();
+
+            // 使用 UpdateLayeredWindow 的窗口会调用失败，此时无法获取透明度
+            if GetLayeredWindowAttributes(self.hwnd, None, Some(&mut alpha), Some(&mut flags))
+                .is_err()
+            {
+                return Ok(1.0);
+            }
+
+            if !flags.contains(LWA_ALPHA) {
+                return Ok(1.0);
+            }
+
+            Ok(alpha as f32 / 255.0)
+        }
+    }
+
+    pub fn alpha_mode(&self) -> XCapResult<AlphaMode> {
+        unsafe {
+            let gwl_ex_style = WINDOW_EX_STYLE(GetWindowLongPtrW(self.hwnd, GWL_EXSTYLE) as u32);
+
+            // 分层窗口由 DWM 按预乘 alpha 合成，截图中半透明的像素也是预乘的，其他窗口是不透明的
+            if gwl_ex_style.contains(WS_EX_LAYERED) {
+                return Ok(AlphaMode::Premultiplied);
+            }
+
+            Ok(AlphaMode::Straight)
+        }
+    }
+
+    pub fn frame_offset(&self) -> XCapResult<(i32, i32)> {
+        let window_info = get_window_info(self.hwnd)?;
+        let frame_bounds = get_window_frame_bounds(self.hwnd)?;
+
+        Ok((
+            window_info.rcClient.left - frame_bounds.left,
+            window_info.rcClient.top - frame_bounds.top,
+        ))
+    }
+
+    pub fn child_rect_at(&self, x: i32, y: i32) -> XCapResult<Rect> {
+        let window_info = get_window_info(self.hwnd)?;
+        let screen_point = POINT {
+            x: window_info.rcClient.left + x,
+            y: window_info.rcClient.top + y,
+        };
+
+        // ChildWindowFromPointEx 只查找直接子窗口，需要逐层向下查找
+        let mut hwnd = self.hwnd;
+        loop {
+            let mut point = screen_point;
+            unsafe {
+                ScreenToClient(hwnd, &mut point).ok()?;
+            }
+
+            let child_hwnd = unsafe {
+                ChildWindowFromPointEx(hwnd, point, CWP_SKIPINVISIBLE | CWP_SKIPTRANSPARENT)
+            };
+
+            if child_hwnd.is_invalid() || child_hwnd == hwnd {
+                break;
+            }
+
+            hwnd = child_hwnd;
+        }
+
+        if hwnd == self.hwnd {
+            return Err(XCapError::new("Not found child window"));
+        }
+
+        let mut rect = RECT::default();
+        unsafe { GetWindowRect(hwnd, &mut rect)? };
+
+        Ok(Rect::new(
+            rect.left - window_info.rcClient.left,
+            rect.top - window_info.rcClient.top,
+            (rect.right - rect.left) as u32,
+            (rect.bottom - rect.top) as u32,
+        ))
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
+        if self.is_minimized()? {
+            return get_last_frame(self.hwnd);
+        }
+
+        // 进程不感知 DPI 时获取到的是虚拟化的坐标，截图的大小和位置会出错
+        let dpi_awareness_guard = DpiAwarenessGuard::new();
+
         // 在win10之后，不同窗口有不同的dpi，所以可能存在截图不全或者截图有较大空白，实际窗口没有填充满图片
-        // 如果窗口不感知dpi，那么就不需要缩放，如果当前进程感知dpi，那么也不需要缩放
+        // 如果窗口不感知dpi，那么就不需要缩放，如果当前线程感知dpi，那么也不需要缩放
         let scope_guard_handle =
             open_process(PROCESS_QUERY_LIMITED_INFORMATION, false, self.pid()?)?;
         let window_is_dpi_awareness = get_process_is_dpi_awareness(*scope_guard_handle)?;
-        let current_process_is_dpi_awareness =
-            unsafe { get_process_is_dpi_awareness(GetCurrentProcess())? };
+        let current_process_is_dpi_awareness = dpi_awareness_guard.is_active()
+            || unsafe { get_process_is_dpi_awareness(GetCurrentProcess())? };
 
         let scale_factor = if !window_is_dpi_awareness {
             1.0
@@ -427,6 +839,134 @@ impl ImplWindow {
             self.current_monitor()?.scale_factor()?
         };
 
-        capture_window(self.hwnd, scale_factor)
+        let image = capture_window(self.hwnd, scale_factor)?;
+        save_last_frame(self.hwnd, &image)?;
+
+        Ok(image)
+    }
+
+    pub fn capture_with_picker() -> XCapResult<RgbaImage> {
+        Err(XCapError::Unsupported(
+            "The window picker is only available on Linux (Wayland)".to_string(),
+        ))
+    }
+
+    pub fn capture_image_snapped(&self) -> XCapResult<(RgbaImage, Rect)> {
+        let _dpi_awareness_guard = DpiAwarenessGuard::new();
+
+        // 窗口坐标已经是整数像素，截图被裁剪到客户区
+        let image = self.capture_image()?;
+        let rc_client = get_window_info(self.hwnd)?.rcClient;
+
+        let pixel_rect = Rect::new(rc_client.left, rc_client.top, image.width(), image.height());
+
+        Ok((image, pixel_rect))
+    }
+
+    pub fn capture_scrollable(&self) -> XCapResult<RgbaImage> {
+        let _dpi_awareness_guard = DpiAwarenessGuard::new();
+
+        capture_scrollable(self.hwnd, |viewport| {
+            let image = self.capture_image()?;
+            // 截图被裁剪到窗口的客户区
+            let rc_client = get_window_info(self.hwnd)?.rcClient;
+
+            let viewport_rect = Rect::new(
+                viewport.left - rc_client.left,
+                viewport.top - rc_client.top,
+                (viewport.right - viewport.left).max(0) as u32,
+                (viewport.bottom - viewport.top).max(0) as u32,
+            );
+            let crop_rect = Rect::new(0, 0, image.width(), image.height())
+                .intersection(&viewport_rect)
+                .ok_or_else(|| XCapError::new("The scrollable area is outside the window"))?;
+
+            Ok(crop_imm(
+                &image,
+                crop_rect.x as u32,
+                crop_rect.y as u32,
+                crop_rect.width,
+                crop_rect.height,
+            )
+            .to_image())
+        })
+    }
+
+    pub fn clear_cache() -> XCapResult<()> {
+        LAST_FRAMES.lock()?.frames.clear();
+
+        Ok(())
+    }
+
+    pub fn capture_all(impl_windows: &[ImplWindow]) -> XCapResult<Vec<RgbaImage>> {
+        let dpi_awareness_guard = DpiAwarenessGuard::new();
+
+        // 当前线程不感知 dpi 时，窗口坐标会被虚拟化，无法与桌面截图对应，所以逐个截图
+        let current_process_is_dpi_awareness = dpi_awareness_guard.is_active()
+            || unsafe { get_process_is_dpi_awareness(GetCurrentProcess())? };
+
+        if !current_process_is_dpi_awareness {
+            return impl_windows
+                .iter()
+                .map(|impl_window| impl_window.capture_image())
+                .collect();
+        }
+
+        // 只截取一次整个虚拟桌面，再从中裁剪出每个窗口
+        let (left, top, width, height) = unsafe {
+            (
+                GetSystemMetrics(SM_XVIRTUALSCREEN),
+                GetSystemMetrics(SM_YVIRTUALSCREEN),
+                GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            )
+        };
+
+        let desktop_image = capture_monitor(left, top, width, height)?;
+        let mut images = Vec::with_capacity(impl_windows.len());
+
+        for impl_window in impl_windows {
+            let x = impl_window.x()? - left;
+            let y = impl_window.y()? - top;
+            let window_width = impl_window.width()?;
+            let window_height = impl_window.height()?;
+
+            // 最小化或者不完全在屏幕内的窗口，单独截图
+            let is_on_screen = !impl_window.is_minimized()?
+                && x >= 0
+                && y >= 0
+                && x + window_width as i32 <= width
+                && y + window_height as i32 <= height;
+
+            if !is_on_screen {
+                images.push(impl_window.capture_image()?);
+                continue;
+            }
+
+            let image = crop_imm(
+                &desktop_image,
+                x as u32,
+                y as u32,
+                window_width,
+                window_height,
+            )
+            .to_image();
+            save_last_frame(impl_window.hwnd, &image)?;
+
+            images.push(image);
+        }
+
+        Ok(images)
     }
 }
+
+#[test]
+fn evict_last_frames_drops_least_recent() {
+    let mut frames: VecDeque<(isize, RgbaImage)> =
+        (0..4).map(|key| (key, RgbaImage::new(1, 1))).collect();
+
+    evict_last_frames(&mut frames, 2);
+
+    let keys: Vec<isize> = frames.iter().map(|(key, _)| *key).collect();
+    assert_eq!(keys, vec![2, 3]);
+}
@@ -0,0 +1,175 @@
+use std::{path::Path, ptr, slice};
+
+use image::RgbaImage;
+use windows::{
+    core::{GUID, HSTRING},
+    Win32::{
+        Media::MediaFoundation::{
+            IMFMediaType, IMFSinkWriter, MFCreateMediaType, MFCreateMemoryBuffer, MFCreateSample,
+            MFCreateSinkWriterFromURL, MFMediaType_Video, MFShutdown, MFStartup,
+            MFVideoFormat_H264, MFVideoFormat_RGB32, MFVideoInterlace_Progressive, MFSTARTUP_FULL,
+            MF_MT_AVG_BITRATE, MF_MT_DEFAULT_STRIDE, MF_MT_FRAME_RATE, MF_MT_FRAME_SIZE,
+            MF_MT_INTERLACE_MODE, MF_MT_MAJOR_TYPE, MF_MT_PIXEL_ASPECT_RATIO, MF_MT_SUBTYPE,
+            MF_VERSION,
+        },
+        System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED},
+    },
+};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    video_config::{VideoCodec, VideoConfig},
+    video_file::VideoEncoder,
+};
+
+// Media Foundation 的时间单位为 100 纳秒
+const MF_TIME_UNIT: i64 = 10_000_000;
+
+/// MFStartup 和 CoInitializeEx 成功后才创建，释放时按相反顺序关闭
+struct MediaFoundationRuntime {
+    is_com_initialized: bool,
+}
+
+impl MediaFoundationRuntime {
+    fn new() -> XCapResult<MediaFoundationRuntime> {
+        unsafe {
+            // 当前线程已经以其他模式初始化 COM 时会失败，此时仍然可以使用 COM
+            let is_com_initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+
+            if let Err(err) = MFStartup(MF_VERSION, MFSTARTUP_FULL) {
+                if is_com_initialized {
+                    CoUninitialize();
+                }
+                return Err(err.into());
+            }
+
+            Ok(MediaFoundationRuntime { is_com_initialized })
+        }
+    }
+}
+
+impl Drop for MediaFoundationRuntime {
+    fn drop(&mut self) {
+        unsafe {
+            if let Err(err) = MFShutdown() {
+                log::error!("MFShutdown failed: {}", err);
+            }
+            if self.is_com_initialized {
+                CoUninitialize();
+            }
+        }
+    }
+}
+
+// MF_MT_FRAME_SIZE 等属性把两个 u32 放在一个 u64 中，高位在前
+fn pack_u32_pair(high: u32, low: u32) -> u64 {
+    ((high as u64) << 32) | low as u64
+}
+
+fn create_video_media_type(
+    subtype: &GUID,
+    width: u32,
+    height: u32,
+    fps: u32,
+) -> XCapResult<IMFMediaType> {
+    unsafe {
+        let media_type = MFCreateMediaType()?;
+        media_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video)?;
+        media_type.SetGUID(&MF_MT_SUBTYPE, subtype)?;
+        media_type.SetUINT32(&MF_MT_INTERLACE_MODE, MFVideoInterlace_Progressive.0 as u32)?;
+        media_type.SetUINT64(&MF_MT_FRAME_SIZE, pack_u32_pair(width, height))?;
+        media_type.SetUINT64(&MF_MT_FRAME_RATE, pack_u32_pair(fps, 1))?;
+        media_type.SetUINT64(&MF_MT_PIXEL_ASPECT_RATIO, pack_u32_pair(1, 1))?;
+
+        Ok(media_type)
+    }
+}
+
+/// 使用 Media Foundation 的 Sink Writer 编码 H.264，容器由文件扩展名决定
+pub struct MediaFoundationEncoder {
+    sink_writer: IMFSinkWriter,
+    stream_index: u32,
+    width: u32,
+    height: u32,
+    fps: u32,
+    // 最后释放
+    _runtime: MediaFoundationRuntime,
+}
+
+impl MediaFoundationEncoder {
+    pub fn new(
+        path: &Path,
+        width: u32,
+        height: u32,
+        config: &VideoConfig,
+    ) -> XCapResult<MediaFoundationEncoder> {
+        if config.codec != VideoCodec::H264 {
+            return Err(XCapError::Unsupported(format!(
+                "Media Foundation can't encode {:?}",
+                config.codec
+            )));
+        }
+
+        let runtime = MediaFoundationRuntime::new()?;
+
+        unsafe {
+            let sink_writer = MFCreateSinkWriterFromURL(&HSTRING::from(path), None, None)?;
+
+            let output_type =
+                create_video_media_type(&MFVideoFormat_H264, width, height, config.fps)?;
+            output_type.SetUINT32(&MF_MT_AVG_BITRATE, config.bitrate)?;
+            let stream_index = sink_writer.AddStream(&output_type)?;
+
+            // RGB32 默认自下而上存储，设置正的 stride 表示自上而下
+            let input_type =
+                create_video_media_type(&MFVideoFormat_RGB32, width, height, config.fps)?;
+            input_type.SetUINT32(&MF_MT_DEFAULT_STRIDE, width * 4)?;
+            sink_writer.SetInputMediaType(stream_index, &input_type, None)?;
+
+            sink_writer.BeginWriting()?;
+
+            Ok(MediaFoundationEncoder {
+                sink_writer,
+                stream_index,
+                width,
+                height,
+                fps: config.fps,
+                _runtime: runtime,
+            })
+        }
+    }
+}
+
+impl VideoEncoder for MediaFoundationEncoder {
+    fn write_frame(&mut self, image: &RgbaImage, index: u64) -> XCapResult<()> {
+        let length = self.width * self.height * 4;
+
+        unsafe {
+            let buffer = MFCreateMemoryBuffer(length)?;
+
+            let mut data = ptr::null_mut();
+            buffer.Lock(&mut data, None, None)?;
+            let bgrx = slice::from_raw_parts_mut(data, length as usize);
+            for (bgrx, rgba) in bgrx.chunks_exact_mut(4).zip(image.as_raw().chunks_exact(4)) {
+                bgrx.copy_from_slice(&[rgba[2], rgba[1], rgba[0], 255]);
+            }
+            buffer.Unlock()?;
+            buffer.SetCurrentLength(length)?;
+
+            let sample = MFCreateSample()?;
+            sample.AddBuffer(&buffer)?;
+            sample.SetSampleTime(index as i64 * MF_TIME_UNIT / self.fps as i64)?;
+            sample.SetSampleDuration(MF_TIME_UNIT / self.fps as i64)?;
+
+            self.sink_writer.WriteSample(self.stream_index, &sample)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> XCapResult<()> {
+        unsafe { self.sink_writer.Finalize()? };
+
+        Ok(())
+    }
+}
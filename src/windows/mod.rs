@@ -1,6 +1,17 @@
 mod capture;
+mod dxgi_capture;
+mod ui_automation;
 mod utils;
 
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+pub mod clock;
+pub mod cursor;
+pub mod dpi_awareness;
+pub mod impl_foreground_watcher;
+#[cfg(feature = "hotkey")]
+pub mod impl_hotkey_listener;
 pub mod impl_monitor;
 pub mod impl_video_recorder;
 pub mod impl_window;
+pub mod media_foundation;
@@ -0,0 +1,202 @@
+use image::RgbaImage;
+use scopeguard::defer;
+use windows::{
+    core::BSTR,
+    Win32::{
+        Foundation::{HWND, RECT},
+        System::Com::{
+            CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+            COINIT_MULTITHREADED,
+        },
+        System::Variant::VARIANT,
+        UI::Accessibility::{
+            CUIAutomation, IUIAutomation, IUIAutomationElement, IUIAutomationScrollPattern,
+            ScrollAmount_LargeIncrement, ScrollAmount_NoAmount, TreeScope_Descendants,
+            TreeScope_Subtree, UIA_AutomationIdPropertyId, UIA_IsScrollPatternAvailablePropertyId,
+            UIA_ScrollPatternId, UIA_ScrollPatternNoScroll,
+            UIA_ScrollVerticallyScrollablePropertyId,
+        },
+    },
+};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    scroll_capture::{capture_scrolled, ScrollContainer},
+};
+
+fn get_native_window_handle(
+    automation: &IUIAutomation,
+    element: IUIAutomationElement,
+) -> XCapResult<HWND> {
+    unsafe {
+        // 没有独立窗口的控件，使用最近的拥有窗口句柄的祖先元素
+        let tree_walker = automation.ControlViewWalker()?;
+        let mut element = element;
+
+        loop {
+            let hwnd = element.CurrentNativeWindowHandle()?;
+            if !hwnd.is_invalid() {
+                return Ok(hwnd);
+            }
+
+            element = tree_walker.GetParentElement(&element)?;
+        }
+    }
+}
+
+/// 通过 UI Automation 的 AutomationId 查找元素所在的窗口
+pub(super) fn find_hwnd_by_automation_id(automation_id: &str) -> XCapResult<HWND> {
+    unsafe {
+        // 当前线程已经以其他模式初始化 COM 时会失败，此时仍然可以使用 COM
+        let is_com_initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+        defer! {
+            if is_com_initialized {
+                CoUninitialize();
+            }
+        };
+
+        let automation: IUIAutomation =
+            CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER)?;
+        let root_element = automation.GetRootElement()?;
+
+        let condition = automation.CreatePropertyCondition(
+            UIA_AutomationIdPropertyId,
+            &VARIANT::from(BSTR::from(automation_id)),
+        )?;
+
+        let element = root_element
+            .FindFirst(TreeScope_Descendants, &condition)
+            .map_err(|err| {
+                XCapError::NotFound(format!(
+                    "Not found element with AutomationId {}: {}",
+                    automation_id, err
+                ))
+            })?;
+
+        get_native_window_handle(&automation, element)
+    }
+}
+
+fn rect_area(rect: &RECT) -> i64 {
+    (rect.right - rect.left).max(0) as i64 * (rect.bottom - rect.top).max(0) as i64
+}
+
+/// 窗口中面积最大的可以纵向滚动的元素
+fn find_vertical_scroll_element(
+    automation: &IUIAutomation,
+    hwnd: HWND,
+) -> XCapResult<IUIAutomationElement> {
+    unsafe {
+        let window_element = automation.ElementFromHandle(hwnd)?;
+
+        let condition = automation.CreateAndCondition(
+            &automation.CreatePropertyCondition(
+                UIA_IsScrollPatternAvailablePropertyId,
+                &VARIANT::from(true),
+            )?,
+            &automation.CreatePropertyCondition(
+                UIA_ScrollVerticallyScrollablePropertyId,
+                &VARIANT::from(true),
+            )?,
+        )?;
+        let elements = window_element.FindAll(TreeScope_Subtree, &condition)?;
+
+        let mut largest_element: Option<(i64, IUIAutomationElement)> = None;
+        for index in 0..elements.Length()? {
+            let element = elements.GetElement(index)?;
+            let area = rect_area(&element.CurrentBoundingRectangle()?);
+
+            match largest_element {
+                Some((largest_area, _)) if largest_area >= area => {}
+                _ => largest_element = Some((area, element)),
+            }
+        }
+
+        largest_element
+            .map(|(_, element)| element)
+            .ok_or_else(|| XCapError::Unsupported("The window has no scrollable area".to_string()))
+    }
+}
+
+struct UiaScrollContainer<F> {
+    element: IUIAutomationElement,
+    scroll_pattern: IUIAutomationScrollPattern,
+    original_percent: f64,
+    capture_viewport: F,
+}
+
+impl<F> ScrollContainer for UiaScrollContainer<F>
+where
+    F: FnMut(&RECT) -> XCapResult<RgbaImage>,
+{
+    fn capture_viewport(&mut self) -> XCapResult<RgbaImage> {
+        // 每次重新获取位置，截图期间窗口可能被移动
+        let viewport = unsafe { self.element.CurrentBoundingRectangle()? };
+
+        (self.capture_viewport)(&viewport)
+    }
+
+    fn scroll_to_top(&mut self) -> XCapResult<()> {
+        unsafe {
+            self.scroll_pattern
+                .SetScrollPercent(UIA_ScrollPatternNoScroll, 0.0)?
+        };
+
+        Ok(())
+    }
+
+    fn scroll_down(&mut self) -> XCapResult<bool> {
+        unsafe {
+            let percent = self.scroll_pattern.CurrentVerticalScrollPercent()?;
+            if percent >= 100.0 {
+                return Ok(false);
+            }
+
+            self.scroll_pattern
+                .Scroll(ScrollAmount_NoAmount, ScrollAmount_LargeIncrement)?;
+
+            Ok(self.scroll_pattern.CurrentVerticalScrollPercent()? != percent)
+        }
+    }
+
+    fn restore(&mut self) -> XCapResult<()> {
+        unsafe {
+            self.scroll_pattern
+                .SetScrollPercent(UIA_ScrollPatternNoScroll, self.original_percent)?
+        };
+
+        Ok(())
+    }
+}
+
+/// 通过 UI Automation 的 ScrollPattern 滚动窗口中最大的可滚动区域并拼接截图，
+/// `capture_viewport` 截取屏幕坐标中的可滚动区域
+pub(super) fn capture_scrollable<F>(hwnd: HWND, capture_viewport: F) -> XCapResult<RgbaImage>
+where
+    F: FnMut(&RECT) -> XCapResult<RgbaImage>,
+{
+    unsafe {
+        let is_com_initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+        defer! {
+            if is_com_initialized {
+                CoUninitialize();
+            }
+        };
+
+        let automation: IUIAutomation =
+            CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER)?;
+        let element = find_vertical_scroll_element(&automation, hwnd)?;
+        let scroll_pattern =
+            element.GetCurrentPatternAs::<IUIAutomationScrollPattern>(UIA_ScrollPatternId)?;
+        let original_percent = scroll_pattern.CurrentVerticalScrollPercent()?;
+
+        let mut scroll_container = UiaScrollContainer {
+            element,
+            scroll_pattern,
+            original_percent,
+            capture_viewport,
+        };
+
+        capture_scrolled(&mut scroll_container)
+    }
+}
@@ -1,4 +1,4 @@
-use std::mem;
+use std::{mem, thread};
 
 use image::RgbaImage;
 use scopeguard::{guard, ScopeGuard};
@@ -13,11 +13,17 @@ use windows::{
             DISPLAYCONFIG_SOURCE_DEVICE_NAME, DISPLAYCONFIG_TARGET_DEVICE_NAME,
             QDC_ONLY_ACTIVE_PATHS,
         },
-        Foundation::{CloseHandle, FreeLibrary, GetLastError, HANDLE, HMODULE, HWND},
+        Foundation::{
+            CloseHandle, FreeLibrary, E_ACCESSDENIED, GENERIC_ALL, HANDLE, HMODULE, HWND, MAX_PATH,
+        },
         Graphics::Gdi::MONITORINFOEXW,
         System::{
             LibraryLoader::{GetProcAddress, LoadLibraryW},
-            Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ},
+            Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_BINARY, RRF_RT_REG_SZ},
+            StationsAndDesktops::{
+                CloseDesktop, GetUserObjectInformationW, OpenInputDesktop, SetThreadDesktop,
+                DESKTOP_ACCESS_FLAGS, DESKTOP_CONTROL_FLAGS, DESKTOP_READOBJECTS, UOI_NAME,
+            },
             Threading::{OpenProcess, PROCESS_ACCESS_RIGHTS},
         },
         UI::WindowsAndMessaging::{GetWindowInfo, WINDOWINFO},
@@ -82,6 +88,19 @@ pub(super) fn bgra_to_rgba(mut buffer: Vec<u8>) -> Vec<u8> {
     buffer
 }
 
+/// Windows 7 及以下版本的 alpha 通道为 0，设置为不透明
+pub(super) fn fix_bgra_alpha(buffer: &mut [u8]) {
+    if get_os_major_version() >= 8 {
+        return;
+    }
+
+    for src in buffer.chunks_exact_mut(4) {
+        if src[3] == 0 {
+            src[3] = 255;
+        }
+    }
+}
+
 pub(super) fn bgra_to_rgba_image(
     width: u32,
     height: u32,
@@ -123,10 +142,10 @@ pub(super) fn load_library(
         let hmodule = LoadLibraryW(lib_filename)?;
 
         if hmodule.is_invalid() {
-            return Err(XCapError::new(format!(
-                "LoadLibraryW error {:?}",
-                GetLastError()
-            )));
+            return Err(XCapError::with_source(
+                "LoadLibraryW failed",
+                windows::core::Error::from_win32(),
+            ));
         }
 
         let scope_guard_hmodule = guard(hmodule, |val| {
@@ -148,10 +167,10 @@ pub(super) fn open_process(
         let handle = OpenProcess(dw_desired_access, b_inherit_handle, dw_process_id)?;
 
         if handle.is_invalid() {
-            return Err(XCapError::new(format!(
-                "OpenProcess error {:?}",
-                GetLastError()
-            )));
+            return Err(XCapError::with_source(
+                "OpenProcess failed",
+                windows::core::Error::from_win32(),
+            ));
         }
 
         let scope_guard_handle = guard(handle, |val| {
@@ -164,6 +183,43 @@ pub(super) fn open_process(
     }
 }
 
+pub(super) fn get_monitor_edid(monitor_device_path: &str) -> XCapResult<Vec<u8>> {
+    // monitorDevicePath 形如 \\?\DISPLAY#DEL4093#5&2b1e8b8&0&UID4352#{...}，
+    // 对应注册表 SYSTEM\CurrentControlSet\Enum\DISPLAY\DEL4093\5&2b1e8b8&0&UID4352
+    let parts: Vec<&str> = monitor_device_path.split('#').collect();
+    if parts.len() < 3 {
+        return Err(XCapError::new(format!(
+            "Invalid monitor device path {}",
+            monitor_device_path
+        )));
+    }
+
+    let sub_key = U16CString::from_str_truncate(format!(
+        r"SYSTEM\CurrentControlSet\Enum\DISPLAY\{}\{}\Device Parameters",
+        parts[1], parts[2]
+    ));
+
+    unsafe {
+        let mut buf_len: u32 = 1024;
+        let mut buf = vec![0u8; buf_len as usize];
+
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            PCWSTR(sub_key.as_ptr()),
+            w!("EDID"),
+            RRF_RT_REG_BINARY,
+            None,
+            Some(buf.as_mut_ptr().cast()),
+            Some(&mut buf_len),
+        )
+        .ok()?;
+
+        buf.truncate(buf_len as usize);
+
+        Ok(buf)
+    }
+}
+
 pub(super) fn get_monitor_config(
     monitor_info_ex_w: MONITORINFOEXW,
 ) -> XCapResult<DISPLAYCONFIG_TARGET_DEVICE_NAME> {
@@ -230,6 +286,85 @@ pub(super) fn get_monitor_config(
     }
 }
 
+pub(super) fn is_secure_desktop() -> bool {
+    unsafe {
+        // 安全桌面（UAC 提示、锁屏）激活时，普通进程无法打开输入桌面
+        let hdesk = match OpenInputDesktop(DESKTOP_CONTROL_FLAGS(0), false, DESKTOP_READOBJECTS) {
+            Ok(hdesk) => hdesk,
+            Err(err) => {
+                log::info!("OpenInputDesktop failed: {}", err);
+                return true;
+            }
+        };
+
+        let scope_guard_hdesk = guard(hdesk, |val| {
+            if let Err(err) = CloseDesktop(val) {
+                log::error!("CloseDesktop {:?} failed {:?}", val, err);
+            }
+        });
+
+        let mut name = [0u16; MAX_PATH as usize];
+        let is_get_name_failed = GetUserObjectInformationW(
+            HANDLE(scope_guard_hdesk.0),
+            UOI_NAME,
+            Some(name.as_mut_ptr().cast()),
+            mem::size_of_val(&name) as u32,
+            None,
+        )
+        .is_err();
+
+        if is_get_name_failed {
+            return false;
+        }
+
+        let name = U16CString::from_vec_truncate(name)
+            .to_string()
+            .unwrap_or_default();
+
+        // 普通用户桌面名称为 Default，安全桌面为 Winlogon
+        !name.eq_ignore_ascii_case("Default")
+    }
+}
+
+/// 在新线程中切换到当前的输入桌面（锁屏、UAC 提示时为 Winlogon）后执行 f，
+/// 需要以 SYSTEM 身份运行在用户所在的会话中才有权限打开 Winlogon 桌面
+pub(super) fn run_on_input_desktop<T: Send>(
+    f: impl FnOnce() -> XCapResult<T> + Send,
+) -> XCapResult<T> {
+    thread::scope(|scope| {
+        scope
+            .spawn(|| unsafe {
+                let hdesk = OpenInputDesktop(
+                    DESKTOP_CONTROL_FLAGS(0),
+                    false,
+                    DESKTOP_ACCESS_FLAGS(GENERIC_ALL.0),
+                )
+                .map_err(|err| {
+                    if err.code() == E_ACCESSDENIED {
+                        XCapError::PermissionDenied(
+                            "Opening the secure desktop requires running as SYSTEM".to_string(),
+                        )
+                    } else {
+                        XCapError::from(err)
+                    }
+                })?;
+
+                let scope_guard_hdesk = guard(hdesk, |val| {
+                    if let Err(err) = CloseDesktop(val) {
+                        log::error!("CloseDesktop {:?} failed {:?}", val, err);
+                    }
+                });
+
+                // 新线程还没有创建窗口，可以切换线程所在的桌面
+                SetThreadDesktop(*scope_guard_hdesk)?;
+
+                f()
+            })
+            .join()
+            .map_err(|_| XCapError::new("Secure desktop capture thread panicked"))?
+    })
+}
+
 pub fn get_window_info(hwnd: HWND) -> XCapResult<WINDOWINFO> {
     let mut window_info = WINDOWINFO {
         cbSize: mem::size_of::<WINDOWINFO>() as u32,